@@ -11,17 +11,12 @@ else
     BUILD_TYPE="debug"
 fi
 
-# flavor
-if [ "$3" == "emu" ]; then
-    FEATURES="--features emu"
-fi
-
 function build() {
     # if release add --release
     if [ "$BUILD_TYPE" == "release" ]; then
-        cargo build --target=$TARGET_ARCH --release --verbose $FEATURES
+        cargo build --target=$TARGET_ARCH --release --verbose
     else
-        cargo build --target=$TARGET_ARCH --verbose $FEATURES
+        cargo build --target=$TARGET_ARCH --verbose
     fi
 }
 
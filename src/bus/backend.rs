@@ -0,0 +1,55 @@
+use socketcan::CanDataFrame;
+
+use super::{
+    can, emu, replay, slcan, CanBackend, CanBusHealth, CanConfig, CanInterfaceError,
+    CanInterfaceTrait, CanRxFrame, CanStats,
+};
+
+/// CAN interface of the backend selected by the configuration of the bus
+pub type DynCanInterface = Box<dyn CanInterfaceTrait>;
+
+#[async_trait]
+impl CanInterfaceTrait for DynCanInterface {
+    async fn new(config: &CanConfig) -> Result<Self, CanInterfaceError> {
+        let iface: DynCanInterface = match config.backend {
+            CanBackend::Socketcan => Box::new(can::CanInterface::new(config).await?),
+            CanBackend::Emulated => Box::new(emu::CanInterface::new(config).await?),
+            CanBackend::Replay => Box::new(replay::CanInterface::new(config).await?),
+            CanBackend::Slcan => Box::new(slcan::CanInterface::new(config).await?),
+            #[cfg(feature = "grpc-can-iface-client")]
+            CanBackend::Grpc => Box::new(super::remote::CanInterface::new(config).await?),
+            #[cfg(not(feature = "grpc-can-iface-client"))]
+            CanBackend::Grpc => return Err(CanInterfaceError::UnsupportedBackend(config.backend)),
+        };
+
+        Ok(iface)
+    }
+
+    async fn send(&mut self, frame: CanDataFrame) -> Result<(), CanInterfaceError> {
+        self.as_mut().send(frame).await
+    }
+
+    async fn recv_poll(&mut self) -> Option<CanRxFrame> {
+        self.as_mut().recv_poll().await
+    }
+
+    fn get_stats(&self) -> CanStats {
+        self.as_ref().get_stats()
+    }
+
+    fn get_health(&self) -> CanBusHealth {
+        self.as_ref().get_health()
+    }
+
+    fn is_bus_off(&self) -> bool {
+        self.as_ref().is_bus_off()
+    }
+
+    fn restart(&mut self) -> Result<(), CanInterfaceError> {
+        self.as_mut().restart()
+    }
+
+    fn ioctl(&mut self, cmd: u32, arg: u32) -> Result<(), CanInterfaceError> {
+        self.as_mut().ioctl(cmd, arg)
+    }
+}
@@ -0,0 +1,257 @@
+use std::{
+    ops::Deref,
+    time::{Duration, Instant},
+};
+
+use chrono::{DateTime, Utc};
+use futures::future::select_all;
+use serde::{de, Deserialize, Deserializer, Serialize};
+
+use socketcan::CanDataFrame;
+
+use super::{
+    CanBackend, CanBusHealth, CanBusSniffedFrames, CanConfig, CanDirection, CanInterfaceError,
+    CanInterfaceTrait, CanRecorder, CanRxFrame, CanSniffer, CanStats, CanTxQueue, CanTxQueueStats,
+    TxPriority,
+};
+use crate::controller::DeviceAlert;
+
+/// Index of a bus in the configured list of buses
+pub type CanBusId = usize;
+
+/// Configured CAN buses, either a single `[can]` table or a list of `[[can]]` tables
+#[derive(Serialize, Clone, Debug)]
+#[serde(transparent)]
+pub struct CanBusesConfig(pub Vec<CanConfig>);
+
+impl Default for CanBusesConfig {
+    fn default() -> Self {
+        Self(vec![CanConfig::default()])
+    }
+}
+
+impl<'de> Deserialize<'de> for CanBusesConfig {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Buses {
+            Single(CanConfig),
+            Multiple(Vec<CanConfig>),
+        }
+
+        let buses = match Buses::deserialize(deserializer)? {
+            Buses::Single(config) => vec![config],
+            Buses::Multiple(configs) => configs,
+        };
+
+        if buses.is_empty() {
+            return Err(de::Error::custom("At least one CAN bus must be configured"));
+        }
+
+        for (i, bus) in buses.iter().enumerate() {
+            if buses[..i]
+                .iter()
+                .any(|other| other.get_name() == bus.get_name())
+            {
+                return Err(de::Error::custom(format!(
+                    "Duplicate CAN bus name {}",
+                    bus.get_name()
+                )));
+            }
+        }
+
+        Ok(Self(buses))
+    }
+}
+
+impl Deref for CanBusesConfig {
+    type Target = [CanConfig];
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+#[derive(Serialize, Debug, Clone, Default)]
+pub struct CanBusStats {
+    pub name: String,
+    pub stats: CanStats,
+    pub tx_queue: CanTxQueueStats,
+    pub health: CanBusHealth,
+}
+
+/// Named CAN bus handled by the controller
+pub struct CanBus<IF: CanInterfaceTrait> {
+    pub name: String,
+    pub backend: CanBackend,
+    pub iface: IF,
+
+    // Record of the frames received and sent on the bus
+    pub recorder: Option<CanRecorder>,
+
+    // Frames which are not CANIOT responses, in sniff mode
+    pub sniffer: Option<CanSniffer>,
+
+    // Frames waiting to be sent
+    tx_queue: CanTxQueue,
+
+    // Next attempt to restart the controller, while the bus is off
+    restart_at: Option<Instant>,
+}
+
+impl<IF: CanInterfaceTrait> CanBus<IF> {
+    pub async fn open(config: &CanConfig) -> Result<Self, CanInterfaceError> {
+        let iface = IF::new(config).await?;
+        let recorder = config
+            .record
+            .as_ref()
+            .map(|record_config| CanRecorder::open(record_config, &config.interface))
+            .transpose()?;
+
+        Ok(Self {
+            name: config.get_name().to_string(),
+            backend: config.backend,
+            iface,
+            recorder,
+            sniffer: config.sniff.as_ref().map(CanSniffer::new),
+            tx_queue: CanTxQueue::new(config.tx.clone()),
+            restart_at: None,
+        })
+    }
+
+    pub fn get_stats(&self) -> CanBusStats {
+        CanBusStats {
+            name: self.name.clone(),
+            stats: self.iface.get_stats(),
+            tx_queue: self.tx_queue.stats,
+            health: self.iface.get_health(),
+        }
+    }
+
+    /// Frames kept by the sniffer, None if the bus is not in sniff mode
+    pub fn get_sniffed_frames(&self) -> Option<CanBusSniffedFrames> {
+        self.sniffer.as_ref().map(|sniffer| CanBusSniffedFrames {
+            name: self.name.clone(),
+            stats: sniffer.stats,
+            frames: sniffer.get_frames(),
+        })
+    }
+
+    pub fn get_alert(&self, now: &DateTime<Utc>) -> DeviceAlert {
+        self.iface.get_health().get_alert(&self.name, now)
+    }
+
+    /// Queue the frame to be sent by flush()
+    pub fn send(
+        &mut self,
+        frame: CanDataFrame,
+        priority: TxPriority,
+    ) -> Result<(), CanInterfaceError> {
+        if self.tx_queue.push(frame, priority) {
+            Ok(())
+        } else {
+            Err(CanInterfaceError::TxQueueFull)
+        }
+    }
+
+    /// Send the frames which are due, unless the bus is off, returns the frames sent
+    pub async fn flush(&mut self, now: &Instant) -> Vec<CanDataFrame> {
+        let mut sent = Vec::new();
+        if self.handle_bus_off(now) {
+            return sent;
+        }
+
+        while let Some(frame) = self.tx_queue.peek(now).cloned() {
+            match self.iface.send(frame.clone()).await {
+                Ok(()) => {
+                    if let Some(recorder) = self.recorder.as_mut() {
+                        recorder.record(CanDirection::Tx, &frame);
+                    }
+                    self.tx_queue.sent(now);
+                    sent.push(frame);
+                }
+                Err(err) if err.is_buffer_full() => {
+                    self.tx_queue.retry(now);
+                    break;
+                }
+                Err(err) => {
+                    error!("Failed to send frame on bus {}: {}", self.name, err);
+                    self.tx_queue.drop_next();
+                }
+            }
+        }
+
+        sent
+    }
+
+    /// Frames queued by send() and dropped since the last call, they will never be sent
+    pub fn take_dropped(&mut self) -> Vec<CanDataFrame> {
+        self.tx_queue.take_dropped()
+    }
+
+    /// Time until flush() has frames to send or the controller to restart
+    pub fn time_to_flush(&self, now: &Instant) -> Option<Duration> {
+        match self.restart_at {
+            Some(restart_at) => Some(restart_at.saturating_duration_since(*now)),
+            None => self.tx_queue.time_to_next(now),
+        }
+    }
+
+    // Keep the frames queued while the bus is off and restart the controller
+    // periodically, returns whether the bus is off
+    fn handle_bus_off(&mut self, now: &Instant) -> bool {
+        if !self.iface.is_bus_off() {
+            if self.restart_at.take().is_some() {
+                info!("Bus {} recovered from bus-off", self.name);
+            }
+            return false;
+        }
+
+        let restart_delay =
+            Duration::from_millis(self.tx_queue.config().bus_off_restart_delay as u64);
+
+        match self.restart_at {
+            None => {
+                warn!(
+                    "Bus {} is bus-off, holding {} frames",
+                    self.name,
+                    self.tx_queue.len()
+                );
+                self.tx_queue.stats.bus_off += 1;
+                self.restart_at = Some(*now + restart_delay);
+            }
+            Some(restart_at) if *now >= restart_at => {
+                match self.iface.restart() {
+                    Ok(()) => {
+                        info!("Bus {} restarted", self.name);
+                        self.tx_queue.stats.restarts += 1;
+                    }
+                    Err(err) => error!("Failed to restart bus {}: {}", self.name, err),
+                }
+                self.restart_at = Some(*now + restart_delay);
+            }
+            Some(_) => {}
+        }
+
+        let bus_off = self.iface.is_bus_off();
+        if !bus_off {
+            self.restart_at = None;
+        }
+        bus_off
+    }
+}
+
+/// Wait for a frame received on any of the buses
+pub async fn recv_poll_any<IF: CanInterfaceTrait>(
+    buses: &mut [CanBus<IF>],
+) -> Option<(CanBusId, CanRxFrame)> {
+    if buses.is_empty() {
+        return futures::future::pending().await;
+    }
+
+    let (frame, bus, _) = select_all(buses.iter_mut().map(|bus| bus.iface.recv_poll())).await;
+    frame.map(|frame| (bus, frame))
+}
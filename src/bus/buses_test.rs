@@ -0,0 +1,60 @@
+use serde::Deserialize;
+
+use super::CanBusesConfig;
+
+#[derive(Deserialize)]
+struct Config {
+    can: CanBusesConfig,
+}
+
+#[test]
+fn test_single_bus_config() {
+    let config: Config = toml::from_str(
+        r#"
+        [can]
+        interface = "can0"
+        "#,
+    )
+    .unwrap();
+
+    assert_eq!(config.can.len(), 1);
+    assert_eq!(config.can[0].get_name(), "can0");
+}
+
+#[test]
+fn test_multiple_buses_config() {
+    let config: Config = toml::from_str(
+        r#"
+        [[can]]
+        interface = "can0"
+
+        [[can]]
+        name = "garage"
+        interface = "can1"
+        "#,
+    )
+    .unwrap();
+
+    assert_eq!(config.can.len(), 2);
+    assert_eq!(config.can[0].get_name(), "can0");
+    assert_eq!(config.can[1].get_name(), "garage");
+    assert_eq!(config.can[1].interface, "can1");
+}
+
+#[test]
+fn test_duplicate_bus_names_rejected() {
+    let result: Result<Config, _> = toml::from_str(
+        r#"
+        [[can]]
+        interface = "can0"
+
+        [[can]]
+        name = "can0"
+        interface = "can1"
+        "#,
+    );
+    assert!(result.is_err());
+
+    let result: Result<Config, _> = toml::from_str("can = []");
+    assert!(result.is_err());
+}
@@ -1,60 +1,261 @@
-use futures_util::{SinkExt, StreamExt};
+use std::{
+    io::{self, ErrorKind},
+    mem,
+    os::fd::{AsRawFd, RawFd},
+    ptr,
+    sync::Arc,
+    thread,
+    time::{Duration, Instant},
+};
+
+use chrono::{DateTime, Utc};
 use log::error;
+use tokio::sync::mpsc;
+
+use embedded_can::Frame as EmbeddedFrame;
+use socketcan::{
+    nl::CanInterface as NlCanInterface, CanDataFrame, CanFilter, CanFrame, CanSocket, Socket,
+    SocketOptions,
+};
+
+use super::{
+    CanBusHealth, CanConfig, CanFilterConfig, CanInterfaceError, CanInterfaceTrait, CanRxFrame,
+    CanStats,
+};
+
+// Frames received and not yet processed by the controller
+const CAN_RX_QUEUE_SIZE: usize = 256;
 
-use crate::caniot::{CANIOT_DEVICE_FILTER_ID, CANIOT_DEVICE_FILTER_MASK};
-use socketcan::tokio::CanSocket;
-use socketcan::{CanDataFrame, CanFilter, CanFrame, Socket, SocketOptions};
+// Period at which the RX thread checks whether the interface has been dropped,
+// also the delay before reading again after an error (e.g. interface down)
+const CAN_RX_READ_TIMEOUT: Duration = Duration::from_secs(1);
 
-use super::{CanConfig, CanInterfaceError, CanInterfaceTrait, CanStats};
+// Minimum interval between two logs of the reception errors
+const CAN_RX_ERROR_LOG_INTERVAL: Duration = Duration::from_secs(10);
+
+impl From<&CanFilterConfig> for CanFilter {
+    fn from(config: &CanFilterConfig) -> Self {
+        if config.inverted {
+            CanFilter::new(config.id | libc::CAN_INV_FILTER, config.mask)
+        } else {
+            CanFilter::new(config.id, config.mask)
+        }
+    }
+}
 
+type RxResult = Result<(CanFrame, DateTime<Utc>), io::Error>;
+
+/// SocketCAN interface.
+///
+/// Frames are received by a dedicated thread so that they are timestamped by
+/// the kernel (SO_TIMESTAMP) rather than when the controller gets to them.
 pub struct CanInterface {
-    sock: CanSocket,
+    interface: String,
+    sock: Arc<CanSocket>,
+    rx_queue: mpsc::Receiver<RxResult>,
     pub stats: CanStats,
+
+    // Bus state and errors reported by the error frames
+    health: CanBusHealth,
+
+    // Last time a reception error was logged
+    error_logged_at: Option<Instant>,
+}
+
+fn enable_timestamps(fd: RawFd) -> Result<(), io::Error> {
+    let enable: libc::c_int = 1;
+    let ret = unsafe {
+        libc::setsockopt(
+            fd,
+            libc::SOL_SOCKET,
+            libc::SO_TIMESTAMP,
+            &enable as *const _ as *const libc::c_void,
+            mem::size_of_val(&enable) as libc::socklen_t,
+        )
+    };
+
+    if ret < 0 {
+        Err(io::Error::last_os_error())
+    } else {
+        Ok(())
+    }
+}
+
+// Kernel reception timestamp from the SCM_TIMESTAMP control message
+fn kernel_timestamp(msg: &libc::msghdr) -> Option<DateTime<Utc>> {
+    let mut cmsg = unsafe { libc::CMSG_FIRSTHDR(msg) };
+    while !cmsg.is_null() {
+        let hdr = unsafe { &*cmsg };
+        if hdr.cmsg_level == libc::SOL_SOCKET && hdr.cmsg_type == libc::SCM_TIMESTAMP {
+            let tv: libc::timeval =
+                unsafe { ptr::read_unaligned(libc::CMSG_DATA(cmsg) as *const libc::timeval) };
+            return DateTime::from_timestamp(tv.tv_sec as i64, tv.tv_usec as u32 * 1000);
+        }
+        cmsg = unsafe { libc::CMSG_NXTHDR(msg, cmsg) };
+    }
+    None
+}
+
+fn recv_frame_timestamped(fd: RawFd) -> RxResult {
+    let mut frame: libc::can_frame = unsafe { mem::zeroed() };
+    let mut iov = libc::iovec {
+        iov_base: &mut frame as *mut _ as *mut libc::c_void,
+        iov_len: mem::size_of::<libc::can_frame>(),
+    };
+
+    // Room for the timestamp control message, u64 for alignment
+    let mut control = [0u64; 8];
+
+    let mut msg: libc::msghdr = unsafe { mem::zeroed() };
+    msg.msg_iov = &mut iov;
+    msg.msg_iovlen = 1;
+    msg.msg_control = control.as_mut_ptr() as *mut libc::c_void;
+    msg.msg_controllen = mem::size_of_val(&control) as _;
+
+    let len = unsafe { libc::recvmsg(fd, &mut msg, 0) };
+    if len < 0 {
+        return Err(io::Error::last_os_error());
+    } else if (len as usize) < mem::size_of::<libc::can_frame>() {
+        return Err(io::Error::new(
+            ErrorKind::InvalidData,
+            "Incomplete CAN frame",
+        ));
+    }
+
+    // Fallback to the reception time by the thread if the kernel did not timestamp the frame
+    let timestamp = kernel_timestamp(&msg).unwrap_or_else(Utc::now);
+
+    Ok((CanFrame::from(frame), timestamp))
+}
+
+fn rx_thread(sock: Arc<CanSocket>, queue: mpsc::Sender<RxResult>) {
+    while !queue.is_closed() {
+        let result = match recv_frame_timestamped(sock.as_raw_fd()) {
+            Err(err)
+                if matches!(
+                    err.kind(),
+                    ErrorKind::WouldBlock | ErrorKind::TimedOut | ErrorKind::Interrupted
+                ) =>
+            {
+                continue
+            }
+            result => result,
+        };
+
+        // Errors such as ENETDOWN or EBADF are returned immediately, do not spin on them
+        let failed = result.is_err();
+
+        if queue.blocking_send(result).is_err() {
+            break;
+        }
+
+        if failed {
+            thread::sleep(CAN_RX_READ_TIMEOUT);
+        }
+    }
+
+    debug!("CAN RX thread exiting");
 }
 
 #[async_trait]
 impl CanInterfaceTrait for CanInterface {
     async fn new(config: &CanConfig) -> Result<Self, CanInterfaceError> {
         let sock = CanSocket::open(&config.interface)?;
-        let filter = CanFilter::new(CANIOT_DEVICE_FILTER_ID, CANIOT_DEVICE_FILTER_MASK);
-        sock.set_filters(&[filter])?;
+        let filters: Vec<CanFilter> = config.filters.iter().map(Into::into).collect();
+        sock.set_filters(filters.as_slice())?;
+        sock.set_error_filter_accept_all()?;
+        sock.set_read_timeout(CAN_RX_READ_TIMEOUT)?;
+        enable_timestamps(sock.as_raw_fd())?;
+
+        let sock = Arc::new(sock);
+        let (sender, rx_queue) = mpsc::channel(CAN_RX_QUEUE_SIZE);
+
+        let rx_sock = sock.clone();
+        thread::Builder::new()
+            .name(format!("can-rx-{}", config.interface))
+            .spawn(move || rx_thread(rx_sock, sender))?;
+
         Ok(Self {
+            interface: config.interface.clone(),
             sock,
+            rx_queue,
             stats: CanStats::default(),
+            health: CanBusHealth::default(),
+            error_logged_at: None,
         })
     }
 
     async fn send(&mut self, frame: CanDataFrame) -> Result<(), CanInterfaceError> {
-        self.sock.send(frame.into()).await?;
+        // Writes to a CAN socket complete immediately, unless the TX queue is full
+        self.sock.write_frame(&frame)?;
         self.stats.tx += 1;
         Ok(())
     }
 
-    async fn recv_poll(&mut self) -> Option<CanDataFrame> {
-        if let Some(result) = self.sock.next().await {
+    async fn recv_poll(&mut self) -> Option<CanRxFrame> {
+        // Error frames only update the health of the bus, returning for them
+        // would hold back the frames of the other buses during an error storm
+        loop {
+            // The RX thread only exits once the interface is dropped
+            let Some(result) = self.rx_queue.recv().await else {
+                return futures::future::pending().await;
+            };
+
             match result {
-                Ok(CanFrame::Data(frame)) => {
+                Ok((CanFrame::Data(frame), timestamp)) => {
                     self.stats.rx += 1;
-                    return Some(frame);
+                    return Some(CanRxFrame { frame, timestamp });
                 }
-                Ok(CanFrame::Remote(frame)) => {
+                Ok((CanFrame::Remote(frame), _)) => {
                     warn!("Unhandled {:?}", frame);
                     self.stats.unhandled += 1;
                 }
-                Ok(CanFrame::Error(frame)) => {
-                    error!("{:?}", frame);
-                    self.stats.unhandled += 1;
+                Ok((CanFrame::Error(frame), timestamp)) => {
+                    let state = self.health.state;
+                    self.health
+                        .handle_error_frame(frame.error_bits(), frame.data(), timestamp);
+                    if self.health.state != state {
+                        warn!("{} {:?} -> {:?}", self.interface, state, self.health.state);
+                    }
+                    debug!("{} error frame {:?}", self.interface, frame);
+                    self.stats.err += 1;
                 }
                 Err(err) => {
-                    error!("{}", err);
                     self.stats.err += 1;
+
+                    // Errors persist until the interface is back up, do not flood the log
+                    let now = Instant::now();
+                    if self.error_logged_at.map_or(true, |logged_at| {
+                        now.duration_since(logged_at) >= CAN_RX_ERROR_LOG_INTERVAL
+                    }) {
+                        error!(
+                            "{} {}, {} errors so far",
+                            self.interface, err, self.stats.err
+                        );
+                        self.error_logged_at = Some(now);
+                    }
                 }
-            };
-        };
-        None
+            }
+        }
     }
 
     fn get_stats(&self) -> CanStats {
         self.stats
     }
+
+    fn get_health(&self) -> CanBusHealth {
+        self.health
+    }
+
+    fn restart(&mut self) -> Result<(), CanInterfaceError> {
+        // Requires CAP_NET_ADMIN, otherwise the kernel restarts the controller
+        // on its own if the interface is configured with restart-ms
+        let iface = NlCanInterface::open(&self.interface)
+            .map_err(|err| CanInterfaceError::RestartError(err.to_string()))?;
+        iface
+            .restart()
+            .map_err(|err| CanInterfaceError::RestartError(err.to_string()))?;
+        self.health.restarted();
+        Ok(())
+    }
 }
@@ -1,22 +1,36 @@
-use std::time::{Duration, Instant};
+use std::{
+    collections::VecDeque,
+    time::{Duration, Instant},
+};
 
 use crate::{
     caniot::{
         self,
-        emu::{self, emu_pool2_realistic_add_devices_to_iface},
+        emu::{self, emu_pool2_realistic_add_devices_to_iface, DeviceFaults, FaultsConfig},
     },
     grpcserver::EmuRequest,
 };
 
+use serde::{Deserialize, Serialize};
 use socketcan::CanDataFrame;
 use tokio::time::sleep;
 
-use super::{CanConfig, CanInterfaceError, CanInterfaceTrait, CanStats};
+use super::{CanConfig, CanInterfaceError, CanInterfaceTrait, CanRxFrame, CanStats};
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct CanEmuConfig {
+    // Faults injected on the frames of the emulated devices
+    #[serde(default)]
+    pub faults: Vec<FaultsConfig>,
+}
 
 pub struct CanInterface {
     stats: CanStats,
     devices: Vec<emu::Device>,
-    to_recv_msgq: Vec<CanDataFrame>,
+    faults: Vec<DeviceFaults>,
+
+    // Frames sent by the devices, by reception time
+    to_recv_msgq: VecDeque<(Instant, CanDataFrame)>,
 }
 
 impl CanInterface {
@@ -24,33 +38,72 @@ impl CanInterface {
         self.devices.push(device);
     }
 
+    pub fn add_faults(&mut self, faults: DeviceFaults) {
+        self.faults.push(faults);
+    }
+
     fn send_emu_request(&mut self, event: EmuRequest) {
         let now = Instant::now();
+        let mut responses = vec![];
         for device in self.devices.iter_mut() {
             device.handle_emu_request(event);
 
             // Process the device immediately after having send the emulated event
             // If a response is generated, it will be added to the to_recv_msgq
             if let Some(req) = device.process(None, &now) {
-                self.to_recv_msgq.push(req.into());
+                responses.push(req);
             }
         }
+
+        for response in responses {
+            self.deliver(response, false, &now);
+        }
+    }
+
+    // Queue a frame sent by a device, after having injected the faults configured for it
+    fn deliver(&mut self, response: caniot::Response, reply: bool, now: &Instant) {
+        let did = response.device_id.to_u8();
+        let frames = match self.faults.iter_mut().find(|faults| faults.did() == did) {
+            Some(faults) => faults.inject(response, reply, now, &mut rand::thread_rng()),
+            None => vec![(Duration::ZERO, response)],
+        };
+
+        for (delay, response) in frames {
+            let at = *now + delay;
+            let index = self.to_recv_msgq.partition_point(|(other, _)| *other <= at);
+            self.to_recv_msgq.insert(index, (at, response.into()));
+        }
+    }
+
+    fn pop_received(&mut self, now: &Instant) -> Option<CanDataFrame> {
+        match self.to_recv_msgq.front() {
+            Some((at, _)) if at <= now => self.to_recv_msgq.pop_front().map(|(_, frame)| frame),
+            _ => None,
+        }
     }
 }
 
 #[async_trait]
 impl CanInterfaceTrait for CanInterface {
-    async fn new(_config: &CanConfig) -> Result<Self, CanInterfaceError> {
+    async fn new(config: &CanConfig) -> Result<Self, CanInterfaceError> {
         warn!("Using emulated CAN interface");
 
         let mut iface = Self {
             stats: CanStats::default(),
             devices: vec![],
-            to_recv_msgq: Vec::new(),
+            faults: vec![],
+            to_recv_msgq: VecDeque::new(),
         };
 
         emu_pool2_realistic_add_devices_to_iface(&mut iface);
 
+        let now = Instant::now();
+        let faults = config.emu.iter().flat_map(|emu| emu.faults.iter());
+        for faults_config in faults {
+            warn!("Injecting faults on emulated device {:?}", faults_config);
+            iface.add_faults(DeviceFaults::new(faults_config.clone(), &now));
+        }
+
         Ok(iface)
     }
 
@@ -59,13 +112,18 @@ impl CanInterfaceTrait for CanInterface {
 
         let now = Instant::now();
         if let Ok(caniot_query) = caniot::Request::try_from(frame) {
+            let mut responses = vec![];
             for device in self.devices.iter_mut() {
                 if device.did == caniot_query.device_id {
                     if let Some(caniot_response) = device.process(Some(&caniot_query.data), &now) {
-                        self.to_recv_msgq.push(caniot_response.into());
+                        responses.push(caniot_response);
                     }
                 }
             }
+
+            for response in responses {
+                self.deliver(response, true, &now);
+            }
         } else {
             warn!("Invalid CAN query frame")
         }
@@ -73,23 +131,34 @@ impl CanInterfaceTrait for CanInterface {
         Ok(())
     }
 
-    async fn recv_poll(&mut self) -> Option<CanDataFrame> {
-        if let Some(frame) = self.to_recv_msgq.pop() {
-            self.stats.rx += 1;
-            return Some(frame);
-        }
-
+    async fn recv_poll(&mut self) -> Option<CanRxFrame> {
         loop {
-            let mut next_telemetry: Option<Duration> = None;
-
             let now = Instant::now();
 
-            for device in self.devices.iter_mut() {
-                if let Some(caniot_response) = device.process(None, &now) {
-                    self.stats.rx += 1;
-                    return Some(caniot_response.into());
+            if let Some(frame) = self.pop_received(&now) {
+                self.stats.rx += 1;
+                return Some(CanRxFrame::new(frame));
+            }
+
+            // Time until the next frame held by the faults is received
+            let mut next_telemetry: Option<Duration> = self
+                .to_recv_msgq
+                .front()
+                .map(|(at, _)| at.saturating_duration_since(now));
+
+            let responses: Vec<caniot::Response> = self
+                .devices
+                .iter_mut()
+                .filter_map(|device| device.process(None, &now))
+                .collect();
+            if !responses.is_empty() {
+                for response in responses {
+                    self.deliver(response, false, &now);
                 }
+                continue;
+            }
 
+            for device in self.devices.iter() {
                 let device_next_telemetry = device.get_time_to_next_device_process(&now);
                 if let Some(device_next_telemetry) = device_next_telemetry {
                     if device_next_telemetry <= next_telemetry.unwrap_or(device_next_telemetry) {
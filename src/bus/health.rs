@@ -0,0 +1,199 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+
+use crate::{
+    controller::DeviceAlert,
+    utils::{join_labels, BusLabel, PrometheusExporterTrait},
+};
+
+// Error classes of the error frame id (linux/can/error.h)
+const CAN_ERR_TX_TIMEOUT: u32 = 0x0000_0001;
+const CAN_ERR_LOSTARB: u32 = 0x0000_0002;
+const CAN_ERR_CRTL: u32 = 0x0000_0004;
+const CAN_ERR_PROT: u32 = 0x0000_0008;
+const CAN_ERR_TRX: u32 = 0x0000_0010;
+const CAN_ERR_ACK: u32 = 0x0000_0020;
+const CAN_ERR_BUSOFF: u32 = 0x0000_0040;
+const CAN_ERR_BUSERROR: u32 = 0x0000_0080;
+const CAN_ERR_RESTARTED: u32 = 0x0000_0100;
+const CAN_ERR_CNT: u32 = 0x0000_0200;
+
+// Controller problems, data[1]
+const CAN_ERR_CRTL_RX_OVERFLOW: u8 = 0x01;
+const CAN_ERR_CRTL_TX_OVERFLOW: u8 = 0x02;
+const CAN_ERR_CRTL_RX_WARNING: u8 = 0x04;
+const CAN_ERR_CRTL_TX_WARNING: u8 = 0x08;
+const CAN_ERR_CRTL_RX_PASSIVE: u8 = 0x10;
+const CAN_ERR_CRTL_TX_PASSIVE: u8 = 0x20;
+const CAN_ERR_CRTL_ACTIVE: u8 = 0x40;
+
+// Frames not acknowledged are reported for this long after the last one
+const NO_ACK_ALERT_DURATION_S: i64 = 60;
+
+/// Fault confinement state of the CAN controller
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default, Serialize)]
+pub enum CanBusState {
+    #[default]
+    ErrorActive,
+    ErrorWarning,
+    ErrorPassive,
+    BusOff,
+}
+
+/// Error frames received, by error class
+#[derive(Serialize, Debug, Clone, Copy, Default)]
+pub struct CanErrorCounters {
+    pub tx_timeout: usize,
+    pub lost_arbitration: usize,
+    pub controller: usize,
+    pub protocol: usize,
+    pub transceiver: usize,
+    pub no_ack: usize,
+    pub bus_off: usize,
+    pub bus_error: usize,
+    pub restarted: usize,
+    pub overflow: usize, // RX or TX buffer overflow of the controller
+}
+
+/// Health of the bus, decoded from the error frames of the controller
+#[derive(Serialize, Debug, Clone, Copy, Default)]
+pub struct CanBusHealth {
+    pub state: CanBusState,
+    pub errors: CanErrorCounters,
+
+    // Error counters of the controller, if reported
+    pub tx_error_counter: u8,
+    pub rx_error_counter: u8,
+
+    // Last frame sent without acknowledgement: no other node on the bus
+    // or missing termination
+    pub last_no_ack: Option<DateTime<Utc>>,
+    pub last_error: Option<DateTime<Utc>>,
+}
+
+impl CanBusHealth {
+    /// Account for an error frame, given its error class bits (id) and data
+    pub fn handle_error_frame(&mut self, bits: u32, data: &[u8], timestamp: DateTime<Utc>) {
+        let byte = |i: usize| data.get(i).copied().unwrap_or_default();
+
+        self.last_error = Some(timestamp);
+
+        if bits & CAN_ERR_TX_TIMEOUT != 0 {
+            self.errors.tx_timeout += 1;
+        }
+        if bits & CAN_ERR_LOSTARB != 0 {
+            self.errors.lost_arbitration += 1;
+        }
+        if bits & CAN_ERR_CRTL != 0 {
+            self.errors.controller += 1;
+
+            let crtl = byte(1);
+            if crtl & (CAN_ERR_CRTL_RX_OVERFLOW | CAN_ERR_CRTL_TX_OVERFLOW) != 0 {
+                self.errors.overflow += 1;
+            }
+            if crtl & (CAN_ERR_CRTL_RX_PASSIVE | CAN_ERR_CRTL_TX_PASSIVE) != 0 {
+                self.state = CanBusState::ErrorPassive;
+            } else if crtl & (CAN_ERR_CRTL_RX_WARNING | CAN_ERR_CRTL_TX_WARNING) != 0 {
+                self.state = CanBusState::ErrorWarning;
+            } else if crtl & CAN_ERR_CRTL_ACTIVE != 0 {
+                self.state = CanBusState::ErrorActive;
+            }
+        }
+        if bits & CAN_ERR_PROT != 0 {
+            self.errors.protocol += 1;
+        }
+        if bits & CAN_ERR_TRX != 0 {
+            self.errors.transceiver += 1;
+        }
+        if bits & CAN_ERR_ACK != 0 {
+            self.errors.no_ack += 1;
+            self.last_no_ack = Some(timestamp);
+        }
+        if bits & CAN_ERR_BUSERROR != 0 {
+            self.errors.bus_error += 1;
+        }
+        if bits & CAN_ERR_CNT != 0 {
+            self.tx_error_counter = byte(6);
+            self.rx_error_counter = byte(7);
+        }
+
+        // Bus-off and restart are the last transitions of the frame
+        if bits & CAN_ERR_BUSOFF != 0 {
+            self.errors.bus_off += 1;
+            self.state = CanBusState::BusOff;
+        }
+        if bits & CAN_ERR_RESTARTED != 0 {
+            self.restarted();
+        }
+    }
+
+    /// The controller has been restarted after bus-off
+    pub fn restarted(&mut self) {
+        self.errors.restarted += 1;
+        self.state = CanBusState::ErrorActive;
+        self.tx_error_counter = 0;
+        self.rx_error_counter = 0;
+    }
+
+    pub fn get_alert(&self, bus: &str, now: &DateTime<Utc>) -> DeviceAlert {
+        let no_ack_recently = self
+            .last_no_ack
+            .is_some_and(|at| *now - at < Duration::seconds(NO_ACK_ALERT_DURATION_S));
+
+        match self.state {
+            CanBusState::BusOff => DeviceAlert::new_error(&format!("Bus CAN {} hors ligne", bus))
+                .with_description("Le contrôleur a quitté le bus après trop d'erreurs (bus-off)"),
+            CanBusState::ErrorPassive => {
+                DeviceAlert::new_error(&format!("Bus CAN {} en erreur", bus))
+                    .with_description("Erreurs répétées, vérifier le câblage et la terminaison")
+            }
+            _ if no_ack_recently => {
+                DeviceAlert::new_warning(&format!("Aucun noeud ne répond sur le bus CAN {}", bus))
+                    .with_description(
+                        "Trames non acquittées : terminaison absente ou noeud débranché",
+                    )
+            }
+            CanBusState::ErrorWarning => {
+                DeviceAlert::new_warning(&format!("Erreurs sur le bus CAN {}", bus))
+            }
+            CanBusState::ErrorActive => {
+                DeviceAlert::new_ok(&format!("Bus CAN {} opérationnel", bus))
+            }
+        }
+    }
+}
+
+impl<'a> PrometheusExporterTrait<'a> for CanBusHealth {
+    type Label = BusLabel;
+    fn export(&self, labels: impl AsRef<[&'a Self::Label]>) -> String {
+        let str_labels = join_labels(&labels);
+        format!(
+            "bus_can_state {{{str_labels}}} {}\n\
+            bus_can_tx_error_counter {{{str_labels}}} {}\n\
+            bus_can_rx_error_counter {{{str_labels}}} {}\n\
+            bus_can_err_tx_timeout {{{str_labels}}} {}\n\
+            bus_can_err_lost_arbitration {{{str_labels}}} {}\n\
+            bus_can_err_controller {{{str_labels}}} {}\n\
+            bus_can_err_protocol {{{str_labels}}} {}\n\
+            bus_can_err_transceiver {{{str_labels}}} {}\n\
+            bus_can_err_no_ack {{{str_labels}}} {}\n\
+            bus_can_err_bus_off {{{str_labels}}} {}\n\
+            bus_can_err_bus_error {{{str_labels}}} {}\n\
+            bus_can_err_restarted {{{str_labels}}} {}\n\
+            bus_can_err_overflow {{{str_labels}}} {}\n",
+            self.state as u8,
+            self.tx_error_counter,
+            self.rx_error_counter,
+            self.errors.tx_timeout,
+            self.errors.lost_arbitration,
+            self.errors.controller,
+            self.errors.protocol,
+            self.errors.transceiver,
+            self.errors.no_ack,
+            self.errors.bus_off,
+            self.errors.bus_error,
+            self.errors.restarted,
+            self.errors.overflow,
+        )
+    }
+}
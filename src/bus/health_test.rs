@@ -0,0 +1,65 @@
+use chrono::{Duration, Utc};
+
+use crate::controller::DeviceAlertType;
+
+use super::{CanBusHealth, CanBusState};
+
+#[test]
+fn test_bus_health_states() {
+    let now = Utc::now();
+    let mut health = CanBusHealth::default();
+    assert_eq!(
+        health.get_alert("can0", &now).alert_type,
+        DeviceAlertType::Ok
+    );
+
+    // Controller problem: TX error warning, with error counters
+    health.handle_error_frame(0x204, &[0, 0x08, 0, 0, 0, 0, 96, 12], now);
+    assert_eq!(health.state, CanBusState::ErrorWarning);
+    assert_eq!(health.tx_error_counter, 96);
+    assert_eq!(health.rx_error_counter, 12);
+    assert_eq!(
+        health.get_alert("can0", &now).alert_type,
+        DeviceAlertType::Warning
+    );
+
+    // TX error passive
+    health.handle_error_frame(0x004, &[0, 0x20, 0, 0, 0, 0, 0, 0], now);
+    assert_eq!(health.state, CanBusState::ErrorPassive);
+    assert_eq!(
+        health.get_alert("can0", &now).alert_type,
+        DeviceAlertType::Error
+    );
+
+    // Bus-off, then restarted
+    health.handle_error_frame(0x040, &[0; 8], now);
+    assert_eq!(health.state, CanBusState::BusOff);
+    assert_eq!(health.errors.bus_off, 1);
+
+    health.handle_error_frame(0x100, &[0; 8], now);
+    assert_eq!(health.state, CanBusState::ErrorActive);
+    assert_eq!(health.errors.restarted, 1);
+    assert_eq!(health.errors.controller, 2);
+}
+
+#[test]
+fn test_bus_health_no_ack() {
+    let now = Utc::now();
+    let mut health = CanBusHealth::default();
+
+    // No acknowledgement, e.g. missing termination or no other node
+    health.handle_error_frame(0x020, &[0; 8], now);
+    assert_eq!(health.errors.no_ack, 1);
+    assert_eq!(health.state, CanBusState::ErrorActive);
+    assert_eq!(
+        health.get_alert("can0", &now).alert_type,
+        DeviceAlertType::Warning
+    );
+
+    // Cleared once no frame has been left unacknowledged for a while
+    let later = now + Duration::minutes(5);
+    assert_eq!(
+        health.get_alert("can0", &later).alert_type,
+        DeviceAlertType::Ok
+    );
+}
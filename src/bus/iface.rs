@@ -1,45 +1,204 @@
+use std::ops::AddAssign;
+
+use chrono::{DateTime, Utc};
 use log::error;
 use serde::{Deserialize, Serialize};
 
 use socketcan::{CanDataFrame, Error as CanError};
 use thiserror::Error;
 
-use crate::utils::{PrometheusExporterTrait, PrometheusNoLabel};
+use crate::caniot::{CANIOT_DEVICE_FILTER_ID, CANIOT_DEVICE_FILTER_MASK};
+use crate::utils::{join_labels, BusLabel, PrometheusExporterTrait};
+
+use super::{
+    CanBusHealth, CanBusState, CanEmuConfig, CanRecordConfig, CanReplayConfig, CanSlcanConfig,
+    CanSniffConfig, CanTxConfig,
+};
+
+/// Implementation of the CAN interface of a bus
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum CanBackend {
+    // SocketCAN interface
+    #[default]
+    Socketcan,
+    // Emulated devices, no CAN interface required
+    Emulated,
+    // Log replayed from [can.replay]
+    Replay,
+    // Remote controller from [can.remote] (grpc-can-iface-client feature)
+    Grpc,
+    // Serial adapter speaking the slcan protocol from [can.slcan]
+    Slcan,
+}
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct CanConfig {
+    // Name of the bus, defaults to the interface name
+    pub name: Option<String>,
+
+    // Interface used for the bus
+    #[serde(default)]
+    pub backend: CanBackend,
+
     pub interface: String,
+
+    // Kernel filters of the frames received by the socketcan backend,
+    // CANIOT responses only by default
+    #[serde(default = "default_filters")]
+    pub filters: Vec<CanFilterConfig>,
+
+    // Keep the frames which are not CANIOT responses
+    pub sniff: Option<CanSniffConfig>,
+
+    // Record all frames received and sent to a file
+    pub record: Option<CanRecordConfig>,
+
+    // Emulated devices of the emulated backend
+    pub emu: Option<CanEmuConfig>,
+
+    // Log replayed by the replay backend
+    pub replay: Option<CanReplayConfig>,
+
+    // Remote controller used by the grpc backend
+    pub remote: Option<CanRemoteConfig>,
+
+    // Serial adapter used by the slcan backend
+    pub slcan: Option<CanSlcanConfig>,
+
+    // Scheduling of the frames sent
+    #[serde(default)]
+    pub tx: CanTxConfig,
 }
 
 impl Default for CanConfig {
     fn default() -> Self {
         CanConfig {
+            name: None,
+            backend: CanBackend::default(),
             interface: "can0".to_string(),
+            filters: default_filters(),
+            sniff: None,
+            record: None,
+            emu: None,
+            replay: None,
+            remote: None,
+            slcan: None,
+            tx: CanTxConfig::default(),
         }
     }
 }
 
+impl CanConfig {
+    pub fn get_name(&self) -> &str {
+        self.name.as_deref().unwrap_or(&self.interface)
+    }
+}
+
+/// Frames received if (can_id & mask) == (id & mask), the EFF/RTR/ERR flags of
+/// can_id can be part of the filter
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CanFilterConfig {
+    pub id: u32,
+    pub mask: u32,
+
+    // Receive the frames not matching the filter instead
+    #[serde(default)]
+    pub inverted: bool,
+}
+
+impl CanFilterConfig {
+    /// Responses of the CANIOT devices
+    pub const CANIOT: Self = Self {
+        id: CANIOT_DEVICE_FILTER_ID,
+        mask: CANIOT_DEVICE_FILTER_MASK,
+        inverted: false,
+    };
+
+    /// Any frame
+    pub const ALL: Self = Self {
+        id: 0,
+        mask: 0,
+        inverted: false,
+    };
+}
+
+fn default_filters() -> Vec<CanFilterConfig> {
+    vec![CanFilterConfig::CANIOT]
+}
+
+fn default_reconnect_delay() -> u32 {
+    1000
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct CanRemoteConfig {
+    // gRPC endpoint of the remote controller, e.g. "http://192.168.10.1:50051"
+    pub url: String,
+
+    // Delay before reconnecting when the connection is lost (ms)
+    #[serde(default = "default_reconnect_delay")]
+    pub reconnect_delay: u32,
+}
+
 #[derive(Serialize, Debug, Clone, Copy, Default)]
 pub struct CanStats {
     pub rx: usize,
     pub tx: usize,
     pub err: usize,
     pub unhandled: usize,
+    // Connections re-established to the remote interface
+    pub reconnects: usize,
+    // Error flags reported by the serial adapter
+    pub adapter_errors: usize,
+}
+
+impl AddAssign<&CanStats> for CanStats {
+    fn add_assign(&mut self, other: &CanStats) {
+        self.rx += other.rx;
+        self.tx += other.tx;
+        self.err += other.err;
+        self.unhandled += other.unhandled;
+        self.reconnects += other.reconnects;
+        self.adapter_errors += other.adapter_errors;
+    }
 }
 
 impl<'a> PrometheusExporterTrait<'a> for CanStats {
-    type Label = PrometheusNoLabel;
-    fn export(&self, _labels: impl AsRef<[&'a Self::Label]>) -> String {
+    type Label = BusLabel;
+    fn export(&self, labels: impl AsRef<[&'a Self::Label]>) -> String {
+        let str_labels = join_labels(&labels);
         format!(
-            "bus_can_rx {}\n\
-            bus_can_tx {}\n\
-            bus_can_err {}\n\
-            bus_can_unhandled {}\n",
-            self.rx, self.tx, self.err, self.unhandled
+            "bus_can_rx {{{str_labels}}} {}\n\
+            bus_can_tx {{{str_labels}}} {}\n\
+            bus_can_err {{{str_labels}}} {}\n\
+            bus_can_unhandled {{{str_labels}}} {}\n\
+            bus_can_reconnects {{{str_labels}}} {}\n\
+            bus_can_adapter_errors {{{str_labels}}} {}\n",
+            self.rx, self.tx, self.err, self.unhandled, self.reconnects, self.adapter_errors
         )
     }
 }
 
+/// Frame received from the bus
+#[derive(Debug, Clone)]
+pub struct CanRxFrame {
+    pub frame: CanDataFrame,
+
+    // Reception time, from the kernel if the interface supports it
+    pub timestamp: DateTime<Utc>,
+}
+
+impl CanRxFrame {
+    /// Frame received now, for interfaces without reception timestamps
+    pub fn new(frame: CanDataFrame) -> Self {
+        Self {
+            frame,
+            timestamp: Utc::now(),
+        }
+    }
+}
+
 #[derive(Error, Debug)]
 pub enum CanInterfaceError {
     #[error("SocketCAN error: {0}")]
@@ -47,21 +206,76 @@ pub enum CanInterfaceError {
 
     #[error("IO error: {0}")]
     IoError(#[from] std::io::Error),
+
+    #[error("Missing configuration section [{0}]")]
+    MissingConfig(&'static str),
+
+    #[error("TX queue full")]
+    TxQueueFull,
+
+    #[error("Failed to restart the bus: {0}")]
+    RestartError(String),
+
+    #[error("CAN adapter error: {0}")]
+    AdapterError(String),
+
+    #[error("CAN backend {0:?} not supported by this build")]
+    UnsupportedBackend(CanBackend),
+
+    #[cfg(feature = "grpc-can-iface-client")]
+    #[error("Remote transport error: {0}")]
+    RemoteTransportError(#[from] tonic::transport::Error),
+
+    #[cfg(feature = "grpc-can-iface-client")]
+    #[error("Remote error: {0}")]
+    RemoteStatus(#[from] tonic::Status),
+
+    #[cfg(feature = "grpc-can-iface-client")]
+    #[error("Remote interface unavailable")]
+    RemoteUnavailable,
+}
+
+impl CanInterfaceError {
+    /// Whether the frame could not be sent because the socket buffer is full,
+    /// sending it again later may succeed
+    pub fn is_buffer_full(&self) -> bool {
+        match self {
+            CanInterfaceError::IoError(err) => {
+                err.raw_os_error() == Some(libc::ENOBUFS)
+                    || err.kind() == std::io::ErrorKind::WouldBlock
+            }
+            _ => false,
+        }
+    }
 }
 
 #[async_trait]
-pub trait CanInterfaceTrait
-where
-    Self: Sized,
-{
-    async fn new(config: &CanConfig) -> Result<Self, CanInterfaceError>;
+pub trait CanInterfaceTrait: Send + Sync {
+    async fn new(config: &CanConfig) -> Result<Self, CanInterfaceError>
+    where
+        Self: Sized;
 
     async fn send(&mut self, frame: CanDataFrame) -> Result<(), CanInterfaceError>;
 
-    async fn recv_poll(&mut self) -> Option<CanDataFrame>;
+    async fn recv_poll(&mut self) -> Option<CanRxFrame>;
 
     fn get_stats(&self) -> CanStats;
 
+    // Bus state and errors, for interfaces receiving error frames
+    fn get_health(&self) -> CanBusHealth {
+        CanBusHealth::default()
+    }
+
+    // Whether the controller went bus-off and no longer takes part in the bus
+    fn is_bus_off(&self) -> bool {
+        self.get_health().state == CanBusState::BusOff
+    }
+
+    // Restart the controller after bus-off
+    fn restart(&mut self) -> Result<(), CanInterfaceError> {
+        Ok(())
+    }
+
     // Allow to perform alternatives operations on the interface (e.g. send emulated events, change filters, inhibit, etc.)
     fn ioctl(&mut self, _cmd: u32, _arg: u32) -> Result<(), CanInterfaceError> {
         error!("ioctl not implemented");
@@ -1,17 +1,42 @@
+pub mod backend;
+pub mod buses;
+pub mod health;
 pub mod iface;
+pub mod recorder;
+pub mod replay;
+pub mod slcan;
+pub mod sniffer;
+pub mod tx_queue;
 
+pub use backend::*;
+pub use buses::*;
+pub use emu::CanEmuConfig;
+pub use health::*;
 pub use iface::*;
+pub use recorder::*;
+pub use replay::CanReplayConfig;
+pub use slcan::CanSlcanConfig;
+pub use sniffer::*;
+pub use tx_queue::*;
 
-#[cfg(feature = "emu")]
-pub mod emu;
-
-#[cfg(feature = "emu")]
-pub use emu::*;
+#[cfg(test)]
+mod buses_test;
+#[cfg(test)]
+mod health_test;
+#[cfg(test)]
+mod recorder_test;
+#[cfg(test)]
+mod slcan_test;
+#[cfg(test)]
+mod sniffer_test;
+#[cfg(test)]
+mod tx_queue_test;
 
-#[cfg(not(feature = "emu"))]
 pub mod can;
+pub mod emu;
+
+#[cfg(feature = "grpc-can-iface-client")]
+pub mod remote;
 
-#[cfg(feature = "emu")]
-pub type IFaceType = crate::bus::emu::CanInterface;
-#[cfg(not(feature = "emu"))]
-pub type IFaceType = crate::bus::can::CanInterface;
+// Backend of each bus selected at runtime by the [can] configuration
+pub type IFaceType = DynCanInterface;
@@ -0,0 +1,241 @@
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use embedded_can::{ExtendedId, Frame as EmbeddedFrame, Id as EmbeddedId, StandardId};
+use log::error;
+use serde::{Deserialize, Serialize};
+use socketcan::CanDataFrame;
+
+use super::CanInterfaceError;
+
+// LINKTYPE_CAN_SOCKETCAN
+const PCAPNG_LINKTYPE_SOCKETCAN: u16 = 227;
+
+const PCAPNG_BLOCK_SHB: u32 = 0x0A0D_0D0A;
+const PCAPNG_BLOCK_IDB: u32 = 0x0000_0001;
+const PCAPNG_BLOCK_EPB: u32 = 0x0000_0006;
+const PCAPNG_BYTE_ORDER_MAGIC: u32 = 0x1A2B_3C4D;
+const PCAPNG_OPT_EPB_FLAGS: u16 = 2;
+
+// can_id (big endian), len, 3 bytes padding/reserved, 8 bytes data
+const SOCKETCAN_FRAME_SIZE: usize = 16;
+const CAN_EFF_FLAG: u32 = 0x8000_0000;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum CanRecordFormat {
+    // candump log format: "(sec.usec) iface ID#DATA"
+    #[default]
+    Candump,
+    // pcapng with the SocketCAN link type, readable by wireshark
+    Pcapng,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct CanRecordConfig {
+    pub path: String,
+    #[serde(default)]
+    pub format: CanRecordFormat,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CanDirection {
+    Rx,
+    Tx,
+}
+
+/// Records all frames received and sent on the bus to a file
+pub struct CanRecorder {
+    writer: BufWriter<File>,
+    format: CanRecordFormat,
+    interface: String,
+}
+
+impl CanRecorder {
+    pub fn open(config: &CanRecordConfig, interface: &str) -> Result<Self, CanInterfaceError> {
+        let file = File::create(&config.path)?;
+        let mut recorder = Self {
+            writer: BufWriter::new(file),
+            format: config.format,
+            interface: interface.to_string(),
+        };
+
+        if recorder.format == CanRecordFormat::Pcapng {
+            recorder.writer.write_all(&pcapng_header_blocks())?;
+            recorder.writer.flush()?;
+        }
+
+        info!(
+            "Recording CAN traffic to {} ({:?})",
+            config.path, config.format
+        );
+
+        Ok(recorder)
+    }
+
+    pub fn record(&mut self, direction: CanDirection, frame: &CanDataFrame) {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+
+        self.record_at(timestamp, direction, frame);
+    }
+
+    /// Record a frame with the time it was received at (since the UNIX epoch)
+    pub fn record_at(
+        &mut self,
+        timestamp: Duration,
+        direction: CanDirection,
+        frame: &CanDataFrame,
+    ) {
+        if let Err(err) = self.write_frame(timestamp, direction, frame) {
+            error!("Failed to record CAN frame: {}", err);
+        }
+    }
+
+    fn write_frame(
+        &mut self,
+        timestamp: Duration,
+        direction: CanDirection,
+        frame: &CanDataFrame,
+    ) -> Result<(), std::io::Error> {
+        match self.format {
+            CanRecordFormat::Candump => {
+                let line = format_candump_line(timestamp, &self.interface, frame);
+                writeln!(self.writer, "{}", line)?;
+            }
+            CanRecordFormat::Pcapng => {
+                self.writer
+                    .write_all(&pcapng_packet_block(timestamp, direction, frame))?;
+            }
+        }
+
+        // Flush every frame so that the log is usable even if the controller crashes
+        self.writer.flush()
+    }
+}
+
+fn frame_raw_id(frame: &CanDataFrame) -> (u32, bool) {
+    match frame.id() {
+        EmbeddedId::Standard(id) => (id.as_raw() as u32, false),
+        EmbeddedId::Extended(id) => (id.as_raw(), true),
+    }
+}
+
+/// Format a frame as a candump log line (e.g. "(1700000000.123456) can0 123#DEADBEEF")
+pub fn format_candump_line(timestamp: Duration, interface: &str, frame: &CanDataFrame) -> String {
+    let (id, extended) = frame_raw_id(frame);
+    let id = if extended {
+        format!("{:08X}", id)
+    } else {
+        format!("{:03X}", id)
+    };
+    let data: String = frame.data().iter().map(|b| format!("{:02X}", b)).collect();
+
+    format!(
+        "({:010}.{:06}) {} {}#{}",
+        timestamp.as_secs(),
+        timestamp.subsec_micros(),
+        interface,
+        id,
+        data
+    )
+}
+
+/// Parse a candump log line, returns the timestamp and the frame.
+/// Remote frames and CAN FD frames are not supported.
+pub fn parse_candump_line(line: &str) -> Option<(Duration, CanDataFrame)> {
+    let mut fields = line.split_whitespace();
+
+    let timestamp = fields.next()?.strip_prefix('(')?.strip_suffix(')')?;
+    let (secs, usecs) = timestamp.split_once('.')?;
+    let timestamp = Duration::new(secs.parse().ok()?, 0)
+        + Duration::from_micros(format!("{:0<6}", usecs).get(..6)?.parse().ok()?);
+
+    let _interface = fields.next()?;
+
+    let (id, data) = fields.next()?.split_once('#')?;
+    let raw_id = u32::from_str_radix(id, 16).ok()?;
+    let id: EmbeddedId = if id.len() > 3 {
+        ExtendedId::new(raw_id)?.into()
+    } else {
+        StandardId::new(raw_id as u16)?.into()
+    };
+
+    if data.len() % 2 != 0 {
+        return None;
+    }
+    let data = (0..data.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(data.get(i..i + 2)?, 16).ok())
+        .collect::<Option<Vec<u8>>>()?;
+
+    Some((timestamp, CanDataFrame::new(id, &data)?))
+}
+
+fn pcapng_block(block_type: u32, body: &[u8]) -> Vec<u8> {
+    // Block type, total length, body, total length
+    let total_length = (12 + body.len()) as u32;
+    let mut block = Vec::with_capacity(total_length as usize);
+    block.extend_from_slice(&block_type.to_le_bytes());
+    block.extend_from_slice(&total_length.to_le_bytes());
+    block.extend_from_slice(body);
+    block.extend_from_slice(&total_length.to_le_bytes());
+    block
+}
+
+/// Section header block followed by the interface description block
+pub fn pcapng_header_blocks() -> Vec<u8> {
+    let mut shb = Vec::new();
+    shb.extend_from_slice(&PCAPNG_BYTE_ORDER_MAGIC.to_le_bytes());
+    shb.extend_from_slice(&1u16.to_le_bytes()); // major version
+    shb.extend_from_slice(&0u16.to_le_bytes()); // minor version
+    shb.extend_from_slice(&(-1i64).to_le_bytes()); // section length unspecified
+
+    let mut idb = Vec::new();
+    idb.extend_from_slice(&PCAPNG_LINKTYPE_SOCKETCAN.to_le_bytes());
+    idb.extend_from_slice(&0u16.to_le_bytes()); // reserved
+    idb.extend_from_slice(&0u32.to_le_bytes()); // no snap length limit
+
+    let mut blocks = pcapng_block(PCAPNG_BLOCK_SHB, &shb);
+    blocks.extend(pcapng_block(PCAPNG_BLOCK_IDB, &idb));
+    blocks
+}
+
+/// Enhanced packet block holding the frame, timestamp is in microseconds
+pub fn pcapng_packet_block(
+    timestamp: Duration,
+    direction: CanDirection,
+    frame: &CanDataFrame,
+) -> Vec<u8> {
+    let (id, extended) = frame_raw_id(frame);
+    let id = if extended { id | CAN_EFF_FLAG } else { id };
+
+    let mut packet = [0u8; SOCKETCAN_FRAME_SIZE];
+    packet[0..4].copy_from_slice(&id.to_be_bytes());
+    packet[4] = frame.data().len() as u8;
+    packet[8..8 + frame.data().len()].copy_from_slice(frame.data());
+
+    let timestamp = timestamp.as_micros() as u64;
+    let flags: u32 = match direction {
+        CanDirection::Rx => 0b01, // inbound
+        CanDirection::Tx => 0b10, // outbound
+    };
+
+    let mut epb = Vec::new();
+    epb.extend_from_slice(&0u32.to_le_bytes()); // interface id
+    epb.extend_from_slice(&((timestamp >> 32) as u32).to_le_bytes());
+    epb.extend_from_slice(&(timestamp as u32).to_le_bytes());
+    epb.extend_from_slice(&(SOCKETCAN_FRAME_SIZE as u32).to_le_bytes()); // captured length
+    epb.extend_from_slice(&(SOCKETCAN_FRAME_SIZE as u32).to_le_bytes()); // original length
+    epb.extend_from_slice(&packet);
+    epb.extend_from_slice(&PCAPNG_OPT_EPB_FLAGS.to_le_bytes());
+    epb.extend_from_slice(&4u16.to_le_bytes());
+    epb.extend_from_slice(&flags.to_le_bytes());
+    epb.extend_from_slice(&[0u8; 4]); // opt_endofopt
+
+    pcapng_block(PCAPNG_BLOCK_EPB, &epb)
+}
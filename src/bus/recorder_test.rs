@@ -0,0 +1,95 @@
+use std::time::Duration;
+
+use chrono::DateTime;
+use embedded_can::{ExtendedId, Frame as EmbeddedFrame, StandardId};
+use socketcan::CanDataFrame;
+
+use super::{
+    format_candump_line, parse_candump_line, pcapng_header_blocks, pcapng_packet_block,
+    replay::CanInterface, CanDirection, CanInterfaceTrait,
+};
+
+#[test]
+fn test_candump_format_parse() {
+    let id = StandardId::new(0x10C).unwrap();
+    let frame = CanDataFrame::new(id, &[0xDE, 0xAD, 0xBE, 0xEF]).unwrap();
+    let timestamp = Duration::from_micros(1_700_000_000_012_345);
+
+    let line = format_candump_line(timestamp, "can0", &frame);
+    assert_eq!(line, "(1700000000.012345) can0 10C#DEADBEEF");
+
+    let (parsed_timestamp, parsed_frame) = parse_candump_line(&line).unwrap();
+    assert_eq!(parsed_timestamp, timestamp);
+    assert_eq!(parsed_frame.id(), frame.id());
+    assert_eq!(parsed_frame.data(), frame.data());
+
+    // extended id and empty payload
+    let (_, frame) = parse_candump_line("(1700000000.000000) vcan0 1234ABCD#").unwrap();
+    assert_eq!(frame.id(), ExtendedId::new(0x1234ABCD).unwrap().into());
+    assert!(frame.data().is_empty());
+
+    // remote frames and garbage are rejected
+    assert!(parse_candump_line("(1700000000.000000) can0 123#R").is_none());
+    assert!(parse_candump_line("can0 123#00").is_none());
+    assert!(parse_candump_line("(1700000000.000000) can0 123#0").is_none());
+}
+
+#[test]
+fn test_pcapng_blocks() {
+    let header = pcapng_header_blocks();
+    // SHB (28 bytes) + IDB (20 bytes)
+    assert_eq!(header.len(), 48);
+    assert_eq!(&header[0..4], &0x0A0D0D0Au32.to_le_bytes());
+
+    let id = StandardId::new(0x10C).unwrap();
+    let frame = CanDataFrame::new(id, &[0x01, 0x02]).unwrap();
+    let block = pcapng_packet_block(Duration::from_secs(1), CanDirection::Tx, &frame);
+
+    assert_eq!(block.len() % 4, 0);
+    assert_eq!(&block[4..8], &(block.len() as u32).to_le_bytes());
+    assert_eq!(
+        &block[block.len() - 4..],
+        &(block.len() as u32).to_le_bytes()
+    );
+    // SocketCAN header: big endian id and length
+    assert_eq!(&block[28..32], &0x10Cu32.to_be_bytes());
+    assert_eq!(block[32], 2);
+    assert_eq!(&block[36..38], &[0x01, 0x02]);
+}
+
+#[test]
+fn test_replay_log() {
+    let log = "\
+        (1700000000.000000) can0 108#0102\n\
+        (1700000000.100000) can0 10C#0102\n\
+        \n\
+        invalid line\n\
+        (1700000000.200000) can0 10C#03\n";
+
+    // queries sent by the controller (108) are not replayed
+    let iface = CanInterface::from_log(log, 0.0);
+    assert_eq!(iface.remaining(), 2);
+}
+
+const REPLAY_LOG: &str = "\
+    (1700000000.100000) can0 10C#0102\n\
+    (1700000000.200000) can0 10C#03\n";
+
+#[tokio::test]
+async fn test_replay_timestamps() {
+    // Time the frames are due on the replay timeline, at twice the logged speed
+    let mut iface = CanInterface::from_log(REPLAY_LOG, 2.0);
+    let first = iface.recv_poll().await.unwrap().timestamp;
+    let second = iface.recv_poll().await.unwrap().timestamp;
+    let interval = (second - first).num_microseconds().unwrap();
+    assert!((49_000..=51_000).contains(&interval));
+
+    // Time the frames were logged at
+    let mut iface = CanInterface::from_log(REPLAY_LOG, 0.0).with_logged_timestamps(true);
+    let frame = iface.recv_poll().await.unwrap();
+    assert_eq!(
+        frame.timestamp,
+        DateTime::from_timestamp(1700000000, 100_000_000).unwrap()
+    );
+    assert_eq!(frame.frame.data(), &[0x01, 0x02]);
+}
@@ -0,0 +1,208 @@
+use std::time::Duration;
+
+use chrono::Utc;
+use socketcan::CanDataFrame;
+use tokio::{select, sync::mpsc, time::sleep};
+
+use crate::grpcserver::{
+    can_iface::{can_iface_service_client::CanIfaceServiceClient, RxCanFrame, TxCanFrame},
+    prost_timestamp_to_utc,
+};
+
+use super::{CanConfig, CanInterfaceError, CanInterfaceTrait, CanRxFrame, CanStats};
+
+// Frames buffered in each direction
+const CAN_REMOTE_QUEUE_SIZE: usize = 64;
+
+enum RemoteEvent {
+    Frame(CanRxFrame),
+    Connected,
+    Disconnected,
+    // Frame received from the remote which is not a valid CAN frame
+    Invalid,
+    // Frame sent while the remote was unreachable
+    TxDropped,
+}
+
+/// Remote controller used as a CAN interface through its CanIfaceService
+/// (hardware in the loop).
+///
+/// The connection is handled by a background task which reconnects whenever
+/// the stream is closed. Frames sent while disconnected are dropped.
+pub struct CanInterface {
+    tx_queue: mpsc::Sender<CanDataFrame>,
+    events: mpsc::Receiver<RemoteEvent>,
+    connected_once: bool,
+    stats: CanStats,
+}
+
+fn rx_event(message: RxCanFrame) -> RemoteEvent {
+    let Some(frame) = message
+        .frame
+        .as_ref()
+        .and_then(|frame| CanDataFrame::try_from(frame).ok())
+    else {
+        return RemoteEvent::Invalid;
+    };
+
+    // Keep the reception time on the remote bus
+    let timestamp = message
+        .timestamp
+        .as_ref()
+        .and_then(prost_timestamp_to_utc)
+        .unwrap_or_else(Utc::now);
+
+    RemoteEvent::Frame(CanRxFrame { frame, timestamp })
+}
+
+// Forward frames in both directions until the connection is lost (Err) or the interface is dropped (Ok)
+async fn session(
+    url: &str,
+    tx_queue: &mut mpsc::Receiver<CanDataFrame>,
+    events: &mpsc::Sender<RemoteEvent>,
+) -> Result<(), CanInterfaceError> {
+    let mut client = CanIfaceServiceClient::connect(url.to_string()).await?;
+
+    let (outbound, mut outbound_receiver) = mpsc::channel(CAN_REMOTE_QUEUE_SIZE);
+    let outbound_stream = async_stream::stream! {
+        while let Some(message) = outbound_receiver.recv().await {
+            yield message;
+        }
+    };
+
+    let mut inbound = client.iface(outbound_stream).await?.into_inner();
+
+    info!("Connected to remote CAN interface {}", url);
+    if events.send(RemoteEvent::Connected).await.is_err() {
+        return Ok(());
+    }
+
+    loop {
+        select! {
+            frame = tx_queue.recv() => {
+                let Some(frame) = frame else {
+                    return Ok(());
+                };
+
+                let message = TxCanFrame {
+                    frame: Some((&frame).into()),
+                };
+                if outbound.send(message).await.is_err() {
+                    return Err(CanInterfaceError::RemoteUnavailable);
+                }
+            }
+            message = inbound.message() => {
+                let Some(message) = message? else {
+                    return Err(CanInterfaceError::RemoteUnavailable);
+                };
+
+                if events.send(rx_event(message)).await.is_err() {
+                    return Ok(());
+                }
+            }
+        }
+    }
+}
+
+async fn run(
+    url: String,
+    reconnect_delay: Duration,
+    mut tx_queue: mpsc::Receiver<CanDataFrame>,
+    events: mpsc::Sender<RemoteEvent>,
+) {
+    loop {
+        match session(&url, &mut tx_queue, &events).await {
+            Ok(()) => break,
+            Err(err) => error!("Remote CAN interface {}: {}", url, err),
+        }
+
+        if events.send(RemoteEvent::Disconnected).await.is_err() {
+            break;
+        }
+
+        // Drop the frames sent until it is time to reconnect
+        let reconnect = sleep(reconnect_delay);
+        tokio::pin!(reconnect);
+        loop {
+            select! {
+                _ = &mut reconnect => break,
+                frame = tx_queue.recv() => {
+                    if frame.is_none() || events.send(RemoteEvent::TxDropped).await.is_err() {
+                        return;
+                    }
+                }
+            }
+        }
+    }
+
+    debug!("Remote CAN interface task exiting");
+}
+
+#[async_trait]
+impl CanInterfaceTrait for CanInterface {
+    async fn new(config: &CanConfig) -> Result<Self, CanInterfaceError> {
+        let Some(remote_config) = config.remote.as_ref() else {
+            return Err(CanInterfaceError::MissingConfig("can.remote"));
+        };
+
+        warn!("Using remote CAN interface {}", remote_config.url);
+
+        let (tx_queue, tx_queue_receiver) = mpsc::channel(CAN_REMOTE_QUEUE_SIZE);
+        let (events_sender, events) = mpsc::channel(CAN_REMOTE_QUEUE_SIZE);
+
+        tokio::spawn(run(
+            remote_config.url.clone(),
+            Duration::from_millis(remote_config.reconnect_delay as u64),
+            tx_queue_receiver,
+            events_sender,
+        ));
+
+        Ok(Self {
+            tx_queue,
+            events,
+            connected_once: false,
+            stats: CanStats::default(),
+        })
+    }
+
+    async fn send(&mut self, frame: CanDataFrame) -> Result<(), CanInterfaceError> {
+        // Never wait for the remote, the controller loop must not be blocked
+        if self.tx_queue.try_send(frame).is_err() {
+            self.stats.err += 1;
+            return Err(CanInterfaceError::RemoteUnavailable);
+        }
+
+        self.stats.tx += 1;
+        Ok(())
+    }
+
+    async fn recv_poll(&mut self) -> Option<CanRxFrame> {
+        loop {
+            match self.events.recv().await? {
+                RemoteEvent::Frame(frame) => {
+                    self.stats.rx += 1;
+                    return Some(frame);
+                }
+                RemoteEvent::Connected => {
+                    if self.connected_once {
+                        self.stats.reconnects += 1;
+                    }
+                    self.connected_once = true;
+                }
+                RemoteEvent::Disconnected => {
+                    warn!("Remote CAN interface disconnected");
+                }
+                RemoteEvent::Invalid => {
+                    self.stats.unhandled += 1;
+                }
+                RemoteEvent::TxDropped => {
+                    self.stats.err += 1;
+                }
+            }
+        }
+    }
+
+    fn get_stats(&self) -> CanStats {
+        self.stats
+    }
+}
@@ -0,0 +1,179 @@
+use std::{
+    collections::VecDeque,
+    time::{Duration, Instant},
+};
+
+use chrono::{DateTime, Utc};
+use embedded_can::{Frame as EmbeddedFrame, Id as EmbeddedId};
+use serde::{Deserialize, Serialize};
+use socketcan::CanDataFrame;
+use tokio::time::sleep;
+
+use crate::caniot;
+
+use super::{
+    parse_candump_line, CanConfig, CanInterfaceError, CanInterfaceTrait, CanRxFrame, CanStats,
+};
+
+fn default_speed() -> f32 {
+    1.0
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct CanReplayConfig {
+    // candump log to replay
+    pub path: String,
+
+    // Replay speed factor (1.0 real time, 10.0 ten times faster), 0 to replay
+    // the frames as fast as possible
+    #[serde(default = "default_speed")]
+    pub speed: f32,
+
+    // Timestamp the frames with the time they were logged at, rather than the
+    // time they are due on the replay timeline
+    #[serde(default)]
+    pub logged_timestamps: bool,
+}
+
+/// Replays a candump log into the controller, as if frames were received from the bus.
+///
+/// Only frames sent by the devices are replayed, queries recorded in the log were
+/// sent by the controller at the time. Frames sent by the controller are dropped.
+pub struct CanInterface {
+    stats: CanStats,
+    speed: f32,
+    logged_timestamps: bool,
+    started_at: Instant,
+    started_at_utc: DateTime<Utc>,
+    first_timestamp: Option<Duration>,
+    frames: VecDeque<(Duration, CanDataFrame)>,
+}
+
+fn is_device_frame(frame: &CanDataFrame) -> bool {
+    match frame.id() {
+        EmbeddedId::Standard(id) => {
+            caniot::Id::from(id.as_raw()).direction == caniot::Direction::Response
+        }
+        EmbeddedId::Extended(_) => false,
+    }
+}
+
+impl CanInterface {
+    pub fn from_log(content: &str, speed: f32) -> Self {
+        let mut frames = VecDeque::new();
+        for (line_number, line) in content.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            match parse_candump_line(line) {
+                Some((timestamp, frame)) if is_device_frame(&frame) => {
+                    frames.push_back((timestamp, frame))
+                }
+                Some(_) => {}
+                None => warn!("Replay: invalid line {}: {}", line_number + 1, line),
+            }
+        }
+
+        Self {
+            stats: CanStats::default(),
+            speed,
+            logged_timestamps: false,
+            started_at: Instant::now(),
+            started_at_utc: Utc::now(),
+            first_timestamp: frames.front().map(|(timestamp, _)| *timestamp),
+            frames,
+        }
+    }
+
+    pub fn with_logged_timestamps(mut self, logged_timestamps: bool) -> Self {
+        self.logged_timestamps = logged_timestamps;
+        self
+    }
+
+    pub fn remaining(&self) -> usize {
+        self.frames.len()
+    }
+
+    // Time after the start of the replay the frame is due, None if replayed as fast as possible
+    fn due_offset(&self, timestamp: Duration) -> Option<Duration> {
+        if self.speed <= 0.0 {
+            return None;
+        }
+        let offset = timestamp.saturating_sub(self.first_timestamp.unwrap_or(timestamp));
+        Some(offset.div_f32(self.speed))
+    }
+
+    // Time to wait before the next frame is due
+    fn time_to_next(&self, timestamp: Duration) -> Option<Duration> {
+        self.due_offset(timestamp)?
+            .checked_sub(self.started_at.elapsed())
+    }
+
+    // Reception time of the frame logged at the timestamp
+    fn rx_timestamp(&self, timestamp: Duration) -> DateTime<Utc> {
+        if self.logged_timestamps {
+            return DateTime::from_timestamp(timestamp.as_secs() as i64, timestamp.subsec_nanos())
+                .unwrap_or_else(Utc::now);
+        }
+
+        // Replayed as fast as possible, the frame is received now
+        self.due_offset(timestamp)
+            .and_then(|offset| chrono::Duration::from_std(offset).ok())
+            .map_or_else(Utc::now, |offset| self.started_at_utc + offset)
+    }
+}
+
+#[async_trait]
+impl CanInterfaceTrait for CanInterface {
+    async fn new(config: &CanConfig) -> Result<Self, CanInterfaceError> {
+        let Some(replay_config) = config.replay.as_ref() else {
+            return Err(CanInterfaceError::MissingConfig("can.replay"));
+        };
+
+        let content = std::fs::read_to_string(&replay_config.path)?;
+        let iface = Self::from_log(&content, replay_config.speed)
+            .with_logged_timestamps(replay_config.logged_timestamps);
+
+        warn!(
+            "Replaying {} frames from {} (speed {})",
+            iface.remaining(),
+            replay_config.path,
+            replay_config.speed
+        );
+
+        Ok(iface)
+    }
+
+    async fn send(&mut self, frame: CanDataFrame) -> Result<(), CanInterfaceError> {
+        debug!("Replay: dropped TX {:?}", frame);
+        self.stats.tx += 1;
+        Ok(())
+    }
+
+    async fn recv_poll(&mut self) -> Option<CanRxFrame> {
+        let Some((timestamp, _)) = self.frames.front() else {
+            // Nothing left to replay
+            return futures::future::pending().await;
+        };
+
+        if let Some(wait) = self.time_to_next(*timestamp) {
+            sleep(wait).await;
+        }
+
+        let (timestamp, frame) = self.frames.pop_front()?;
+        self.stats.rx += 1;
+
+        if self.frames.is_empty() {
+            info!("Replay completed");
+        }
+
+        Some(CanRxFrame {
+            frame,
+            timestamp: self.rx_timestamp(timestamp),
+        })
+    }
+
+    fn get_stats(&self) -> CanStats {
+        self.stats
+    }
+}
@@ -0,0 +1,484 @@
+use std::{
+    fs::{File, OpenOptions},
+    io::{self, ErrorKind, Read, Write},
+    mem,
+    os::{
+        fd::{AsRawFd, RawFd},
+        unix::fs::OpenOptionsExt,
+    },
+    sync::{Arc, Mutex},
+    thread,
+    time::{Duration, Instant},
+};
+
+use chrono::{DateTime, Utc};
+use embedded_can::{ExtendedId, Frame as EmbeddedFrame, Id as EmbeddedId, StandardId};
+use log::error;
+use serde::{Deserialize, Serialize};
+use socketcan::{CanDataFrame, CanRemoteFrame};
+use tokio::sync::mpsc;
+
+use super::{
+    CanBusHealth, CanBusState, CanConfig, CanInterfaceError, CanInterfaceTrait, CanRxFrame,
+    CanStats,
+};
+
+// Frames received and not yet processed by the controller
+const SLCAN_RX_QUEUE_SIZE: usize = 256;
+
+// Read timeout of the serial line, in tenths of seconds (VTIME)
+const SLCAN_READ_TIMEOUT_DS: libc::cc_t = 10;
+
+// Period at which the status flags of the adapter are read
+const SLCAN_STATUS_PERIOD: Duration = Duration::from_secs(1);
+
+// Longest line, extended frame with 8 bytes of data and a timestamp
+const SLCAN_MAX_LINE: usize = 1 + 8 + 1 + 16 + 4;
+
+// Sent by the adapter when a command fails
+const SLCAN_BELL: u8 = 0x07;
+
+// Status flags ("F" command)
+const SLCAN_STATUS_RX_FIFO_FULL: u8 = 0x01;
+const SLCAN_STATUS_TX_FIFO_FULL: u8 = 0x02;
+const SLCAN_STATUS_ERROR_WARNING: u8 = 0x04;
+const SLCAN_STATUS_DATA_OVERRUN: u8 = 0x08;
+const SLCAN_STATUS_ERROR_PASSIVE: u8 = 0x20;
+const SLCAN_STATUS_ARBITRATION_LOST: u8 = 0x40;
+const SLCAN_STATUS_BUS_ERROR: u8 = 0x80;
+
+fn default_bitrate() -> u32 {
+    500_000
+}
+
+fn default_baudrate() -> u32 {
+    115_200
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct CanSlcanConfig {
+    // Serial device of the adapter, e.g. "/dev/ttyUSB0"
+    pub device: String,
+
+    // Bitrate of the CAN bus
+    #[serde(default = "default_bitrate")]
+    pub bitrate: u32,
+
+    // Baudrate of the serial line, ignored by USB CDC adapters
+    #[serde(default = "default_baudrate")]
+    pub baudrate: u32,
+}
+
+/// Message received from an slcan (Lawicel) adapter
+#[derive(Debug)]
+pub enum SlcanMessage {
+    Data(CanDataFrame),
+    Remote(CanRemoteFrame),
+    // Status flags of the adapter
+    Status(u8),
+    // Command acknowledged
+    Ack,
+    // Command refused
+    Error,
+    Invalid(String),
+}
+
+fn bitrate_command(bitrate: u32) -> Option<&'static str> {
+    match bitrate {
+        10_000 => Some("S0"),
+        20_000 => Some("S1"),
+        50_000 => Some("S2"),
+        100_000 => Some("S3"),
+        125_000 => Some("S4"),
+        250_000 => Some("S5"),
+        500_000 => Some("S6"),
+        800_000 => Some("S7"),
+        1_000_000 => Some("S8"),
+        _ => None,
+    }
+}
+
+fn serial_speed(baudrate: u32) -> Option<libc::speed_t> {
+    match baudrate {
+        9600 => Some(libc::B9600),
+        19200 => Some(libc::B19200),
+        38400 => Some(libc::B38400),
+        57600 => Some(libc::B57600),
+        115_200 => Some(libc::B115200),
+        230_400 => Some(libc::B230400),
+        460_800 => Some(libc::B460800),
+        921_600 => Some(libc::B921600),
+        1_000_000 => Some(libc::B1000000),
+        2_000_000 => Some(libc::B2000000),
+        3_000_000 => Some(libc::B3000000),
+        _ => None,
+    }
+}
+
+/// Encode a data frame to be sent to the adapter, e.g. "t1232ABCD\r"
+pub fn encode_frame(frame: &CanDataFrame) -> String {
+    let id = match frame.id() {
+        EmbeddedId::Standard(id) => format!("t{:03X}", id.as_raw()),
+        EmbeddedId::Extended(id) => format!("T{:08X}", id.as_raw()),
+    };
+    let data: String = frame.data().iter().map(|b| format!("{:02X}", b)).collect();
+
+    format!("{}{}{}\r", id, frame.dlc(), data)
+}
+
+fn decode_frame(line: &str) -> Option<SlcanMessage> {
+    if !line.is_ascii() {
+        return None;
+    }
+
+    let (kind, rest) = line.split_at(1);
+    let id_len = match kind {
+        "t" | "r" => 3,
+        "T" | "R" => 8,
+        _ => return None,
+    };
+
+    let raw_id = u32::from_str_radix(rest.get(..id_len)?, 16).ok()?;
+    let id: EmbeddedId = if id_len == 3 {
+        StandardId::new(raw_id.try_into().ok()?)?.into()
+    } else {
+        ExtendedId::new(raw_id)?.into()
+    };
+
+    let dlc = rest.get(id_len..id_len + 1)?.parse::<usize>().ok()?;
+    let rest = &rest[id_len + 1..];
+
+    if kind == "r" || kind == "R" {
+        // Optional timestamp
+        return match rest.len() {
+            0 | 4 => CanRemoteFrame::new_remote(id, dlc).map(SlcanMessage::Remote),
+            _ => None,
+        };
+    }
+
+    // Data, followed by an optional timestamp
+    if dlc > 8 || !(rest.len() == 2 * dlc || rest.len() == 2 * dlc + 4) {
+        return None;
+    }
+    let data = (0..dlc)
+        .map(|i| u8::from_str_radix(&rest[2 * i..2 * i + 2], 16).ok())
+        .collect::<Option<Vec<u8>>>()?;
+
+    CanDataFrame::new(id, &data).map(SlcanMessage::Data)
+}
+
+/// Decode a line received from the adapter, without its terminating '\r'
+pub fn decode_line(line: &[u8]) -> SlcanMessage {
+    let Ok(line) = std::str::from_utf8(line) else {
+        return SlcanMessage::Invalid(format!("{:02X?}", line));
+    };
+
+    let message = match line.chars().next() {
+        // Acknowledge of a command, "z" and "Z" for transmitted frames
+        None => Some(SlcanMessage::Ack),
+        Some('z' | 'Z') if line.len() == 1 => Some(SlcanMessage::Ack),
+        Some('t' | 'T' | 'r' | 'R') => decode_frame(line),
+        Some('F') if line.len() == 3 => u8::from_str_radix(&line[1..], 16)
+            .ok()
+            .map(SlcanMessage::Status),
+        _ => None,
+    };
+
+    message.unwrap_or_else(|| SlcanMessage::Invalid(line.to_string()))
+}
+
+/// Splits the bytes received from the adapter into messages
+#[derive(Default)]
+pub struct SlcanDecoder {
+    line: Vec<u8>,
+}
+
+impl SlcanDecoder {
+    pub fn push(&mut self, byte: u8) -> Option<SlcanMessage> {
+        match byte {
+            b'\r' => Some(decode_line(&mem::take(&mut self.line))),
+            SLCAN_BELL => {
+                self.line.clear();
+                Some(SlcanMessage::Error)
+            }
+            b'\n' => None,
+            byte => {
+                if self.line.len() < SLCAN_MAX_LINE {
+                    self.line.push(byte);
+                }
+                None
+            }
+        }
+    }
+}
+
+/// Update the health of the bus from the status flags of the adapter,
+/// returns the number of error flags set
+pub fn handle_status_flags(
+    health: &mut CanBusHealth,
+    flags: u8,
+    timestamp: DateTime<Utc>,
+) -> usize {
+    if flags & (SLCAN_STATUS_RX_FIFO_FULL | SLCAN_STATUS_TX_FIFO_FULL | SLCAN_STATUS_DATA_OVERRUN)
+        != 0
+    {
+        health.errors.overflow += 1;
+    }
+    if flags & SLCAN_STATUS_ARBITRATION_LOST != 0 {
+        health.errors.lost_arbitration += 1;
+    }
+    if flags & SLCAN_STATUS_BUS_ERROR != 0 {
+        health.errors.bus_error += 1;
+    }
+
+    // Flags are cleared once read, no error state flag means error active
+    health.state = if flags & SLCAN_STATUS_ERROR_PASSIVE != 0 {
+        CanBusState::ErrorPassive
+    } else if flags & SLCAN_STATUS_ERROR_WARNING != 0 {
+        CanBusState::ErrorWarning
+    } else {
+        CanBusState::ErrorActive
+    };
+
+    if flags != 0 {
+        health.last_error = Some(timestamp);
+    }
+
+    flags.count_ones() as usize
+}
+
+fn open_serial(config: &CanSlcanConfig) -> Result<File, CanInterfaceError> {
+    let speed = serial_speed(config.baudrate).ok_or_else(|| {
+        CanInterfaceError::AdapterError(format!("Unsupported baudrate {}", config.baudrate))
+    })?;
+
+    let file = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .custom_flags(libc::O_NOCTTY)
+        .open(&config.device)?;
+    configure_serial(file.as_raw_fd(), speed)?;
+
+    Ok(file)
+}
+
+// Raw mode, reads returning after SLCAN_READ_TIMEOUT_DS if nothing is received
+fn configure_serial(fd: RawFd, speed: libc::speed_t) -> Result<(), io::Error> {
+    let mut tio: libc::termios = unsafe { mem::zeroed() };
+    if unsafe { libc::tcgetattr(fd, &mut tio) } < 0 {
+        return Err(io::Error::last_os_error());
+    }
+
+    unsafe { libc::cfmakeraw(&mut tio) };
+    if unsafe { libc::cfsetspeed(&mut tio, speed) } < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    tio.c_cflag |= libc::CLOCAL | libc::CREAD;
+    tio.c_cc[libc::VMIN] = 0;
+    tio.c_cc[libc::VTIME] = SLCAN_READ_TIMEOUT_DS;
+
+    if unsafe { libc::tcsetattr(fd, libc::TCSANOW, &tio) } < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    if unsafe { libc::tcflush(fd, libc::TCIOFLUSH) } < 0 {
+        return Err(io::Error::last_os_error());
+    }
+
+    Ok(())
+}
+
+// Send a command and wait for the adapter to acknowledge it
+fn command(file: &mut File, cmd: &str) -> Result<(), CanInterfaceError> {
+    file.write_all(format!("{}\r", cmd).as_bytes())?;
+
+    let mut decoder = SlcanDecoder::default();
+    let mut byte = [0u8; 1];
+    loop {
+        if file.read(&mut byte)? == 0 {
+            return Err(CanInterfaceError::AdapterError(format!(
+                "No response to command {}",
+                cmd
+            )));
+        }
+
+        match decoder.push(byte[0]) {
+            Some(SlcanMessage::Ack) => return Ok(()),
+            Some(SlcanMessage::Error) => {
+                return Err(CanInterfaceError::AdapterError(format!(
+                    "Command {} refused",
+                    cmd
+                )))
+            }
+            _ => {}
+        }
+    }
+}
+
+type RxResult = Result<(SlcanMessage, DateTime<Utc>), io::Error>;
+
+fn rx_thread(mut reader: File, writer: Arc<Mutex<File>>, queue: mpsc::Sender<RxResult>) {
+    let mut decoder = SlcanDecoder::default();
+    let mut buf = [0u8; 64];
+    let mut status_polled_at = Instant::now();
+
+    while !queue.is_closed() {
+        if status_polled_at.elapsed() >= SLCAN_STATUS_PERIOD {
+            status_polled_at = Instant::now();
+            if let Ok(mut writer) = writer.lock() {
+                let _ = writer.write_all(b"F\r");
+            }
+        }
+
+        let len = match reader.read(&mut buf) {
+            Ok(len) => len,
+            Err(err) if err.kind() == ErrorKind::Interrupted => continue,
+            Err(err) => {
+                // The adapter has been disconnected
+                let _ = queue.blocking_send(Err(err));
+                break;
+            }
+        };
+
+        let timestamp = Utc::now();
+        for byte in &buf[..len] {
+            if let Some(message) = decoder.push(*byte) {
+                if queue.blocking_send(Ok((message, timestamp))).is_err() {
+                    break;
+                }
+            }
+        }
+    }
+
+    debug!("slcan RX thread exiting");
+}
+
+/// CAN interface through a serial adapter speaking the slcan (Lawicel) protocol.
+///
+/// Lines are received by a dedicated thread, which also polls the status flags
+/// of the adapter.
+pub struct CanInterface {
+    device: String,
+    writer: Arc<Mutex<File>>,
+    rx_queue: mpsc::Receiver<RxResult>,
+    stats: CanStats,
+
+    // Bus state reported by the status flags
+    health: CanBusHealth,
+}
+
+impl CanInterface {
+    fn write(&self, line: &str) -> Result<(), CanInterfaceError> {
+        let mut writer = self
+            .writer
+            .lock()
+            .map_err(|_| CanInterfaceError::AdapterError("Serial line poisoned".to_string()))?;
+        writer.write_all(line.as_bytes())?;
+        Ok(())
+    }
+}
+
+impl Drop for CanInterface {
+    fn drop(&mut self) {
+        let _ = self.write("C\r");
+    }
+}
+
+#[async_trait]
+impl CanInterfaceTrait for CanInterface {
+    async fn new(config: &CanConfig) -> Result<Self, CanInterfaceError> {
+        let Some(slcan_config) = config.slcan.as_ref() else {
+            return Err(CanInterfaceError::MissingConfig("can.slcan"));
+        };
+
+        let bitrate = bitrate_command(slcan_config.bitrate).ok_or_else(|| {
+            CanInterfaceError::AdapterError(format!("Unsupported bitrate {}", slcan_config.bitrate))
+        })?;
+
+        let mut file = open_serial(slcan_config)?;
+
+        // The channel may be left open, closing a closed channel is refused
+        let _ = command(&mut file, "C");
+        command(&mut file, bitrate)?;
+        command(&mut file, "O")?;
+
+        warn!(
+            "Using slcan adapter {} ({} bit/s)",
+            slcan_config.device, slcan_config.bitrate
+        );
+
+        let reader = file.try_clone()?;
+        let writer = Arc::new(Mutex::new(file));
+        let (sender, rx_queue) = mpsc::channel(SLCAN_RX_QUEUE_SIZE);
+
+        let rx_writer = writer.clone();
+        thread::Builder::new()
+            .name(format!("slcan-rx-{}", config.get_name()))
+            .spawn(move || rx_thread(reader, rx_writer, sender))?;
+
+        Ok(Self {
+            device: slcan_config.device.clone(),
+            writer,
+            rx_queue,
+            stats: CanStats::default(),
+            health: CanBusHealth::default(),
+        })
+    }
+
+    async fn send(&mut self, frame: CanDataFrame) -> Result<(), CanInterfaceError> {
+        self.write(&encode_frame(&frame))?;
+        self.stats.tx += 1;
+        Ok(())
+    }
+
+    async fn recv_poll(&mut self) -> Option<CanRxFrame> {
+        // Acknowledges and status flags are handled here, only data frames are
+        // returned to the controller
+        loop {
+            // Nothing more to receive once the adapter is disconnected
+            let Some(result) = self.rx_queue.recv().await else {
+                return futures::future::pending().await;
+            };
+
+            match result {
+                Ok((SlcanMessage::Data(frame), timestamp)) => {
+                    self.stats.rx += 1;
+                    return Some(CanRxFrame { frame, timestamp });
+                }
+                Ok((SlcanMessage::Remote(frame), _)) => {
+                    warn!("Unhandled {:?}", frame);
+                    self.stats.unhandled += 1;
+                }
+                Ok((SlcanMessage::Status(flags), timestamp)) => {
+                    let state = self.health.state;
+                    self.stats.adapter_errors +=
+                        handle_status_flags(&mut self.health, flags, timestamp);
+                    if self.health.state != state {
+                        warn!("{} {:?} -> {:?}", self.device, state, self.health.state);
+                    }
+                }
+                Ok((SlcanMessage::Ack, _)) => {}
+                Ok((SlcanMessage::Error, _)) => {
+                    // Frame refused, e.g. TX FIFO of the adapter full
+                    warn!("{} command refused by the adapter", self.device);
+                    self.stats.err += 1;
+                }
+                Ok((SlcanMessage::Invalid(line), _)) => {
+                    warn!("{} invalid line {:?}", self.device, line);
+                    self.stats.err += 1;
+                }
+                Err(err) => {
+                    error!("{}: {}", self.device, err);
+                    self.stats.err += 1;
+                }
+            }
+        }
+    }
+
+    fn get_stats(&self) -> CanStats {
+        self.stats
+    }
+
+    fn get_health(&self) -> CanBusHealth {
+        self.health
+    }
+}
@@ -0,0 +1,168 @@
+use std::{
+    ffi::CStr,
+    fs::File,
+    io::{Read, Write},
+    os::fd::FromRawFd,
+    sync::mpsc,
+    thread,
+    time::Duration,
+};
+
+use embedded_can::{ExtendedId, Frame as EmbeddedFrame, StandardId};
+use socketcan::CanDataFrame;
+
+use super::{
+    slcan::{decode_line, encode_frame, CanInterface, SlcanDecoder, SlcanMessage},
+    CanBackend, CanBusState, CanConfig, CanInterfaceTrait, CanSlcanConfig,
+};
+
+#[test]
+fn test_slcan_encode_decode() {
+    let frame = CanDataFrame::new(StandardId::new(0x123).unwrap(), &[0x2A, 0xBC]).unwrap();
+    assert_eq!(encode_frame(&frame), "t12322ABC\r");
+
+    let frame = CanDataFrame::new(ExtendedId::new(0x1234ABCD).unwrap(), &[]).unwrap();
+    assert_eq!(encode_frame(&frame), "T1234ABCD0\r");
+
+    // Data frame, with and without timestamp
+    for line in ["t0A92DEAD", "t0A92DEAD1F2E"] {
+        match decode_line(line.as_bytes()) {
+            SlcanMessage::Data(frame) => {
+                assert_eq!(frame.id(), StandardId::new(0x0A9).unwrap().into());
+                assert_eq!(frame.data(), &[0xDE, 0xAD]);
+            }
+            message => panic!("Unexpected {:?}", message),
+        }
+    }
+
+    assert!(matches!(
+        decode_line(b"R1234ABCD4"),
+        SlcanMessage::Remote(_)
+    ));
+    assert!(matches!(decode_line(b"F24"), SlcanMessage::Status(0x24)));
+    assert!(matches!(decode_line(b""), SlcanMessage::Ack));
+    assert!(matches!(decode_line(b"z"), SlcanMessage::Ack));
+
+    // Truncated data, standard id out of range and garbage
+    assert!(matches!(decode_line(b"t0A92DE"), SlcanMessage::Invalid(_)));
+    assert!(matches!(decode_line(b"tFFF0"), SlcanMessage::Invalid(_)));
+    assert!(matches!(decode_line(b"t0A99"), SlcanMessage::Invalid(_)));
+    assert!(matches!(decode_line(b"hello"), SlcanMessage::Invalid(_)));
+
+    // Lines split by '\r', errors signaled by BEL
+    let mut decoder = SlcanDecoder::default();
+    let messages: Vec<SlcanMessage> = b"\x07z\rt1230\r"
+        .iter()
+        .filter_map(|byte| decoder.push(*byte))
+        .collect();
+    assert!(matches!(
+        messages.as_slice(),
+        [
+            SlcanMessage::Error,
+            SlcanMessage::Ack,
+            SlcanMessage::Data(_)
+        ]
+    ));
+}
+
+// Pseudo terminal, returns the master side and the path of the slave
+fn open_pty() -> (File, String) {
+    unsafe {
+        let master = libc::posix_openpt(libc::O_RDWR | libc::O_NOCTTY);
+        assert!(master >= 0);
+        assert_eq!(libc::grantpt(master), 0);
+        assert_eq!(libc::unlockpt(master), 0);
+
+        let mut name = [0 as libc::c_char; 64];
+        assert_eq!(libc::ptsname_r(master, name.as_mut_ptr(), name.len()), 0);
+        let path = CStr::from_ptr(name.as_ptr()).to_string_lossy().into_owned();
+
+        (File::from_raw_fd(master), path)
+    }
+}
+
+// Adapter answering the commands, forwarding the frames sent to frames_sent
+fn fake_adapter(mut pty: File, frames_sent: mpsc::Sender<String>) {
+    let mut line = Vec::new();
+    let mut byte = [0u8; 1];
+
+    while let Ok(1) = pty.read(&mut byte) {
+        if byte[0] != b'\r' {
+            line.push(byte[0]);
+            continue;
+        }
+
+        let command = String::from_utf8(std::mem::take(&mut line)).unwrap();
+        let answer: &[u8] = match command.as_str() {
+            // Channel already closed
+            "C" => b"\x07",
+            "S6" => b"\r",
+            // Acknowledge, then a frame is received from the bus
+            "O" => b"\rt0A92DEAD\r",
+            // Error warning and error passive, then another frame is received
+            "F" => b"F24\rt0B10\r",
+            frame if frame.starts_with('t') => {
+                let _ = frames_sent.send(frame.to_string());
+                b"z\r"
+            }
+            _ => b"\x07",
+        };
+
+        if pty.write_all(answer).is_err() {
+            break;
+        }
+    }
+}
+
+#[test]
+fn test_slcan_pty() {
+    let (pty, path) = open_pty();
+    let (frames_sent, frames_sent_receiver) = mpsc::channel();
+    thread::spawn(move || fake_adapter(pty, frames_sent));
+
+    let config = CanConfig {
+        backend: CanBackend::Slcan,
+        slcan: Some(CanSlcanConfig {
+            device: path,
+            bitrate: 500_000,
+            baudrate: 115_200,
+        }),
+        ..Default::default()
+    };
+
+    let rt = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .unwrap();
+
+    rt.block_on(async {
+        let mut iface = CanInterface::new(&config).await.unwrap();
+
+        let rx_frame = iface.recv_poll().await.unwrap();
+        assert_eq!(rx_frame.frame.id(), StandardId::new(0x0A9).unwrap().into());
+        assert_eq!(rx_frame.frame.data(), &[0xDE, 0xAD]);
+
+        let frame = CanDataFrame::new(StandardId::new(0x123).unwrap(), &[0x2A, 0xBC]).unwrap();
+        iface.send(frame).await.unwrap();
+        assert_eq!(
+            frames_sent_receiver
+                .recv_timeout(Duration::from_secs(1))
+                .unwrap(),
+            "t12322ABC"
+        );
+
+        // Acknowledge of the frame sent and status flags polled by the adapter
+        // thread are handled before the next frame is returned
+        let rx_frame = tokio::time::timeout(Duration::from_secs(5), iface.recv_poll())
+            .await
+            .expect("Frame not received")
+            .unwrap();
+        assert_eq!(rx_frame.frame.id(), StandardId::new(0x0B1).unwrap().into());
+
+        assert_eq!(iface.get_stats().tx, 1);
+        assert_eq!(iface.get_stats().rx, 2);
+        assert_eq!(iface.get_stats().err, 0);
+        assert_eq!(iface.get_stats().adapter_errors, 2);
+        assert_eq!(iface.get_health().state, CanBusState::ErrorPassive);
+    });
+}
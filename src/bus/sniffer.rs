@@ -0,0 +1,101 @@
+use std::collections::VecDeque;
+
+use chrono::{DateTime, Utc};
+use embedded_can::{Frame as EmbeddedFrame, Id as EmbeddedId};
+use serde::{Deserialize, Serialize};
+use socketcan::CanDataFrame;
+
+fn default_capacity() -> usize {
+    256
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct CanSniffConfig {
+    // Frames kept, the oldest ones are overwritten beyond
+    #[serde(default = "default_capacity")]
+    pub capacity: usize,
+}
+
+impl Default for CanSniffConfig {
+    fn default() -> Self {
+        Self {
+            capacity: default_capacity(),
+        }
+    }
+}
+
+/// Frame received on the bus which is not a valid CANIOT response
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct CanSniffedFrame {
+    pub timestamp: DateTime<Utc>,
+    pub id: u32,
+    pub extended: bool,
+    pub data: Vec<u8>,
+
+    // Why the frame could not be handled as a CANIOT response
+    pub error: String,
+}
+
+#[derive(Serialize, Debug, Clone, Copy, Default)]
+pub struct CanSnifferStats {
+    pub sniffed: usize,     // Frames kept since the start
+    pub overwritten: usize, // Frames overwritten before being read
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct CanBusSniffedFrames {
+    pub name: String,
+    pub stats: CanSnifferStats,
+    pub frames: Vec<CanSniffedFrame>,
+}
+
+/// Ring buffer of the last frames of third-party nodes sharing the bus
+pub struct CanSniffer {
+    capacity: usize,
+    frames: VecDeque<CanSniffedFrame>,
+    pub stats: CanSnifferStats,
+}
+
+impl CanSniffer {
+    pub fn new(config: &CanSniffConfig) -> Self {
+        Self {
+            capacity: config.capacity,
+            frames: VecDeque::with_capacity(config.capacity),
+            stats: CanSnifferStats::default(),
+        }
+    }
+
+    pub fn push(&mut self, frame: &CanDataFrame, timestamp: DateTime<Utc>, error: String) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        if self.frames.len() == self.capacity {
+            self.frames.pop_front();
+            self.stats.overwritten += 1;
+        }
+
+        let (id, extended) = match frame.id() {
+            EmbeddedId::Standard(id) => (id.as_raw() as u32, false),
+            EmbeddedId::Extended(id) => (id.as_raw(), true),
+        };
+
+        self.frames.push_back(CanSniffedFrame {
+            timestamp,
+            id,
+            extended,
+            data: frame.data().to_vec(),
+            error,
+        });
+        self.stats.sniffed += 1;
+    }
+
+    /// Frames kept, oldest first
+    pub fn get_frames(&self) -> Vec<CanSniffedFrame> {
+        self.frames.iter().cloned().collect()
+    }
+
+    pub fn clear(&mut self) {
+        self.frames.clear();
+    }
+}
@@ -0,0 +1,51 @@
+use chrono::Utc;
+use embedded_can::{ExtendedId, StandardId};
+use socketcan::CanDataFrame;
+
+use super::{CanConfig, CanFilterConfig, CanSniffConfig, CanSniffer};
+
+#[test]
+fn test_sniffer_ring_buffer() {
+    let mut sniffer = CanSniffer::new(&CanSniffConfig { capacity: 2 });
+
+    for id in 0..3 {
+        let frame = CanDataFrame::new(ExtendedId::new(id).unwrap(), &[id as u8]).unwrap();
+        sniffer.push(&frame, Utc::now(), "foreign".to_string());
+    }
+    let frame = CanDataFrame::new(StandardId::new(0x7FF).unwrap(), &[]).unwrap();
+    sniffer.push(&frame, Utc::now(), "query".to_string());
+
+    // The oldest frames are overwritten
+    let frames = sniffer.get_frames();
+    assert_eq!(frames.len(), 2);
+    assert_eq!((frames[0].id, frames[0].extended), (2, true));
+    assert_eq!(frames[0].data, vec![2]);
+    assert_eq!((frames[1].id, frames[1].extended), (0x7FF, false));
+    assert_eq!(frames[1].error, "query");
+    assert_eq!(sniffer.stats.sniffed, 4);
+    assert_eq!(sniffer.stats.overwritten, 2);
+
+    sniffer.clear();
+    assert!(sniffer.get_frames().is_empty());
+    assert_eq!(sniffer.stats.sniffed, 4);
+}
+
+#[test]
+fn test_filters_config() {
+    let config: CanConfig = toml::from_str(r#"interface = "can0""#).unwrap();
+    assert_eq!(config.filters, vec![CanFilterConfig::CANIOT]);
+    assert!(config.sniff.is_none());
+
+    let config: CanConfig = toml::from_str(
+        r#"
+        interface = "can0"
+        filters = [{ id = 0, mask = 0 }, { id = 0x100, mask = 0x700, inverted = true }]
+
+        [sniff]
+        "#,
+    )
+    .unwrap();
+    assert_eq!(config.filters[0], CanFilterConfig::ALL);
+    assert!(config.filters[1].inverted);
+    assert_eq!(config.sniff.unwrap().capacity, 256);
+}
@@ -0,0 +1,256 @@
+use std::{
+    collections::VecDeque,
+    mem,
+    time::{Duration, Instant},
+};
+
+use serde::{Deserialize, Serialize};
+use socketcan::CanDataFrame;
+
+use crate::utils::{join_labels, BusLabel, PrometheusExporterTrait};
+
+/// Priority of a frame waiting to be sent, higher priorities are sent first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub enum TxPriority {
+    // Polling and broadcasts
+    Low,
+    // Requests sent by the controller on its own (e.g. reconciliations)
+    Normal,
+    // Actions and queries waited for by a user
+    High,
+}
+
+impl TxPriority {
+    const COUNT: usize = 3;
+
+    fn index(self) -> usize {
+        self as usize
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct CanTxConfig {
+    // Maximum frames per second sent on the bus, 0 for unlimited
+    #[serde(default)]
+    pub max_fps: u32,
+
+    // Frames waiting to be sent, lowest priority frames are dropped beyond
+    #[serde(default = "default_queue_size")]
+    pub queue_size: usize,
+
+    // Delay before sending again when the socket buffer is full (ms)
+    #[serde(default = "default_retry_delay")]
+    pub retry_delay: u32,
+
+    // Attempts to send a frame before it is dropped
+    #[serde(default = "default_max_retries")]
+    pub max_retries: u32,
+
+    // Delay before restarting the controller of a bus-off bus (ms)
+    #[serde(default = "default_bus_off_restart_delay")]
+    pub bus_off_restart_delay: u32,
+}
+
+fn default_queue_size() -> usize {
+    64
+}
+
+fn default_retry_delay() -> u32 {
+    10
+}
+
+fn default_max_retries() -> u32 {
+    10
+}
+
+fn default_bus_off_restart_delay() -> u32 {
+    1000
+}
+
+impl Default for CanTxConfig {
+    fn default() -> Self {
+        Self {
+            max_fps: 0,
+            queue_size: default_queue_size(),
+            retry_delay: default_retry_delay(),
+            max_retries: default_max_retries(),
+            bus_off_restart_delay: default_bus_off_restart_delay(),
+        }
+    }
+}
+
+#[derive(Serialize, Debug, Clone, Copy, Default)]
+pub struct CanTxQueueStats {
+    pub depth: usize,     // Frames waiting to be sent
+    pub max_depth: usize, // Highest depth reached
+    pub dropped: usize,   // Frames dropped, queue full or too many retries
+    pub retries: usize,   // Sends retried because the socket buffer was full
+    pub bus_off: usize,   // Times the bus went bus-off
+    pub restarts: usize,  // Restarts of the controller after bus-off
+}
+
+impl<'a> PrometheusExporterTrait<'a> for CanTxQueueStats {
+    type Label = BusLabel;
+    fn export(&self, labels: impl AsRef<[&'a Self::Label]>) -> String {
+        let str_labels = join_labels(&labels);
+        format!(
+            "bus_can_tx_queue_depth {{{str_labels}}} {}\n\
+            bus_can_tx_queue_max_depth {{{str_labels}}} {}\n\
+            bus_can_tx_dropped {{{str_labels}}} {}\n\
+            bus_can_tx_retries {{{str_labels}}} {}\n\
+            bus_can_bus_off {{{str_labels}}} {}\n\
+            bus_can_restarts {{{str_labels}}} {}\n",
+            self.depth, self.max_depth, self.dropped, self.retries, self.bus_off, self.restarts
+        )
+    }
+}
+
+/// Frames waiting to be sent on a bus, by priority.
+///
+/// The queue paces the frames according to the frames per second limit and
+/// holds them while the socket buffer is full.
+pub struct CanTxQueue {
+    config: CanTxConfig,
+
+    // Frames by priority, oldest first
+    queues: [VecDeque<CanDataFrame>; TxPriority::COUNT],
+
+    // Instant before which no frame is to be sent (rate limit or retry delay)
+    hold_until: Option<Instant>,
+
+    // Failed attempts to send the next frame
+    attempts: u32,
+
+    // Frames dropped once queued, to be reported to their senders
+    dropped: Vec<CanDataFrame>,
+
+    pub stats: CanTxQueueStats,
+}
+
+impl CanTxQueue {
+    pub fn new(config: CanTxConfig) -> Self {
+        Self {
+            config,
+            queues: Default::default(),
+            hold_until: None,
+            attempts: 0,
+            dropped: Vec::new(),
+            stats: CanTxQueueStats::default(),
+        }
+    }
+
+    pub fn config(&self) -> &CanTxConfig {
+        &self.config
+    }
+
+    pub fn len(&self) -> usize {
+        self.queues.iter().map(VecDeque::len).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.queues.iter().all(VecDeque::is_empty)
+    }
+
+    /// Queue the frame, returns false if it is dropped because the queue is full
+    /// of frames of the same or higher priority.
+    ///
+    /// A full queue drops its oldest frame of the lowest priority to make room.
+    pub fn push(&mut self, frame: CanDataFrame, priority: TxPriority) -> bool {
+        if self.len() >= self.config.queue_size {
+            let lowest = self
+                .queues
+                .iter_mut()
+                .take(priority.index())
+                .find(|queue| !queue.is_empty());
+
+            match lowest {
+                Some(queue) => {
+                    self.dropped.extend(queue.pop_front());
+                }
+                None => {
+                    self.stats.dropped += 1;
+                    return false;
+                }
+            }
+            self.stats.dropped += 1;
+        }
+
+        self.queues[priority.index()].push_back(frame);
+        self.update_depth();
+        true
+    }
+
+    /// Frame to be sent now, None if the queue is empty or on hold
+    pub fn peek(&self, now: &Instant) -> Option<&CanDataFrame> {
+        if self.hold_until.is_some_and(|until| *now < until) {
+            return None;
+        }
+
+        self.queues.iter().rev().find_map(VecDeque::front)
+    }
+
+    /// The frame returned by peek() has been sent
+    pub fn sent(&mut self, now: &Instant) {
+        self.pop();
+        self.hold_until = match self.config.max_fps {
+            0 => None,
+            fps => Some(*now + Duration::from_secs(1) / fps),
+        };
+    }
+
+    /// The frame returned by peek() could not be sent because the socket
+    /// buffer is full, it is retried later unless it has been tried too many times
+    pub fn retry(&mut self, now: &Instant) {
+        self.attempts += 1;
+        if self.attempts > self.config.max_retries {
+            self.drop_next();
+        } else {
+            self.stats.retries += 1;
+        }
+        self.hold_until = Some(*now + Duration::from_millis(self.config.retry_delay as u64));
+    }
+
+    /// The frame returned by peek() failed to be sent and will never be
+    pub fn drop_next(&mut self) {
+        if let Some(frame) = self.pop() {
+            self.dropped.push(frame);
+            self.stats.dropped += 1;
+        }
+    }
+
+    /// Frames dropped since the last call, either to make room for higher priority
+    /// frames or after too many attempts to send them
+    pub fn take_dropped(&mut self) -> Vec<CanDataFrame> {
+        mem::take(&mut self.dropped)
+    }
+
+    /// Time until the next frame is to be sent, None if the queue is empty
+    pub fn time_to_next(&self, now: &Instant) -> Option<Duration> {
+        if self.is_empty() {
+            None
+        } else {
+            Some(
+                self.hold_until
+                    .map(|until| until.saturating_duration_since(*now))
+                    .unwrap_or_default(),
+            )
+        }
+    }
+
+    fn pop(&mut self) -> Option<CanDataFrame> {
+        self.attempts = 0;
+        let popped = self
+            .queues
+            .iter_mut()
+            .rev()
+            .find(|queue| !queue.is_empty())
+            .and_then(VecDeque::pop_front);
+        self.update_depth();
+        popped
+    }
+
+    fn update_depth(&mut self) {
+        self.stats.depth = self.len();
+        self.stats.max_depth = self.stats.max_depth.max(self.stats.depth);
+    }
+}
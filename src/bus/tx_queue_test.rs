@@ -0,0 +1,105 @@
+use std::time::{Duration, Instant};
+
+use embedded_can::{Frame as EmbeddedFrame, StandardId};
+use socketcan::CanDataFrame;
+
+use super::{CanTxConfig, CanTxQueue, TxPriority};
+
+fn frame(id: u16) -> CanDataFrame {
+    CanDataFrame::new(StandardId::new(id).unwrap(), &[]).unwrap()
+}
+
+fn next_id(queue: &CanTxQueue, now: &Instant) -> Option<embedded_can::Id> {
+    queue.peek(now).map(|frame| frame.id())
+}
+
+#[test]
+fn test_tx_queue_priorities() {
+    let now = Instant::now();
+    let mut queue = CanTxQueue::new(CanTxConfig::default());
+
+    assert!(queue.push(frame(1), TxPriority::Low));
+    assert!(queue.push(frame(2), TxPriority::High));
+    assert!(queue.push(frame(3), TxPriority::Normal));
+    assert!(queue.push(frame(4), TxPriority::High));
+
+    let mut sent = Vec::new();
+    while let Some(id) = next_id(&queue, &now) {
+        sent.push(id);
+        queue.sent(&now);
+    }
+
+    let expected: Vec<embedded_can::Id> = [2, 4, 3, 1]
+        .into_iter()
+        .map(|id| StandardId::new(id).unwrap().into())
+        .collect();
+    assert_eq!(sent, expected);
+    assert_eq!(queue.stats.depth, 0);
+    assert_eq!(queue.stats.max_depth, 4);
+}
+
+#[test]
+fn test_tx_queue_full() {
+    let now = Instant::now();
+    let mut queue = CanTxQueue::new(CanTxConfig {
+        queue_size: 2,
+        ..Default::default()
+    });
+
+    assert!(queue.push(frame(1), TxPriority::Low));
+    assert!(queue.push(frame(2), TxPriority::High));
+
+    // The low priority frame makes room for the normal one
+    assert!(queue.push(frame(3), TxPriority::Normal));
+    assert_eq!(queue.len(), 2);
+    assert_eq!(queue.stats.dropped, 1);
+
+    // No lower priority frame to drop
+    assert!(!queue.push(frame(4), TxPriority::Normal));
+    assert_eq!(queue.stats.dropped, 2);
+
+    // Only the frame dropped once queued is reported, the rejected one is not queued
+    let dropped: Vec<_> = queue.take_dropped().iter().map(|f| f.id()).collect();
+    assert_eq!(dropped, vec![StandardId::new(1).unwrap().into()]);
+    assert!(queue.take_dropped().is_empty());
+
+    assert_eq!(
+        next_id(&queue, &now),
+        Some(StandardId::new(2).unwrap().into())
+    );
+}
+
+#[test]
+fn test_tx_queue_rate_limit_and_retries() {
+    let now = Instant::now();
+    let mut queue = CanTxQueue::new(CanTxConfig {
+        max_fps: 10,
+        retry_delay: 5,
+        max_retries: 1,
+        ..Default::default()
+    });
+
+    assert_eq!(queue.time_to_next(&now), None);
+
+    queue.push(frame(1), TxPriority::Normal);
+    queue.push(frame(2), TxPriority::Normal);
+    assert_eq!(queue.time_to_next(&now), Some(Duration::ZERO));
+
+    // Next frame held for 100 ms
+    queue.sent(&now);
+    assert!(queue.peek(&now).is_none());
+    assert_eq!(queue.time_to_next(&now), Some(Duration::from_millis(100)));
+
+    // Socket buffer full, retried once then dropped
+    let now = now + Duration::from_millis(100);
+    queue.retry(&now);
+    assert_eq!(queue.stats.retries, 1);
+    assert_eq!(queue.time_to_next(&now), Some(Duration::from_millis(5)));
+
+    let now = now + Duration::from_millis(5);
+    assert!(queue.peek(&now).is_some());
+    queue.retry(&now);
+    assert!(queue.is_empty());
+    assert_eq!(queue.stats.dropped, 1);
+    assert_eq!(queue.take_dropped().len(), 1);
+}
@@ -0,0 +1,235 @@
+use std::{fmt, time::Duration};
+
+use chrono::{DateTime, Utc};
+use num::FromPrimitive;
+use num_derive::FromPrimitive;
+use serde::Serialize;
+
+use super::{Attribute, AttributeType, DeviceId, Endpoint, ErrorCode, ProtocolError};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct Version {
+    pub major: u8,
+    pub minor: u8,
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}", self.major, self.minor)
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct Features(pub u32);
+
+impl Features {
+    pub fn is_set(&self, bit: u8) -> bool {
+        bit < 32 && self.0 & (1 << bit) != 0
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct ConfigFlags {
+    pub error_response: bool,
+    pub telemetry_delay_random: bool,
+    pub telemetry_endpoint: Endpoint,
+}
+
+impl ConfigFlags {
+    const ERROR_RESPONSE: u32 = 1 << 0;
+    const TELEMETRY_DELAY_RANDOM: u32 = 1 << 1;
+    const TELEMETRY_ENDPOINT_SHIFT: u32 = 2;
+    const TELEMETRY_ENDPOINT_MASK: u32 = 0b11;
+
+    fn from_raw(raw: u32) -> Self {
+        let endpoint = (raw >> Self::TELEMETRY_ENDPOINT_SHIFT) & Self::TELEMETRY_ENDPOINT_MASK;
+        Self {
+            error_response: raw & Self::ERROR_RESPONSE != 0,
+            telemetry_delay_random: raw & Self::TELEMETRY_DELAY_RANDOM != 0,
+            // 2 bits always fit in an endpoint
+            telemetry_endpoint: Endpoint::from_u32(endpoint).unwrap(),
+        }
+    }
+
+    fn to_raw(&self) -> u32 {
+        let mut raw = (self.telemetry_endpoint as u32) << Self::TELEMETRY_ENDPOINT_SHIFT;
+        if self.error_response {
+            raw |= Self::ERROR_RESPONSE;
+        }
+        if self.telemetry_delay_random {
+            raw |= Self::TELEMETRY_DELAY_RANDOM;
+        }
+        raw
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct Location {
+    pub region: [u8; 2],
+    pub country: [u8; 2],
+}
+
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}/{}",
+            String::from_utf8_lossy(&self.region),
+            String::from_utf8_lossy(&self.country)
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, FromPrimitive, Serialize)]
+pub enum ResetReason {
+    Unknown = 0,
+    PowerOn = 1,
+    Watchdog = 2,
+    External = 3,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub enum AttributeValue {
+    U32(u32),
+    DeviceId(DeviceId),
+    Version(Version),
+    StringPart([u8; 4]),
+    // Reassembled multi-part attribute
+    String(String),
+    Timestamp(DateTime<Utc>),
+    Duration(Duration),
+    Features(Features),
+    ConfigFlags(ConfigFlags),
+    // Offset to UTC in seconds
+    Timezone(i32),
+    Location(Location),
+    IoMask(u32),
+    ErrorCode(ErrorCode),
+    ResetReason(ResetReason),
+}
+
+impl AttributeValue {
+    pub fn decode(value_type: AttributeType, raw: u32) -> Result<Self, ProtocolError> {
+        let value = match value_type {
+            AttributeType::U32 => AttributeValue::U32(raw),
+            AttributeType::DeviceId => AttributeValue::DeviceId(
+                u8::try_from(raw)
+                    .map_err(|_| ProtocolError::AttributeValueDecodeError)
+                    .and_then(DeviceId::try_from)?,
+            ),
+            AttributeType::Version => AttributeValue::Version(Version {
+                major: (raw >> 8) as u8,
+                minor: raw as u8,
+            }),
+            AttributeType::StringPart => AttributeValue::StringPart(raw.to_le_bytes()),
+            AttributeType::Timestamp => AttributeValue::Timestamp(
+                DateTime::from_timestamp(raw as i64, 0)
+                    .ok_or(ProtocolError::AttributeValueDecodeError)?,
+            ),
+            AttributeType::DurationSeconds => {
+                AttributeValue::Duration(Duration::from_secs(raw as u64))
+            }
+            AttributeType::DurationMillis => {
+                AttributeValue::Duration(Duration::from_millis(raw as u64))
+            }
+            AttributeType::Features => AttributeValue::Features(Features(raw)),
+            AttributeType::ConfigFlags => AttributeValue::ConfigFlags(ConfigFlags::from_raw(raw)),
+            AttributeType::Timezone => AttributeValue::Timezone(raw as i32),
+            AttributeType::Location => {
+                let bytes = raw.to_le_bytes();
+                AttributeValue::Location(Location {
+                    region: [bytes[0], bytes[1]],
+                    country: [bytes[2], bytes[3]],
+                })
+            }
+            AttributeType::IoMask => AttributeValue::IoMask(raw),
+            AttributeType::ErrorCode => AttributeValue::ErrorCode(
+                // Devices may report errors as negative values
+                ErrorCode::from_i32((raw as i32).abs())
+                    .ok_or(ProtocolError::AttributeValueDecodeError)?,
+            ),
+            AttributeType::ResetReason => AttributeValue::ResetReason(
+                ResetReason::from_u32(raw).ok_or(ProtocolError::AttributeValueDecodeError)?,
+            ),
+        };
+
+        Ok(value)
+    }
+
+    pub fn encode(&self, value_type: AttributeType) -> Result<u32, ProtocolError> {
+        let raw = match (value_type, self) {
+            (AttributeType::U32, AttributeValue::U32(raw)) => *raw,
+            (AttributeType::DeviceId, AttributeValue::DeviceId(did)) => did.to_u8() as u32,
+            (AttributeType::Version, AttributeValue::Version(version)) => {
+                ((version.major as u32) << 8) | version.minor as u32
+            }
+            (AttributeType::StringPart, AttributeValue::StringPart(bytes)) => {
+                u32::from_le_bytes(*bytes)
+            }
+            (AttributeType::Timestamp, AttributeValue::Timestamp(timestamp)) => {
+                u32::try_from(timestamp.timestamp())
+                    .map_err(|_| ProtocolError::AttributeValueEncodeError)?
+            }
+            (AttributeType::DurationSeconds, AttributeValue::Duration(duration)) => {
+                u32::try_from(duration.as_secs())
+                    .map_err(|_| ProtocolError::AttributeValueEncodeError)?
+            }
+            (AttributeType::DurationMillis, AttributeValue::Duration(duration)) => {
+                u32::try_from(duration.as_millis())
+                    .map_err(|_| ProtocolError::AttributeValueEncodeError)?
+            }
+            (AttributeType::Features, AttributeValue::Features(features)) => features.0,
+            (AttributeType::ConfigFlags, AttributeValue::ConfigFlags(flags)) => flags.to_raw(),
+            (AttributeType::Timezone, AttributeValue::Timezone(offset)) => *offset as u32,
+            (AttributeType::Location, AttributeValue::Location(location)) => u32::from_le_bytes([
+                location.region[0],
+                location.region[1],
+                location.country[0],
+                location.country[1],
+            ]),
+            (AttributeType::IoMask, AttributeValue::IoMask(mask)) => *mask,
+            (AttributeType::ErrorCode, AttributeValue::ErrorCode(code)) => code.value() as u32,
+            (AttributeType::ResetReason, AttributeValue::ResetReason(reason)) => *reason as u32,
+            _ => return Err(ProtocolError::AttributeValueTypeMismatch),
+        };
+
+        Ok(raw)
+    }
+}
+
+impl fmt::Display for AttributeValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AttributeValue::U32(value) => write!(f, "{}", value),
+            AttributeValue::DeviceId(did) => write!(f, "{}", did),
+            AttributeValue::Version(version) => write!(f, "{}", version),
+            AttributeValue::StringPart(bytes) => {
+                write!(
+                    f,
+                    "{}",
+                    String::from_utf8_lossy(bytes).trim_end_matches('\0')
+                )
+            }
+            AttributeValue::String(value) => write!(f, "{}", value),
+            AttributeValue::Timestamp(timestamp) => write!(f, "{}", timestamp),
+            AttributeValue::Duration(duration) => write!(f, "{:?}", duration),
+            AttributeValue::Features(features) => write!(f, "0x{:08x}", features.0),
+            AttributeValue::ConfigFlags(flags) => write!(f, "{:?}", flags),
+            AttributeValue::Timezone(offset) => write!(f, "UTC{:+}s", offset),
+            AttributeValue::Location(location) => write!(f, "{}", location),
+            AttributeValue::IoMask(mask) => write!(f, "0x{:08x}", mask),
+            AttributeValue::ErrorCode(code) => write!(f, "{:?}", code),
+            AttributeValue::ResetReason(reason) => write!(f, "{:?}", reason),
+        }
+    }
+}
+
+impl Attribute {
+    pub fn decode(&self, raw: u32) -> Result<AttributeValue, ProtocolError> {
+        AttributeValue::decode(self.value_type(), raw)
+    }
+
+    pub fn encode(&self, value: &AttributeValue) -> Result<u32, ProtocolError> {
+        value.encode(self.value_type())
+    }
+}
@@ -1,9 +1,10 @@
 use num_derive::FromPrimitive;
+use strum::{EnumIter, EnumString};
 
 use super::ProtocolError;
 
 #[repr(u16)]
-#[derive(Debug, Clone, Copy, PartialEq, FromPrimitive)]
+#[derive(Debug, Clone, Copy, PartialEq, FromPrimitive, EnumIter, EnumString)]
 pub enum Attribute {
     NodeId = 0x0000,
     Version = 0x0010,
@@ -88,3 +89,186 @@ impl TryFrom<u16> for Attribute {
         }
     }
 }
+
+/// Encoding of the 32-bit value carried by an attribute
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AttributeType {
+    U32,
+    DeviceId,
+    Version,
+    // 4 bytes of a string spread over several attribute parts
+    StringPart,
+    // Seconds since UNIX epoch
+    Timestamp,
+    DurationSeconds,
+    DurationMillis,
+    Features,
+    ConfigFlags,
+    Timezone,
+    Location,
+    // One bit per IO
+    IoMask,
+    ErrorCode,
+    ResetReason,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AttributeAccess {
+    ReadOnly,
+    ReadWrite,
+}
+
+impl Attribute {
+    pub fn key(&self) -> u16 {
+        *self as u16
+    }
+
+    pub fn value_type(&self) -> AttributeType {
+        use Attribute::*;
+        match self {
+            NodeId => AttributeType::DeviceId,
+            Version => AttributeType::Version,
+            Name | BuildCommit => AttributeType::StringPart,
+            MagicNumber => AttributeType::U32,
+            BuildDate => AttributeType::Timestamp,
+            Features => AttributeType::Features,
+            SystemUptimeSynced => AttributeType::DurationSeconds,
+            SystemTime => AttributeType::Timestamp,
+            SystemUptime => AttributeType::DurationSeconds,
+            SystemStartTime => AttributeType::Timestamp,
+            SystemLastTelemetry => AttributeType::DurationSeconds,
+            SystemLastTelemetryMsMod => AttributeType::DurationMillis,
+            SystemReceivedTotal
+            | SystemReceivedReadAttr
+            | SystemReceivedWriteAttr
+            | SystemReceivedCommand
+            | SystemReceivedReqTelemetry
+            | SystemReceivedIgnored
+            | SystemSentTotal
+            | SystemSentTelemetry
+            | SystemUnused4
+            | SystemUnused5
+            | SystemBattery => AttributeType::U32,
+            SystemLastCommandError | SystemLastTelemetryError => AttributeType::ErrorCode,
+            ConfigTelemetryPeriod
+            | ConfigTelemetryDelay
+            | ConfigTelemetryDelayMin
+            | ConfigTelemetryDelayMax => AttributeType::DurationMillis,
+            ConfigFlags => AttributeType::ConfigFlags,
+            ConfigTimezone => AttributeType::Timezone,
+            ConfigLocation => AttributeType::Location,
+            ConfigCls0GpioPulseDurationOc1
+            | ConfigCls0GpioPulseDurationOc2
+            | ConfigCls0GpioPulseDurationRl1
+            | ConfigCls0GpioPulseDurationRl2 => AttributeType::DurationMillis,
+            ConfigCls0GpioOutputsDefault | ConfigCls0GpioMaskTelemetryOnChange => {
+                AttributeType::IoMask
+            }
+            ConfigCls1GpioPulseDurationPc0
+            | ConfigCls1GpioPulseDurationPc1
+            | ConfigCls1GpioPulseDurationPc2
+            | ConfigCls1GpioPulseDurationPc3
+            | ConfigCls1GpioPulseDurationPd0
+            | ConfigCls1GpioPulseDurationPd1
+            | ConfigCls1GpioPulseDurationPd2
+            | ConfigCls1GpioPulseDurationPd3
+            | ConfigCls1GpioPulseDurationPei0
+            | ConfigCls1GpioPulseDurationPei1
+            | ConfigCls1GpioPulseDurationPei2
+            | ConfigCls1GpioPulseDurationPei3
+            | ConfigCls1GpioPulseDurationPei4
+            | ConfigCls1GpioPulseDurationPei5
+            | ConfigCls1GpioPulseDurationPei6
+            | ConfigCls1GpioPulseDurationPei7
+            | ConfigCls1GpioPulseDurationPb0
+            | ConfigCls1GpioPulseDurationPe0
+            | ConfigCls1GpioPulseDurationPe1
+            | ConfigCls1GpioPulseDurationReserved => AttributeType::DurationMillis,
+            ConfigCls1GpioDirections
+            | ConfigCls1GpioOutputsDefault
+            | ConfigCls1GpioMaskTelemetryOnChange => AttributeType::IoMask,
+            DiagResetCount
+            | DiagResetCountUnknown
+            | DiagResetCountPowerOn
+            | DiagResetCountWatchdog
+            | DiagResetCountExternal => AttributeType::U32,
+            DiagLastResetReason => AttributeType::ResetReason,
+        }
+    }
+
+    pub fn access(&self) -> AttributeAccess {
+        match self {
+            // The system time is written by the controller for time synchronization
+            Attribute::SystemTime => AttributeAccess::ReadWrite,
+            attr if attr.is_config() => AttributeAccess::ReadWrite,
+            _ => AttributeAccess::ReadOnly,
+        }
+    }
+
+    pub fn is_writable(&self) -> bool {
+        self.access() == AttributeAccess::ReadWrite
+    }
+
+    pub fn is_config(&self) -> bool {
+        (0x2000..0x3000).contains(&self.key())
+    }
+
+    /// Class the attribute is restricted to, None if available on all classes
+    pub fn class(&self) -> Option<u8> {
+        match self.key() {
+            0x2070..=0x20C0 => Some(0),
+            0x20D0..=0x2230 => Some(1),
+            _ => None,
+        }
+    }
+
+    /// Keys reserved by the protocol, not implemented by the devices
+    pub fn is_reserved(&self) -> bool {
+        matches!(
+            self,
+            Attribute::SystemUnused4
+                | Attribute::SystemUnused5
+                | Attribute::ConfigCls1GpioPulseDurationReserved
+        )
+    }
+
+    pub fn is_available_for_class(&self, class: u8) -> bool {
+        self.class().map_or(true, |c| c == class)
+    }
+
+    /// Number of 4-byte parts the attribute value spans
+    pub fn parts_count(&self) -> u8 {
+        match self {
+            Attribute::Name => 8,
+            Attribute::BuildCommit => 5,
+            _ => 1,
+        }
+    }
+
+    pub fn is_multi_part(&self) -> bool {
+        self.parts_count() > 1
+    }
+
+    pub fn part_key(&self, part: u8) -> u16 {
+        self.key() | (part & 0xf) as u16
+    }
+
+    /// Build the string value of a multi-part attribute from its reassembled parts
+    pub fn decode_string(&self, bytes: &[u8]) -> String {
+        match self {
+            // The commit hash is stored as raw bytes
+            Attribute::BuildCommit => bytes.iter().map(|b| format!("{:02x}", b)).collect(),
+            _ => {
+                let end = bytes.iter().position(|b| *b == 0).unwrap_or(bytes.len());
+                String::from_utf8_lossy(&bytes[..end]).into_owned()
+            }
+        }
+    }
+
+    /// Whether the reassembled bytes are a complete value, the string may end
+    /// before the last part is read
+    pub fn is_string_complete(&self, bytes: &[u8]) -> bool {
+        bytes.len() >= self.parts_count() as usize * 4
+            || (*self != Attribute::BuildCommit && bytes.contains(&0))
+    }
+}
@@ -1,4 +1,10 @@
+use std::time::Duration;
+
+use chrono::DateTime;
+
+use super::attribute_value::*;
 use super::attributes::*;
+use super::{DeviceId, Endpoint, ErrorCode};
 
 #[test]
 fn test_attributes() {
@@ -11,3 +17,118 @@ fn test_attributes() {
         );
     }
 }
+
+#[test]
+fn test_attributes_metadata() {
+    assert_eq!(
+        Attribute::ConfigTelemetryPeriod.value_type(),
+        AttributeType::DurationMillis
+    );
+    assert_eq!(
+        Attribute::SystemUptime.value_type(),
+        AttributeType::DurationSeconds
+    );
+
+    assert!(Attribute::ConfigTelemetryPeriod.is_writable());
+    assert!(Attribute::SystemTime.is_writable());
+    assert!(!Attribute::SystemUptime.is_writable());
+    assert!(!Attribute::NodeId.is_writable());
+
+    assert_eq!(Attribute::ConfigFlags.class(), None);
+    assert_eq!(Attribute::ConfigCls0GpioPulseDurationOc1.class(), Some(0));
+    assert_eq!(
+        Attribute::ConfigCls0GpioMaskTelemetryOnChange.class(),
+        Some(0)
+    );
+    assert_eq!(Attribute::ConfigCls1GpioPulseDurationPc0.class(), Some(1));
+    assert_eq!(
+        Attribute::ConfigCls1GpioMaskTelemetryOnChange.class(),
+        Some(1)
+    );
+    assert!(Attribute::ConfigCls1GpioDirections.is_available_for_class(1));
+    assert!(!Attribute::ConfigCls1GpioDirections.is_available_for_class(0));
+}
+
+#[test]
+fn test_attributes_values() {
+    assert_eq!(
+        Attribute::ConfigTelemetryPeriod.decode(60000).unwrap(),
+        AttributeValue::Duration(Duration::from_secs(60))
+    );
+    assert_eq!(
+        Attribute::SystemUptime.decode(3600).unwrap(),
+        AttributeValue::Duration(Duration::from_secs(3600))
+    );
+    assert_eq!(
+        Attribute::NodeId.decode(0x18).unwrap(),
+        AttributeValue::DeviceId(DeviceId::from_u8(0x18))
+    );
+    assert!(Attribute::NodeId.decode(0x40).is_err());
+    assert_eq!(
+        Attribute::Version.decode(0x0102).unwrap(),
+        AttributeValue::Version(Version { major: 1, minor: 2 })
+    );
+    assert_eq!(
+        Attribute::SystemTime.decode(1_700_000_000).unwrap(),
+        AttributeValue::Timestamp(DateTime::from_timestamp(1_700_000_000, 0).unwrap())
+    );
+    assert_eq!(
+        Attribute::ConfigTimezone.decode(-3600i32 as u32).unwrap(),
+        AttributeValue::Timezone(-3600)
+    );
+    assert_eq!(
+        Attribute::DiagLastResetReason.decode(2).unwrap(),
+        AttributeValue::ResetReason(ResetReason::Watchdog)
+    );
+    assert_eq!(
+        Attribute::SystemLastCommandError.decode(0x3A03).unwrap(),
+        AttributeValue::ErrorCode(ErrorCode::Ekey)
+    );
+    assert_eq!(
+        Attribute::ConfigFlags.decode(0b1101).unwrap(),
+        AttributeValue::ConfigFlags(ConfigFlags {
+            error_response: true,
+            telemetry_delay_random: false,
+            telemetry_endpoint: Endpoint::BoardControl,
+        })
+    );
+
+    // Every decoded value must encode back to the same raw value
+    for (attr, raw) in [
+        (Attribute::ConfigTelemetryPeriod, 60000),
+        (Attribute::SystemTime, 1_700_000_000),
+        (Attribute::ConfigFlags, 0b1101),
+        (Attribute::ConfigTimezone, -3600i32 as u32),
+        (Attribute::ConfigLocation, 0x52_46_45_55),
+        (Attribute::Version, 0x0102),
+        (Attribute::Features, 0xdead_beef),
+    ] {
+        let value = attr.decode(raw).unwrap();
+        assert_eq!(attr.encode(&value).unwrap(), raw);
+    }
+
+    assert!(Attribute::ConfigTelemetryPeriod
+        .encode(&AttributeValue::U32(1000))
+        .is_err());
+}
+
+#[test]
+fn test_attributes_string() {
+    assert!(Attribute::Name.is_multi_part());
+    assert!(!Attribute::NodeId.is_multi_part());
+    assert_eq!(Attribute::Name.part_key(0), 0x0020);
+    assert_eq!(Attribute::Name.part_key(3), 0x0023);
+
+    let name = b"garage\0\0";
+    assert!(!Attribute::Name.is_string_complete(&name[..4]));
+    assert!(Attribute::Name.is_string_complete(name));
+    assert_eq!(Attribute::Name.decode_string(name), "garage");
+
+    let commit = [0xabu8; 20];
+    assert!(!Attribute::BuildCommit.is_string_complete(&commit[..16]));
+    assert!(Attribute::BuildCommit.is_string_complete(&commit));
+    assert_eq!(
+        Attribute::BuildCommit.decode_string(&commit),
+        "ab".repeat(20)
+    );
+}
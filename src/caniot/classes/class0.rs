@@ -1,10 +1,14 @@
-use super::traits::{Class, ClassCommandTrait, ClassTelemetryTrait, TempSensType};
+use super::{
+    layout::{ClassLayout, IoKind},
+    traits::{Class, ClassCommandTrait, ClassTelemetryTrait, TempSensType},
+};
 use crate::{
     caniot::{ClCd, Payload, ProtocolError, Temperature, Ty, Xps},
     utils::math::avg_of_slice_opt,
 };
 use num::FromPrimitive;
 use serde::Serialize;
+use strum::EnumIter;
 
 #[derive(Debug, Default, Clone, Copy, PartialEq, Serialize)]
 pub struct Telemetry {
@@ -38,6 +42,23 @@ impl ClassTelemetryTrait for Telemetry {
             TempSensType::Any => self.temp_in.to_celsius(),
         }
     }
+
+    fn get_ios(&self) -> Vec<bool> {
+        vec![
+            self.oc1, self.oc2, self.rl1, self.rl2, self.in1, self.in2, self.in3, self.in4,
+        ]
+    }
+
+    fn get_pulses(&self) -> Vec<bool> {
+        vec![self.poc1, self.poc2, self.prl1, self.prl2]
+    }
+
+    fn get_temperatures(&self) -> Vec<Option<f32>> {
+        std::iter::once(&self.temp_in)
+            .chain(self.temp_out.iter())
+            .map(|t| t.to_celsius())
+            .collect()
+    }
 }
 
 impl TryFrom<&Payload<Ty>> for Telemetry {
@@ -122,6 +143,44 @@ impl Into<Payload<Ty>> for Telemetry {
     }
 }
 
+impl Telemetry {
+    pub fn get_input(&self, input: Input) -> bool {
+        match input {
+            Input::In1 => self.in1,
+            Input::In2 => self.in2,
+            Input::In3 => self.in3,
+            Input::In4 => self.in4,
+        }
+    }
+
+    pub fn get_output(&self, output: Output) -> bool {
+        match output {
+            Output::Oc1 => self.oc1,
+            Output::Oc2 => self.oc2,
+            Output::Rl1 => self.rl1,
+            Output::Rl2 => self.rl2,
+        }
+    }
+}
+
+/// Class 0 digital inputs
+#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumIter, Serialize)]
+pub enum Input {
+    In1,
+    In2,
+    In3,
+    In4,
+}
+
+/// Class 0 outputs: open-collectors and relays
+#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumIter, Serialize)]
+pub enum Output {
+    Oc1,
+    Oc2,
+    Rl1,
+    Rl2,
+}
+
 #[derive(Debug, Default, Clone, Copy, PartialEq, Serialize)]
 pub struct Command {
     pub coc1: Xps,
@@ -130,6 +189,32 @@ pub struct Command {
     pub crl2: Xps,
 }
 
+impl Command {
+    pub fn get(&self, output: Output) -> Xps {
+        match output {
+            Output::Oc1 => self.coc1,
+            Output::Oc2 => self.coc2,
+            Output::Rl1 => self.crl1,
+            Output::Rl2 => self.crl2,
+        }
+    }
+
+    pub fn set(&mut self, output: Output, xps: Xps) -> &mut Self {
+        match output {
+            Output::Oc1 => self.coc1 = xps,
+            Output::Oc2 => self.coc2 = xps,
+            Output::Rl1 => self.crl1 = xps,
+            Output::Rl2 => self.crl2 = xps,
+        }
+        self
+    }
+
+    pub fn with(mut self, output: Output, xps: Xps) -> Self {
+        self.set(output, xps);
+        self
+    }
+}
+
 impl ClassCommandTrait for Command {
     fn has_effect(&self) -> bool {
         self.coc1 != Xps::None
@@ -178,6 +263,20 @@ pub struct Class0;
 
 impl Class for Class0 {
     const CLASS_ID: u8 = 0;
+    const LAYOUT: ClassLayout = ClassLayout {
+        ios: &[
+            ("oc1", IoKind::Output),
+            ("oc2", IoKind::Output),
+            ("rl1", IoKind::Output),
+            ("rl2", IoKind::Output),
+            ("in1", IoKind::Input),
+            ("in2", IoKind::Input),
+            ("in3", IoKind::Input),
+            ("in4", IoKind::Input),
+        ],
+        pulses: &["oc1", "oc2", "rl1", "rl2"],
+        temperatures: &["board", "ext0", "ext1", "ext2"],
+    };
 
     type Telemetry = Telemetry;
     type Command = Command;
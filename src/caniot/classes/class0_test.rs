@@ -1,4 +1,4 @@
-use crate::caniot::{AsPayload, Temperature};
+use crate::caniot::{traits::Class, AsPayload, BoardClassTelemetry, Temperature, Xps};
 
 use super::class0;
 
@@ -40,3 +40,46 @@ fn telemetry() {
         }
     }
 }
+
+#[test]
+fn command_builder() {
+    let cmd = class0::Command::default()
+        .with(class0::Output::Oc2, Xps::Toggle)
+        .with(class0::Output::Rl1, Xps::PulseOn);
+
+    assert_eq!(cmd.coc1, Xps::None);
+    assert_eq!(cmd.coc2, Xps::Toggle);
+    assert_eq!(cmd.crl1, Xps::PulseOn);
+    assert_eq!(cmd.get(class0::Output::Rl2), Xps::None);
+
+    let deser = class0::Command::try_from_raw(&cmd.to_raw_vec()).unwrap();
+    assert_eq!(deser, cmd);
+}
+
+#[test]
+fn io_snapshot() {
+    let mut telem = class0::Telemetry::default();
+    telem.rl1 = true;
+    telem.in3 = true;
+    telem.prl1 = true;
+    telem.temp_in = Temperature::new(2150);
+
+    let snapshot = BoardClassTelemetry::Class0(telem).io_snapshot();
+    let layout = <class0::Class0 as Class>::LAYOUT;
+
+    assert_eq!(snapshot.class, 0);
+    assert_eq!(snapshot.ios.len(), layout.ios.len());
+    assert_eq!(snapshot.inputs().count(), layout.inputs_count());
+    assert_eq!(snapshot.outputs().count(), layout.outputs_count());
+    assert_eq!(snapshot.get_io("rl1"), Some(true));
+    assert_eq!(snapshot.get_io("in3"), Some(true));
+    assert_eq!(snapshot.get_io("oc1"), Some(false));
+    assert_eq!(snapshot.get_io("pc0"), None);
+    assert!(snapshot
+        .pulses
+        .iter()
+        .all(|pulse| pulse.active == (pulse.output == "rl1")));
+    assert_eq!(snapshot.temperatures.len(), layout.temperatures_count());
+    assert_eq!(snapshot.get_temperature("board"), Some(21.5));
+    assert_eq!(snapshot.get_temperature("ext0"), None);
+}
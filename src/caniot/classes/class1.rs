@@ -6,7 +6,10 @@ use crate::{
     utils::math::avg_of_slice_opt,
 };
 
-use super::traits::{Class, ClassCommandTrait, ClassTelemetryTrait, TempSensType};
+use super::{
+    layout::{ClassLayout, IoKind},
+    traits::{Class, ClassCommandTrait, ClassTelemetryTrait, TempSensType},
+};
 
 pub const CLASS1_IO_COUNT: usize = 19;
 
@@ -31,6 +34,22 @@ impl ClassTelemetryTrait for Telemetry {
             TempSensType::Any => self.temp_in.to_celsius(),
         }
     }
+
+    fn get_ios(&self) -> Vec<bool> {
+        self.ios.to_vec()
+    }
+
+    fn get_pulses(&self) -> Vec<bool> {
+        // Pulse states are not reported by class 1 boards
+        Vec::new()
+    }
+
+    fn get_temperatures(&self) -> Vec<Option<f32>> {
+        std::iter::once(&self.temp_in)
+            .chain(self.temp_out.iter())
+            .map(|t| t.to_celsius())
+            .collect()
+    }
 }
 
 impl TryFrom<&Payload<Ty>> for Telemetry {
@@ -128,28 +147,35 @@ impl Into<Payload<Ty>> for Telemetry {
     }
 }
 
+/// Class 1 IOs, in the order of the telemetry and command payloads
 #[repr(u8)]
-#[derive(EnumIter)]
-pub enum Class1CommandFields {
-    Poc0,
-    Poc1,
-    Poc2,
-    Poc3,
+#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumIter, Serialize)]
+pub enum Pin {
+    Pc0,
+    Pc1,
+    Pc2,
+    Pc3,
     Pd0,
     Pd1,
     Pd2,
     Pd3,
-    Eio0,
-    Eio1,
-    Eio2,
-    Eio3,
-    Eio4,
-    Eio5,
-    Eio6,
-    Eio7,
+    Pei0,
+    Pei1,
+    Pei2,
+    Pei3,
+    Pei4,
+    Pei5,
+    Pei6,
+    Pei7,
     Pb0,
-    Ee0,
-    Ee1,
+    Pe0,
+    Pe1,
+}
+
+impl Telemetry {
+    pub fn get(&self, pin: Pin) -> bool {
+        self.ios[pin as usize]
+    }
 }
 
 #[derive(Debug, Default, Clone, Copy, PartialEq, Serialize)]
@@ -157,6 +183,22 @@ pub struct Command {
     pub ios: [Xps; CLASS1_IO_COUNT],
 }
 
+impl Command {
+    pub fn get(&self, pin: Pin) -> Xps {
+        self.ios[pin as usize]
+    }
+
+    pub fn set(&mut self, pin: Pin, xps: Xps) -> &mut Self {
+        self.ios[pin as usize] = xps;
+        self
+    }
+
+    pub fn with(mut self, pin: Pin, xps: Xps) -> Self {
+        self.set(pin, xps);
+        self
+    }
+}
+
 impl<'a> ClassCommandTrait for Command {
     fn has_effect(&self) -> bool {
         self.ios.iter().any(|&x| x != Xps::None)
@@ -182,8 +224,8 @@ impl TryFrom<&Payload<ClCd>> for Command {
         let payload = payload.as_ref();
         if payload.len() >= 7 {
             Ok(Command {
-                ios: Class1CommandFields::iter()
-                    .map(|field| Xps::get_at(payload, field as usize).unwrap_or_default())
+                ios: Pin::iter()
+                    .map(|pin| Xps::get_at(payload, pin as usize).unwrap_or_default())
                     .collect::<Vec<Xps>>()
                     .try_into()
                     .unwrap(),
@@ -198,6 +240,31 @@ pub struct Class1;
 
 impl Class for Class1 {
     const CLASS_ID: u8 = 1;
+    const LAYOUT: ClassLayout = ClassLayout {
+        ios: &[
+            ("pc0", IoKind::InputOutput),
+            ("pc1", IoKind::InputOutput),
+            ("pc2", IoKind::InputOutput),
+            ("pc3", IoKind::InputOutput),
+            ("pd0", IoKind::InputOutput),
+            ("pd1", IoKind::InputOutput),
+            ("pd2", IoKind::InputOutput),
+            ("pd3", IoKind::InputOutput),
+            ("pei0", IoKind::InputOutput),
+            ("pei1", IoKind::InputOutput),
+            ("pei2", IoKind::InputOutput),
+            ("pei3", IoKind::InputOutput),
+            ("pei4", IoKind::InputOutput),
+            ("pei5", IoKind::InputOutput),
+            ("pei6", IoKind::InputOutput),
+            ("pei7", IoKind::InputOutput),
+            ("pb0", IoKind::InputOutput),
+            ("pe0", IoKind::InputOutput),
+            ("pe1", IoKind::InputOutput),
+        ],
+        pulses: &[],
+        temperatures: &["board", "ext0", "ext1", "ext2"],
+    };
 
     type Telemetry = Telemetry;
     type Command = Command;
@@ -1,6 +1,6 @@
 use num::FromPrimitive;
 
-use crate::caniot::{AsPayload, Payload, Temperature, Ty};
+use crate::caniot::{AsPayload, Payload, Temperature, Ty, Xps};
 
 use super::class1;
 
@@ -60,3 +60,19 @@ fn command() {
         assert_eq!(deser.ios[i], cmd.ios[i]);
     }
 }
+
+#[test]
+fn command_builder() {
+    let cmd = class1::Command::default()
+        .with(class1::Pin::Pc0, Xps::SetOn)
+        .with(class1::Pin::Pei7, Xps::PulseOn)
+        .with(class1::Pin::Pe1, Xps::Toggle);
+
+    assert_eq!(cmd.get(class1::Pin::Pc0), Xps::SetOn);
+    assert_eq!(cmd.get(class1::Pin::Pei7), Xps::PulseOn);
+    assert_eq!(cmd.get(class1::Pin::Pe1), Xps::Toggle);
+    assert_eq!(cmd.get(class1::Pin::Pb0), Xps::None);
+
+    let deser = class1::Command::try_from_raw(&cmd.to_raw_vec()).unwrap();
+    assert_eq!(deser.ios, cmd.ios);
+}
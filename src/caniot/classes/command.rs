@@ -1,6 +1,76 @@
-use crate::caniot::{AsPayload, Cd, Payload, ProtocolError, RequestData, SysCtrl};
+use serde::Serialize;
 
-use super::traits::Class;
+use crate::caniot::{AsPayload, Cd, Payload, ProtocolError, RequestData, SysCtrl, Xps};
+
+use super::{
+    class0, class1,
+    traits::{Class, ClassCommandTrait},
+};
+
+/// Output of a class 0 or class 1 board
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum BoardOutput {
+    Class0(class0::Output),
+    Class1(class1::Pin),
+}
+
+impl BoardOutput {
+    pub fn class_id(&self) -> u8 {
+        match self {
+            BoardOutput::Class0(_) => class0::Class0::CLASS_ID,
+            BoardOutput::Class1(_) => class1::Class1::CLASS_ID,
+        }
+    }
+
+    /// Build the board level command applying the xps to the output only
+    pub fn into_command_request(&self, xps: Xps) -> RequestData {
+        match self {
+            BoardOutput::Class0(output) => {
+                class0::Command::default().with(*output, xps).into_request()
+            }
+            BoardOutput::Class1(pin) => class1::Command::default().with(*pin, xps).into_request(),
+        }
+    }
+}
+
+/// Command of a class 0 or class 1 board, built output by output
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BoardCommand {
+    Class0(class0::Command),
+    Class1(class1::Command),
+}
+
+impl BoardCommand {
+    pub fn new(class_id: u8) -> Option<Self> {
+        match class_id {
+            class0::Class0::CLASS_ID => Some(BoardCommand::Class0(class0::Command::default())),
+            class1::Class1::CLASS_ID => Some(BoardCommand::Class1(class1::Command::default())),
+            _ => None,
+        }
+    }
+
+    /// Set the command of an output, returns false if the output belongs to another class
+    pub fn set(&mut self, output: BoardOutput, xps: Xps) -> bool {
+        match (self, output) {
+            (BoardCommand::Class0(command), BoardOutput::Class0(output)) => {
+                command.set(output, xps);
+                true
+            }
+            (BoardCommand::Class1(command), BoardOutput::Class1(pin)) => {
+                command.set(pin, xps);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    pub fn into_request(&self) -> RequestData {
+        match self {
+            BoardCommand::Class0(command) => command.into_request(),
+            BoardCommand::Class1(command) => command.into_request(),
+        }
+    }
+}
 
 // #[derive(Clone)]
 pub struct BoardClassCommand<C: Class> {
@@ -0,0 +1,123 @@
+use serde::Serialize;
+
+use super::traits::{Class, ClassTelemetryTrait};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum IoKind {
+    Input,
+    Output,
+    // Configurable pin, its level is reported whatever its direction
+    InputOutput,
+}
+
+/// Layout of the IOs and sensors reported in the telemetry of a board class
+#[derive(Debug, Clone, Copy)]
+pub struct ClassLayout {
+    // Digital IOs, in the order returned by ClassTelemetryTrait::get_ios()
+    pub ios: &'static [(&'static str, IoKind)],
+
+    // Outputs which pulse state is reported, in the order returned by
+    // ClassTelemetryTrait::get_pulses()
+    pub pulses: &'static [&'static str],
+
+    // Temperature sensors, in the order returned by ClassTelemetryTrait::get_temperatures()
+    pub temperatures: &'static [&'static str],
+}
+
+impl ClassLayout {
+    pub fn inputs_count(&self) -> usize {
+        self.ios
+            .iter()
+            .filter(|(_, kind)| *kind != IoKind::Output)
+            .count()
+    }
+
+    pub fn outputs_count(&self) -> usize {
+        self.ios
+            .iter()
+            .filter(|(_, kind)| *kind != IoKind::Input)
+            .count()
+    }
+
+    pub fn temperatures_count(&self) -> usize {
+        self.temperatures.len()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct IoState {
+    pub name: &'static str,
+    pub kind: IoKind,
+    pub state: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PulseState {
+    pub output: &'static str,
+    pub active: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TemperatureSensor {
+    pub name: &'static str,
+    pub value: Option<f32>,
+}
+
+/// Class-agnostic view of the IOs and temperatures of a board telemetry
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct IoSnapshot {
+    pub class: u8,
+    pub ios: Vec<IoState>,
+    pub pulses: Vec<PulseState>,
+    pub temperatures: Vec<TemperatureSensor>,
+}
+
+impl IoSnapshot {
+    pub fn from_telemetry<C: Class>(telemetry: &C::Telemetry) -> Self {
+        let layout = &C::LAYOUT;
+
+        Self {
+            class: C::CLASS_ID,
+            ios: layout
+                .ios
+                .iter()
+                .zip(telemetry.get_ios())
+                .map(|(&(name, kind), state)| IoState { name, kind, state })
+                .collect(),
+            pulses: layout
+                .pulses
+                .iter()
+                .zip(telemetry.get_pulses())
+                .map(|(&output, active)| PulseState { output, active })
+                .collect(),
+            temperatures: layout
+                .temperatures
+                .iter()
+                .zip(telemetry.get_temperatures())
+                .map(|(&name, value)| TemperatureSensor { name, value })
+                .collect(),
+        }
+    }
+
+    pub fn inputs(&self) -> impl Iterator<Item = &IoState> {
+        self.ios.iter().filter(|io| io.kind != IoKind::Output)
+    }
+
+    pub fn outputs(&self) -> impl Iterator<Item = &IoState> {
+        self.ios.iter().filter(|io| io.kind != IoKind::Input)
+    }
+
+    pub fn get_io(&self, name: &str) -> Option<bool> {
+        self.ios
+            .iter()
+            .find(|io| io.name == name)
+            .map(|io| io.state)
+    }
+
+    pub fn get_temperature(&self, name: &str) -> Option<f32> {
+        self.temperatures
+            .iter()
+            .find(|sensor| sensor.name == name)
+            .and_then(|sensor| sensor.value)
+    }
+}
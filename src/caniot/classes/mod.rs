@@ -2,15 +2,22 @@ pub mod class0;
 pub mod class1;
 
 pub mod command;
+pub mod layout;
 pub mod telemetry;
 pub mod traits;
 pub mod utils;
+pub mod wiring;
 
-pub use command::BoardClassCommand;
+pub use command::{BoardClassCommand, BoardCommand, BoardOutput};
+pub use layout::{ClassLayout, IoKind, IoSnapshot};
 pub use telemetry::BoardClassTelemetry;
+pub use wiring::{BoardIo, Wire};
 
 #[cfg(test)]
 mod class0_test;
 
 #[cfg(test)]
 mod class1_test;
+
+#[cfg(test)]
+mod wiring_test;
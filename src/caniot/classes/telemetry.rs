@@ -5,6 +5,7 @@ use crate::caniot::{Payload, ProtocolError, Ty};
 use super::{
     class0::{self, Class0},
     class1::{self, Class1},
+    layout::{ClassLayout, IoSnapshot},
     traits::{Class, ClassTelemetryTrait, TempSensType},
 };
 
@@ -42,6 +43,33 @@ impl BoardClassTelemetry {
             BoardClassTelemetry::Class1(telemetry) => telemetry.get_temperature(sensor),
         }
     }
+
+    pub fn layout(&self) -> &'static ClassLayout {
+        match self {
+            BoardClassTelemetry::Class0(_) => &Class0::LAYOUT,
+            BoardClassTelemetry::Class1(_) => &Class1::LAYOUT,
+        }
+    }
+
+    pub fn io_snapshot(&self) -> IoSnapshot {
+        match self {
+            BoardClassTelemetry::Class0(telemetry) => {
+                IoSnapshot::from_telemetry::<Class0>(telemetry)
+            }
+            BoardClassTelemetry::Class1(telemetry) => {
+                IoSnapshot::from_telemetry::<Class1>(telemetry)
+            }
+        }
+    }
+}
+
+/// Layout of the given board class, None if the class is not supported
+pub fn boardlc_get_layout(class: u8) -> Option<&'static ClassLayout> {
+    match class {
+        0 => Some(&Class0::LAYOUT),
+        1 => Some(&Class1::LAYOUT),
+        _ => None,
+    }
 }
 
 impl TryFrom<&Payload<Ty>> for BoardClassTelemetry {
@@ -1,16 +1,13 @@
 use crate::caniot::{self, AsPayload, Cd, ClCd, Payload, Ty};
 
+use super::layout::ClassLayout;
+
 pub trait Class {
     const CLASS_ID: u8;
+    const LAYOUT: ClassLayout;
 
     type Telemetry: ClassTelemetryTrait;
     type Command: ClassCommandTrait;
-
-    // layout functions
-    // inputs count
-    // outputs count
-    // temperatures count
-    //
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -31,6 +28,15 @@ pub trait ClassTelemetryTrait: AsPayload<Ty> {
     }
 
     fn get_temperature(&self, sensor: TempSensType) -> Option<f32>;
+
+    // Digital IOs states, in the order of the class layout
+    fn get_ios(&self) -> Vec<bool>;
+
+    // Pulse states, in the order of the class layout
+    fn get_pulses(&self) -> Vec<bool>;
+
+    // Temperatures in celsius, in the order of the class layout
+    fn get_temperatures(&self) -> Vec<Option<f32>>;
 }
 
 impl From<Payload<ClCd>> for Payload<Cd> {
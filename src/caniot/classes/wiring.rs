@@ -0,0 +1,263 @@
+use std::ops::Not;
+
+use serde::Serialize;
+
+use crate::caniot::Xps;
+
+use super::{class0, class1, command::BoardOutput, telemetry::BoardClassTelemetry};
+
+/// IO of a class 0 or class 1 board which state is reported in the telemetry
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum BoardIo {
+    Class0Input(class0::Input),
+    Class0Output(class0::Output),
+    Class1(class1::Pin),
+}
+
+impl BoardIo {
+    pub fn class_id(&self) -> u8 {
+        match self {
+            BoardIo::Class0Input(_) | BoardIo::Class0Output(_) => 0,
+            BoardIo::Class1(_) => 1,
+        }
+    }
+
+    /// State of the IO, None if the telemetry is of another class
+    pub fn read(&self, telemetry: &BoardClassTelemetry) -> Option<bool> {
+        match (self, telemetry) {
+            (BoardIo::Class0Input(input), BoardClassTelemetry::Class0(t)) => {
+                Some(t.get_input(*input))
+            }
+            (BoardIo::Class0Output(output), BoardClassTelemetry::Class0(t)) => {
+                Some(t.get_output(*output))
+            }
+            (BoardIo::Class1(pin), BoardClassTelemetry::Class1(t)) => Some(t.get(*pin)),
+            _ => None,
+        }
+    }
+}
+
+impl From<BoardOutput> for BoardIo {
+    fn from(output: BoardOutput) -> Self {
+        match output {
+            BoardOutput::Class0(output) => BoardIo::Class0Output(output),
+            BoardOutput::Class1(pin) => BoardIo::Class1(pin),
+        }
+    }
+}
+
+/// Wiring of a semantic field to a board IO or output.
+///
+/// An inverted wire reads `true` when the IO is low and drives the output low
+/// when the field is set, e.g. `!BoardIo::Class0Input(class0::Input::In4)`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct Wire<IO> {
+    pub io: IO,
+    pub inverted: bool,
+}
+
+impl<IO> Wire<IO> {
+    pub const fn new(io: IO) -> Self {
+        Self {
+            io,
+            inverted: false,
+        }
+    }
+
+    pub const fn inverted(io: IO) -> Self {
+        Self { io, inverted: true }
+    }
+}
+
+impl<IO> Not for Wire<IO> {
+    type Output = Self;
+
+    fn not(self) -> Self {
+        Self {
+            io: self.io,
+            inverted: !self.inverted,
+        }
+    }
+}
+
+impl Not for BoardIo {
+    type Output = Wire<BoardIo>;
+
+    fn not(self) -> Wire<BoardIo> {
+        Wire::inverted(self)
+    }
+}
+
+impl Not for BoardOutput {
+    type Output = Wire<BoardOutput>;
+
+    fn not(self) -> Wire<BoardOutput> {
+        Wire::inverted(self)
+    }
+}
+
+impl From<BoardIo> for Wire<BoardIo> {
+    fn from(io: BoardIo) -> Self {
+        Wire::new(io)
+    }
+}
+
+impl From<BoardOutput> for Wire<BoardOutput> {
+    fn from(output: BoardOutput) -> Self {
+        Wire::new(output)
+    }
+}
+
+impl Wire<BoardIo> {
+    pub fn read(&self, telemetry: &BoardClassTelemetry) -> Option<bool> {
+        self.io.read(telemetry).map(|state| state != self.inverted)
+    }
+}
+
+impl Wire<BoardOutput> {
+    /// Semantic state of the output, as reported in the telemetry
+    pub fn read(&self, telemetry: &BoardClassTelemetry) -> Option<bool> {
+        Wire::new(BoardIo::from(self.io))
+            .read(telemetry)
+            .map(|state| state != self.inverted)
+    }
+
+    /// Command to apply to the output for the semantic command
+    pub fn xps(&self, xps: Xps) -> Xps {
+        if !self.inverted {
+            return xps;
+        }
+
+        match xps {
+            Xps::SetOn => Xps::SetOff,
+            Xps::SetOff => Xps::SetOn,
+            Xps::PulseOn => Xps::PulseOff,
+            Xps::PulseOff => Xps::PulseOn,
+            xps => xps,
+        }
+    }
+}
+
+/// Declare a struct of semantic inputs read from the board telemetry, e.g.
+///
+/// ```ignore
+/// board_inputs! {
+///     #[derive(Debug, Clone, Default)]
+///     pub struct GateIOState {
+///         gate_open: BoardIo::Class0Input(class0::Input::In2),
+///         // contact closed when the box is intact
+///         sabotage: !BoardIo::Class0Input(class0::Input::In4),
+///     }
+/// }
+/// ```
+///
+/// Fields are `bool`, `wiring()` returns the declared wires and
+/// `from_telemetry()` reads them (None if the telemetry is of another class).
+#[macro_export]
+macro_rules! board_inputs {
+    (
+        $(#[$meta:meta])*
+        $vis:vis struct $name:ident {
+            $($(#[$field_meta:meta])* $field:ident: $wire:expr),* $(,)?
+        }
+    ) => {
+        $(#[$meta])*
+        $vis struct $name {
+            $($(#[$field_meta])* pub $field: bool,)*
+        }
+
+        #[allow(dead_code)]
+        impl $name {
+            pub fn wiring() -> Vec<(&'static str, $crate::caniot::Wire<$crate::caniot::BoardIo>)> {
+                vec![$((stringify!($field), $crate::caniot::Wire::from($wire)),)*]
+            }
+
+            pub fn from_telemetry(telemetry: &$crate::caniot::BoardClassTelemetry) -> Option<Self> {
+                Some(Self {
+                    $($field: $crate::caniot::Wire::<$crate::caniot::BoardIo>::from($wire)
+                        .read(telemetry)?,)*
+                })
+            }
+        }
+    };
+}
+
+/// Declare a struct of semantic outputs, each field holds the command to apply, e.g.
+///
+/// ```ignore
+/// board_outputs! {
+///     pub struct GateOutputs {
+///         gate_motor: BoardOutput::Class0(class0::Output::Rl1),
+///         // active low
+///         light: !BoardOutput::Class0(class0::Output::Oc1),
+///     }
+/// }
+/// ```
+///
+/// Fields are `Xps` (default `Xps::None`), `into_request()` builds the board
+/// command applying the inversion of each wire. It fails if the outputs are
+/// wired to an unsupported class or to different classes.
+#[macro_export]
+macro_rules! board_outputs {
+    (
+        $(#[$meta:meta])*
+        $vis:vis struct $name:ident {
+            $($(#[$field_meta:meta])* $field:ident: $wire:expr),* $(,)?
+        }
+    ) => {
+        $(#[$meta])*
+        #[derive(Debug, Default, Clone, Copy, PartialEq)]
+        $vis struct $name {
+            $($(#[$field_meta])* pub $field: $crate::caniot::Xps,)*
+        }
+
+        #[allow(dead_code)]
+        impl $name {
+            pub fn wiring() -> Vec<(&'static str, $crate::caniot::Wire<$crate::caniot::BoardOutput>)> {
+                vec![$((stringify!($field), $crate::caniot::Wire::from($wire)),)*]
+            }
+
+            /// Semantic states of the outputs, as reported in the telemetry
+            pub fn read_states(
+                telemetry: &$crate::caniot::BoardClassTelemetry,
+            ) -> Option<Vec<(&'static str, bool)>> {
+                Self::wiring()
+                    .into_iter()
+                    .map(|(name, wire)| wire.read(telemetry).map(|state| (name, state)))
+                    .collect()
+            }
+
+            pub fn has_effect(&self) -> bool {
+                [$(self.$field),*]
+                    .iter()
+                    .any(|xps| *xps != $crate::caniot::Xps::None)
+            }
+
+            pub fn into_board_command(
+                &self,
+            ) -> Result<$crate::caniot::BoardCommand, $crate::caniot::ProtocolError> {
+                let wiring = Self::wiring();
+                let class_id = wiring
+                    .first()
+                    .map(|(_, wire)| wire.io.class_id())
+                    .unwrap_or_default();
+                let mut command = $crate::caniot::BoardCommand::new(class_id)
+                    .ok_or($crate::caniot::ProtocolError::UnsupportedClass)?;
+                $(
+                    let wire = $crate::caniot::Wire::<$crate::caniot::BoardOutput>::from($wire);
+                    // Outputs wired to different board classes
+                    if !command.set(wire.io, wire.xps(self.$field)) {
+                        return Err($crate::caniot::ProtocolError::CommandEncodeError);
+                    }
+                )*
+                Ok(command)
+            }
+
+            pub fn into_request(
+                &self,
+            ) -> Result<$crate::caniot::RequestData, $crate::caniot::ProtocolError> {
+                Ok(self.into_board_command()?.into_request())
+            }
+        }
+    };
+}
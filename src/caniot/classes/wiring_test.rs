@@ -0,0 +1,90 @@
+use crate::{
+    board_inputs, board_outputs,
+    caniot::{
+        class0, class1, BoardClassTelemetry, BoardCommand, BoardIo, BoardOutput, ProtocolError, Xps,
+    },
+};
+
+board_inputs! {
+    struct TestInputs {
+        door_open: BoardIo::Class0Input(class0::Input::In1),
+        intact: !BoardIo::Class0Input(class0::Input::In2),
+        light_on: BoardIo::Class0Output(class0::Output::Oc1),
+    }
+}
+
+board_outputs! {
+    struct TestOutputs {
+        motor: BoardOutput::Class0(class0::Output::Rl1),
+        light: !BoardOutput::Class0(class0::Output::Oc1),
+    }
+}
+
+board_outputs! {
+    struct MixedOutputs {
+        relay: BoardOutput::Class0(class0::Output::Rl1),
+        pin: BoardOutput::Class1(class1::Pin::Pc0),
+    }
+}
+
+#[test]
+fn test_wired_inputs() {
+    let telemetry = BoardClassTelemetry::Class0(class0::Telemetry {
+        in1: true,
+        in2: true,
+        oc1: true,
+        ..Default::default()
+    });
+
+    let inputs = TestInputs::from_telemetry(&telemetry).unwrap();
+    assert!(inputs.door_open);
+    assert!(!inputs.intact);
+    assert!(inputs.light_on);
+
+    // wiring of another class
+    let telemetry = BoardClassTelemetry::Class1(class1::Telemetry::default());
+    assert!(TestInputs::from_telemetry(&telemetry).is_none());
+}
+
+#[test]
+fn test_wired_outputs() {
+    let outputs = TestOutputs::default();
+    assert!(!outputs.has_effect());
+
+    let outputs = TestOutputs {
+        motor: Xps::PulseOn,
+        light: Xps::SetOn,
+    };
+    assert!(outputs.has_effect());
+
+    // inverted output
+    let expected = class0::Command::default()
+        .with(class0::Output::Rl1, Xps::PulseOn)
+        .with(class0::Output::Oc1, Xps::SetOff);
+    assert_eq!(
+        outputs.into_board_command().unwrap(),
+        BoardCommand::Class0(expected)
+    );
+
+    let telemetry = BoardClassTelemetry::Class0(class0::Telemetry {
+        rl1: true,
+        ..Default::default()
+    });
+    assert_eq!(
+        TestOutputs::read_states(&telemetry).unwrap(),
+        vec![("motor", true), ("light", true)]
+    );
+}
+
+#[test]
+fn test_wired_outputs_class_mismatch() {
+    let outputs = MixedOutputs {
+        relay: Xps::SetOn,
+        pin: Xps::SetOn,
+    };
+    assert!(matches!(
+        outputs.into_board_command(),
+        Err(ProtocolError::CommandEncodeError)
+    ));
+    assert!(outputs.into_request().is_err());
+}
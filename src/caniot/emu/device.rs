@@ -61,7 +61,7 @@ impl Device {
     fn read_attribute(&self, attr: impl TryInto<Attribute>) -> Option<u32> {
         match attr.try_into() {
             Ok(Attribute::NodeId) => Some(self.did.to_u8() as u32),
-            Ok(Attribute::SystemUptime) => Some(self.start_time.elapsed().as_millis() as u32),
+            Ok(Attribute::SystemUptime) => Some(self.start_time.elapsed().as_secs() as u32),
             Ok(Attribute::ConfigTelemetryPeriod) => {
                 self.telemetry_interval.map(|ms| ms.as_millis() as u32)
             }
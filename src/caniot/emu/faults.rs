@@ -0,0 +1,199 @@
+use std::time::{Duration, Instant};
+
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::caniot::{self, ErrorCode, ErrorSource, ResponseData};
+
+/// Distribution of the delay before a frame of the device is received (ms)
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+#[serde(tag = "distribution", rename_all = "lowercase")]
+pub enum LatencyConfig {
+    Fixed { delay: u32 },
+    Uniform { min: u32, max: u32 },
+    Exponential { mean: u32 },
+}
+
+impl LatencyConfig {
+    pub fn sample<R: Rng>(&self, rng: &mut R) -> Duration {
+        let ms = match *self {
+            LatencyConfig::Fixed { delay } => delay as f64,
+            LatencyConfig::Uniform { min, max } => rng.gen_range(min..=max.max(min)) as f64,
+            LatencyConfig::Exponential { mean } => -(mean as f64) * (1.0 - rng.gen::<f64>()).ln(),
+        };
+        Duration::from_secs_f64(ms / 1000.0)
+    }
+}
+
+/// The device does not send anything during the last `duration` ms of every `period` ms
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct SilenceConfig {
+    pub period: u32,   // ms
+    pub duration: u32, // ms
+}
+
+impl SilenceConfig {
+    pub fn is_silent(&self, elapsed: Duration) -> bool {
+        if self.period == 0 {
+            return false;
+        }
+        let in_period = (elapsed.as_millis() % self.period as u128) as u32;
+        in_period >= self.period.saturating_sub(self.duration)
+    }
+}
+
+/// Faults injected on the frames sent by an emulated device, probabilities
+/// are between 0.0 (never) and 1.0 (always)
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct FaultsConfig {
+    pub did: u8,
+
+    // Delay before the frames of the device are received
+    pub latency: Option<LatencyConfig>,
+
+    // Frames lost
+    #[serde(default)]
+    pub drop: f64,
+
+    // Frames received twice
+    #[serde(default)]
+    pub duplicate: f64,
+
+    // Frames held for reorder_delay, the frames sent meanwhile are received first
+    #[serde(default)]
+    pub reorder: f64,
+    #[serde(default = "default_reorder_delay")]
+    pub reorder_delay: u32, // ms
+
+    // Replies to requests replaced by an error response with error_code
+    #[serde(default)]
+    pub error: f64,
+    #[serde(default = "default_error_code")]
+    pub error_code: ErrorCode,
+
+    // Periods during which the device goes silent
+    pub silence: Option<SilenceConfig>,
+}
+
+fn default_reorder_delay() -> u32 {
+    100
+}
+
+fn default_error_code() -> ErrorCode {
+    ErrorCode::Eagain
+}
+
+impl Default for FaultsConfig {
+    fn default() -> Self {
+        Self {
+            did: 0,
+            latency: None,
+            drop: 0.0,
+            duplicate: 0.0,
+            reorder: 0.0,
+            reorder_delay: default_reorder_delay(),
+            error: 0.0,
+            error_code: default_error_code(),
+            silence: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FaultsStats {
+    pub dropped: usize,
+    pub silenced: usize, // Frames dropped while the device is silent
+    pub duplicated: usize,
+    pub reordered: usize,
+    pub errors: usize,
+}
+
+fn happens<R: Rng>(rng: &mut R, probability: f64) -> bool {
+    probability > 0.0 && rng.gen::<f64>() < probability
+}
+
+// Source of the error replacing the response, None if it is already an error
+fn error_source(data: &ResponseData) -> Option<ErrorSource> {
+    match data {
+        ResponseData::Telemetry { endpoint, .. } => Some(ErrorSource::Telemetry(*endpoint, None)),
+        ResponseData::Attribute { key, .. } => Some(ErrorSource::Attribute(Some(*key))),
+        ResponseData::Error { .. } => None,
+    }
+}
+
+/// Faults injected on the frames of an emulated device
+pub struct DeviceFaults {
+    config: FaultsConfig,
+    started_at: Instant,
+    pub stats: FaultsStats,
+}
+
+impl DeviceFaults {
+    pub fn new(config: FaultsConfig, now: &Instant) -> Self {
+        Self {
+            config,
+            started_at: *now,
+            stats: FaultsStats::default(),
+        }
+    }
+
+    pub fn did(&self) -> u8 {
+        self.config.did
+    }
+
+    pub fn is_silent(&self, now: &Instant) -> bool {
+        self.config
+            .silence
+            .is_some_and(|silence| silence.is_silent(now.duration_since(self.started_at)))
+    }
+
+    /// Apply the faults to a frame sent by the device, `reply` tells whether it
+    /// answers a request.
+    ///
+    /// Returns the frames to be received, each with the delay before its reception.
+    pub fn inject<R: Rng>(
+        &mut self,
+        mut response: caniot::Response,
+        reply: bool,
+        now: &Instant,
+        rng: &mut R,
+    ) -> Vec<(Duration, caniot::Response)> {
+        if self.is_silent(now) {
+            self.stats.silenced += 1;
+            return vec![];
+        }
+
+        if happens(rng, self.config.drop) {
+            self.stats.dropped += 1;
+            return vec![];
+        }
+
+        if reply && happens(rng, self.config.error) {
+            if let Some(source) = error_source(&response.data) {
+                self.stats.errors += 1;
+                response.data = ResponseData::Error {
+                    source,
+                    error: Some(self.config.error_code),
+                };
+            }
+        }
+
+        let mut delay = self
+            .config
+            .latency
+            .map(|latency| latency.sample(rng))
+            .unwrap_or_default();
+
+        if happens(rng, self.config.reorder) {
+            self.stats.reordered += 1;
+            delay += Duration::from_millis(self.config.reorder_delay as u64);
+        }
+
+        if happens(rng, self.config.duplicate) {
+            self.stats.duplicated += 1;
+            vec![(delay, response.clone()), (delay, response)]
+        } else {
+            vec![(delay, response)]
+        }
+    }
+}
@@ -0,0 +1,141 @@
+use std::time::{Duration, Instant};
+
+use chrono::Utc;
+use rand::{rngs::StdRng, SeedableRng};
+
+use crate::caniot::{self, DeviceId, ErrorCode, ErrorSource, ResponseData};
+
+use super::faults::*;
+
+fn attribute_response() -> caniot::Response {
+    caniot::Response {
+        device_id: DeviceId::from_u8(1),
+        data: ResponseData::Attribute {
+            key: 0x1010,
+            value: 42,
+        },
+        timestamp: Utc::now(),
+    }
+}
+
+#[test]
+fn test_faults_config() {
+    let config: FaultsConfig = toml::from_str(
+        r#"
+        did = 1
+        latency = { distribution = "uniform", min = 10, max = 200 }
+        drop = 0.1
+        error = 0.5
+        error_code = "Etimeout"
+        silence = { period = 60000, duration = 10000 }
+        "#,
+    )
+    .unwrap();
+
+    assert_eq!(config.did, 1);
+    assert_eq!(
+        config.latency,
+        Some(LatencyConfig::Uniform { min: 10, max: 200 })
+    );
+    assert_eq!(config.drop, 0.1);
+    assert_eq!(config.duplicate, 0.0);
+    assert_eq!(config.reorder_delay, 100);
+    assert_eq!(config.error_code, ErrorCode::Etimeout);
+
+    let mut rng = StdRng::seed_from_u64(0);
+    for _ in 0..100 {
+        let delay = config.latency.unwrap().sample(&mut rng);
+        assert!(delay >= Duration::from_millis(10) && delay <= Duration::from_millis(200));
+    }
+}
+
+#[test]
+fn test_faults_silence() {
+    let silence = SilenceConfig {
+        period: 1000,
+        duration: 200,
+    };
+
+    assert!(!silence.is_silent(Duration::from_millis(0)));
+    assert!(!silence.is_silent(Duration::from_millis(799)));
+    assert!(silence.is_silent(Duration::from_millis(800)));
+    assert!(silence.is_silent(Duration::from_millis(999)));
+    assert!(!silence.is_silent(Duration::from_millis(1000)));
+
+    let now = Instant::now();
+    let mut rng = StdRng::seed_from_u64(0);
+    let mut faults = DeviceFaults::new(
+        FaultsConfig {
+            did: 1,
+            silence: Some(silence),
+            ..Default::default()
+        },
+        &now,
+    );
+
+    assert_eq!(
+        faults
+            .inject(attribute_response(), true, &now, &mut rng)
+            .len(),
+        1
+    );
+
+    let later = now + Duration::from_millis(900);
+    assert!(faults.is_silent(&later));
+    assert!(faults
+        .inject(attribute_response(), true, &later, &mut rng)
+        .is_empty());
+    assert_eq!(faults.stats.silenced, 1);
+}
+
+#[test]
+fn test_faults_inject() {
+    let now = Instant::now();
+    let mut rng = StdRng::seed_from_u64(0);
+
+    let mut faults = DeviceFaults::new(
+        FaultsConfig {
+            did: 1,
+            drop: 1.0,
+            ..Default::default()
+        },
+        &now,
+    );
+    assert!(faults
+        .inject(attribute_response(), true, &now, &mut rng)
+        .is_empty());
+    assert_eq!(faults.stats.dropped, 1);
+
+    let mut faults = DeviceFaults::new(
+        FaultsConfig {
+            did: 1,
+            latency: Some(LatencyConfig::Fixed { delay: 50 }),
+            duplicate: 1.0,
+            reorder: 1.0,
+            error: 1.0,
+            error_code: ErrorCode::Etimeout,
+            ..Default::default()
+        },
+        &now,
+    );
+
+    // Replies are replaced by errors, delayed by the latency and the reordering
+    let frames = faults.inject(attribute_response(), true, &now, &mut rng);
+    assert_eq!(frames.len(), 2);
+    for (delay, response) in frames {
+        assert_eq!(delay, Duration::from_millis(150));
+        assert_eq!(
+            response.data,
+            ResponseData::Error {
+                source: ErrorSource::Attribute(Some(0x1010)),
+                error: Some(ErrorCode::Etimeout),
+            }
+        );
+    }
+
+    // Frames sent spontaneously are not turned into errors
+    let frames = faults.inject(attribute_response(), false, &now, &mut rng);
+    assert_eq!(frames[0].1.data, attribute_response().data);
+    assert_eq!(faults.stats.errors, 1);
+    assert_eq!(faults.stats.duplicated, 2);
+}
@@ -1,10 +1,13 @@
 pub mod behavior;
 pub mod device;
 pub mod events;
+pub mod faults;
 pub mod helpers;
 pub mod nodes;
 pub mod pools;
 
+#[cfg(test)]
+mod faults_test;
 #[cfg(test)]
 mod helpers_test;
 
@@ -12,5 +15,6 @@ use behavior::*;
 pub use device::Device;
 #[allow(unused_imports)]
 pub use events::*;
+pub use faults::*;
 use nodes::*;
 pub use pools::*;
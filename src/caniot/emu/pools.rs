@@ -1,7 +1,7 @@
 use std::time::Duration;
 
 use crate::{
-    bus::CanInterface,
+    bus::emu::CanInterface,
     caniot::{self, DeviceId},
 };
 
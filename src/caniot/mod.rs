@@ -1,3 +1,4 @@
+pub mod attribute_value;
 pub mod attributes;
 pub mod classes;
 pub mod datatypes;
@@ -7,6 +8,7 @@ pub mod protocol;
 pub mod sys_control;
 pub mod types;
 
+pub use attribute_value::*;
 pub use attributes::*;
 
 pub use classes::*;
@@ -26,5 +28,4 @@ mod payloads_test;
 #[cfg(test)]
 mod sys_control_test;
 
-#[cfg(feature = "emu")]
 pub mod emu;
@@ -1,9 +1,11 @@
 use num_derive::FromPrimitive;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
 pub const ERROR_BASE: isize = 0x3A00;
 
-#[derive(Debug, PartialEq, Eq, Clone, Copy, FromPrimitive, Serialize)]
+#[derive(
+    Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy, FromPrimitive, Serialize, Deserialize,
+)]
 pub enum ErrorCode {
     Ok = 0x00000000,
     Einval = ERROR_BASE, // Invalid argument
@@ -41,6 +41,11 @@ where
         }
     }
 
+    /// Set the time the frame was received at
+    pub fn with_timestamp(self, timestamp: DateTime<Utc>) -> Self {
+        Self { timestamp, ..self }
+    }
+
     pub fn into_data(self) -> T {
         self.data
     }
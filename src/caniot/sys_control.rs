@@ -46,6 +46,17 @@ impl SysCtrl {
             inhibit,
         }
     }
+
+    pub const fn watchdog_control(watchdog_enable: TS) -> SysCtrl {
+        SysCtrl {
+            hardware_reset: false,
+            _software_reset: false,
+            _watchdog_reset: false,
+            watchdog_enable,
+            factory_reset: false,
+            inhibit: TSP::None,
+        }
+    }
 }
 
 impl Into<u8> for SysCtrl {
@@ -20,4 +20,10 @@ pub enum ProtocolError {
     ClassCommandSizeError,
     #[error("Unsupported caniot class")]
     UnsupportedClass,
+    #[error("Attribute value decode error")]
+    AttributeValueDecodeError,
+    #[error("Attribute value encode error")]
+    AttributeValueEncodeError,
+    #[error("Attribute value type mismatch")]
+    AttributeValueTypeMismatch,
 }
@@ -3,7 +3,7 @@ use serde::{Deserialize, Serialize};
 use std::fs;
 use toml;
 
-use crate::bus::CanConfig;
+use crate::bus::CanBusesConfig;
 use crate::controller::CaniotConfig;
 use crate::coprocessor::CoproConfig;
 use crate::database::DatabaseConfig;
@@ -13,7 +13,8 @@ use crate::grpcserver::GrpcConfig;
 
 #[derive(Serialize, Deserialize, Clone, Debug, Default)]
 pub struct AppConfig {
-    pub can: CanConfig,
+    // Single [can] table or list of [[can]] tables
+    pub can: CanBusesConfig,
     pub caniot: CaniotConfig,
     pub copro: CoproConfig,
     pub web: WebserverConfig,
@@ -1,5 +1,15 @@
+#[cfg(feature = "can-tunnel")]
+use socketcan::CanDataFrame;
+#[cfg(feature = "can-tunnel")]
+use tokio::sync::mpsc;
 use tokio::sync::oneshot;
 
+#[cfg(feature = "can-tunnel")]
+use crate::bus::CanRxFrame;
+#[cfg(feature = "can-tunnel")]
+use crate::controller::core::can_tunnel::{CanTunnelConfig, CanTunnelId, CanTunnelInfos};
+
+use crate::bus::CanBusId;
 use crate::caniot::{self as ct, DeviceId};
 use crate::controller::DeviceInfos;
 use crate::controller::{ActionTrait, DeviceAction};
@@ -7,6 +17,7 @@ use crate::grpcserver::EmuRequest;
 
 use super::caniot_devices_controller::CaniotControllerError;
 use super::device_filter::DeviceFilter;
+use super::pending_discovery::DiscoveryReport;
 
 pub enum CaniotApiMessage {
     GetDevices {
@@ -21,6 +32,24 @@ pub enum CaniotApiMessage {
         // If None, the controller will send the query and not wait for a response.
         respond_to: Option<oneshot::Sender<Result<ct::Response, CaniotControllerError>>>,
     },
+    // Broadcast query, all responses received within the window are returned
+    BroadcastQuery {
+        query: ct::Request,
+        window_ms: Option<u32>,
+        respond_to: oneshot::Sender<Result<Vec<(CanBusId, ct::Response)>, CaniotControllerError>>,
+    },
+    // Probe all device ids, responding devices are added to the devices list
+    Discover {
+        probe_timeout_ms: Option<u32>,
+        interval_ms: Option<u32>,
+        respond_to: oneshot::Sender<Result<DiscoveryReport, CaniotControllerError>>,
+    },
+    ReadStringAttribute {
+        did: DeviceId,
+        attribute: ct::Attribute,
+        timeout_ms: Option<u32>,
+        respond_to: oneshot::Sender<Result<String, CaniotControllerError>>,
+    },
     DevicesResetMeasuresStats,
     DeviceAction {
         did: Option<DeviceId>,
@@ -34,11 +63,20 @@ pub enum CaniotApiMessage {
     },
     #[cfg(feature = "can-tunnel")]
     EstablishCanTunnel {
-        rx_queue: mpsc::Sender<CanDataFrame>, // Messages received from the bus
+        config: CanTunnelConfig,
+        rx_queue: mpsc::Sender<CanRxFrame>, // Messages received from the bus
         tx_queue: mpsc::Receiver<CanDataFrame>, // Messages to sent to the bus
+        respond_to: oneshot::Sender<Result<CanTunnelId, CaniotControllerError>>,
+    },
+    #[cfg(feature = "can-tunnel")]
+    GetCanTunnels {
+        respond_to: oneshot::Sender<Vec<CanTunnelInfos>>,
+    },
+    #[cfg(feature = "can-tunnel")]
+    CloseCanTunnel {
+        id: CanTunnelId,
         respond_to: oneshot::Sender<Result<(), CaniotControllerError>>,
     },
-    #[cfg(feature = "emu")]
     EmulationRequest {
         event: EmuRequest,
     },
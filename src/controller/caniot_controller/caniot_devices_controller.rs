@@ -5,30 +5,40 @@ use std::sync::Arc;
 use std::time::{Duration, Instant};
 
 use chrono::{DateTime, Utc};
-use futures::future::Pending;
 use itertools::{partition, Itertools};
 
-use socketcan::CanDataFrame;
 use tokio::sync::oneshot::Sender;
 
-use crate::bus::{CanInterfaceError, CanInterfaceTrait, CAN_IOCTL_SEND_EMU_EVENT};
-use crate::caniot::{self, are_requests_concurrent, Frame, RequestData};
+use crate::bus::{
+    CanBackend, CanBus, CanBusId, CanDirection, CanInterfaceError, CanInterfaceTrait, CanRxFrame,
+    TxPriority, CAN_IOCTL_SEND_EMU_EVENT,
+};
+use crate::caniot::{self, Frame, RequestData};
 use crate::caniot::{DeviceId, Request};
 use crate::controller::caniot_controller::api_message::CaniotApiMessage;
 use crate::controller::caniot_controller::auto_attach::device_init_controller;
 use crate::controller::caniot_controller::pending_action::PendingAction;
-use crate::controller::caniot_controller::pending_query::{PendingQuery, PendingQueryTenant};
+use crate::controller::caniot_controller::pending_attributes_config::PendingAttributesConfig;
+use crate::controller::caniot_controller::pending_attributes_report::PendingAttributesReport;
+use crate::controller::caniot_controller::pending_broadcast::PendingBroadcast;
+use crate::controller::caniot_controller::pending_discovery::PendingDiscovery;
+use crate::controller::caniot_controller::pending_query::{
+    take_answered_queries, PendingQuery, PendingQueryTenant,
+};
+use crate::controller::caniot_controller::pending_string_attribute::PendingStringAttribute;
+use crate::controller::caniot_controller::pending_time_sync::PendingTimeSync;
+use crate::controller::caniot_controller::scheduled_request::ScheduledRequest;
 use crate::controller::{
     ActionVerdict, CaniotConfig, CaniotDevicesConfig, Device, DeviceAction, DeviceActionResult,
-    DeviceError, DeviceInfos, ProcessContext, Verdict,
+    DeviceControlState, DeviceError, DeviceInfos, ProcessContext, TimeSyncMode,
 };
 use crate::database::{SettingsStore, Storage};
 use crate::utils::expirable::{ttl, ExpirableTrait};
 
-#[cfg(feature = "can-tunnel")]
-use super::can_tunnel::CanTunnelContextServer;
 use super::device_filter::DeviceFilter;
 use super::stats::CaniotControllerStats;
+#[cfg(feature = "can-tunnel")]
+use crate::controller::core::can_tunnel::{CanTunnelContextServer, CanTunnelError};
 
 use log::{info, warn};
 
@@ -36,12 +46,17 @@ use thiserror::Error;
 
 const PENDING_QUERY_DEFAULT_TIMEOUT_MS: u32 = 1000; // 1s
 const ACTION_DEFAULT_TIMEOUT_MS: u32 = PENDING_QUERY_DEFAULT_TIMEOUT_MS; // 1s
+const BROADCAST_DEFAULT_WINDOW_MS: u32 = 2000; // 2s
+const TIME_SYNC_DEFAULT_MAX_DRIFT_S: u32 = 2;
 
 #[derive(Error, Debug)]
 pub enum CaniotControllerError {
     #[error("Timeout Error")]
     Timeout,
 
+    #[error("Request dropped before being sent")]
+    Dropped,
+
     #[error("Unsupported query Error")]
     UnsupportedQuery,
 
@@ -62,6 +77,9 @@ pub enum CaniotControllerError {
     #[error("Unknown device")]
     NoSuchDevice,
 
+    #[error("Device {0} is known on multiple buses")]
+    AmbiguousDevice(DeviceId),
+
     #[error("No such device can handle the action")]
     NoSuchDeviceForAction,
 
@@ -79,14 +97,50 @@ pub enum CaniotControllerError {
 
     #[error("Device error: {0}")]
     DeviceError(#[from] DeviceError),
+
+    #[error("Attribute {0:?} is read-only")]
+    AttributeReadOnly(caniot::Attribute),
+
+    #[error("Attribute {0:?} is not available for class {1}")]
+    AttributeWrongClass(caniot::Attribute, u8),
+
+    #[error("Unexpected response from device")]
+    UnexpectedResponse,
+
+    #[error("Discovery already in progress")]
+    DiscoveryInProgress,
+
+    #[cfg(feature = "can-tunnel")]
+    #[error("CAN tunnel error: {0}")]
+    CanTunnelError(#[from] CanTunnelError),
+
+    #[error("Device returned error {code:?} (source {source:?})")]
+    DeviceReturnedError {
+        code: caniot::ErrorCode,
+        source: caniot::ErrorSource,
+    },
+}
+
+impl CaniotControllerError {
+    /// Error returned by the device if the response is an error frame
+    pub fn from_response(response: &caniot::ResponseData) -> Option<Self> {
+        match response {
+            caniot::ResponseData::Error { source, error } => Some(Self::DeviceReturnedError {
+                // Error frames without error code are reported as unexpected
+                code: error.unwrap_or(caniot::ErrorCode::Eunexpected),
+                source: source.clone(),
+            }),
+            _ => None,
+        }
+    }
 }
 
 enum ActionResultOrPending {
     // Action Result
     Result(DeviceActionResult),
 
-    // Pending Action on Device (DID)
-    Pending(DeviceAction, Frame<RequestData>),
+    // Pending Action on Device (DID), on the bus of the device
+    Pending(DeviceAction, CanBusId, Frame<RequestData>),
 }
 
 pub struct CaniotDevicesController<IF: CanInterfaceTrait> {
@@ -97,31 +151,39 @@ pub struct CaniotDevicesController<IF: CanInterfaceTrait> {
     pub config: CaniotConfig,
     pub stats: CaniotControllerStats,
 
-    // can interface
-    pub iface: IF,
+    // can buses
+    pub buses: Vec<CanBus<IF>>,
 
     // caniot devices
     pending_queries: Vec<PendingQuery>,
-    devices: HashMap<DeviceId, Device>, // caniot devices
+    devices: HashMap<(CanBusId, DeviceId), Device>, // caniot devices by bus
+
+    // Discovery scan waiting for the interval to elapse before sending its next probe
+    discovery_parked: Option<PendingDiscovery>,
+
+    // Requests of the devices verdicts, in order
+    scheduled_requests: Vec<ScheduledRequest>,
 
     #[cfg(feature = "can-tunnel")]
-    tunnel_server: CanTunnelContextServer,
+    pub tunnel_server: CanTunnelContextServer,
 }
 
 impl<IF: CanInterfaceTrait> CaniotDevicesController<IF> {
     pub(crate) fn new(
-        iface: IF,
+        buses: Vec<CanBus<IF>>,
         config: CaniotConfig,
         storage: Arc<Storage>,
     ) -> Result<Self, CaniotControllerError> {
         Ok(Self {
-            iface,
+            buses,
             storage,
             config,
             stats: CaniotControllerStats::default(),
 
             pending_queries: Vec::new(),
             devices: HashMap::new(),
+            discovery_parked: None,
+            scheduled_requests: Vec::new(),
             #[cfg(feature = "can-tunnel")]
             tunnel_server: CanTunnelContextServer::default(),
         })
@@ -131,34 +193,37 @@ impl<IF: CanInterfaceTrait> CaniotDevicesController<IF> {
         self.request_telemetry_broadcast().await
     }
 
-    pub async fn iface_send_caniot_frame(
-        iface: &mut impl CanInterfaceTrait,
+    // Queue the request, it is sent on the bus when the controller loop flushes it
+    pub fn iface_send_caniot_frame(
+        bus: &mut CanBus<IF>,
         stats: &mut CaniotControllerStats,
         request: &caniot::Request,
+        priority: TxPriority,
     ) -> Result<(), CaniotControllerError> {
-        info!("TX {}", request);
-
-        let can_frame = request.into();
+        info!("TX {} {}", bus.name, request);
 
-        iface.send(can_frame).await?;
+        bus.send(request.into(), priority)?;
         stats.iface_tx += 1;
+
         Ok(())
     }
 
     async fn device_get_or_create<'d, 'stg>(
-        devices: &'d mut HashMap<DeviceId, Device>,
+        devices: &'d mut HashMap<(CanBusId, DeviceId), Device>,
+        bus: CanBusId,
         did: DeviceId,
         devices_config: &CaniotDevicesConfig,
         stg: SettingsStore<'stg>,
     ) -> &'d mut Device {
-        match devices.entry(did) {
+        match devices.entry((bus, did)) {
             Entry::Occupied(entry) => entry.into_mut(),
             Entry::Vacant(entry) => {
                 // Create controller for device
                 let device_controller = device_init_controller(did, (), devices_config, stg).await;
 
                 // Create device and attach controller if any
-                let new_device = Device::new(did, device_controller);
+                let mut new_device = Device::new(did, bus, device_controller);
+                new_device.control = DeviceControlState::new(devices_config.inhibit_pulse_duration);
 
                 // Insert device in the devices map
                 entry.insert(new_device)
@@ -166,67 +231,280 @@ impl<IF: CanInterfaceTrait> CaniotDevicesController<IF> {
         }
     }
 
+    /// Send the request on the bus, on all buses if None
     pub async fn send_caniot_frame(
         &mut self,
+        bus: Option<CanBusId>,
         request: &caniot::Request,
+        priority: TxPriority,
+    ) -> Result<(), CaniotControllerError> {
+        self.send_caniot_frame_inner(bus, request, priority, true)
+            .await
+    }
+
+    // If create_device is false, the device is not instantiated if unknown (e.g. discovery
+    // probes to device ids which may not exist)
+    async fn send_caniot_frame_inner(
+        &mut self,
+        bus: Option<CanBusId>,
+        request: &caniot::Request,
+        priority: TxPriority,
+        create_device: bool,
     ) -> Result<(), CaniotControllerError> {
         if request.is_broadcast() {
             self.stats.broadcast_tx += 1;
-        } else {
-            // Get or instantiate device
-            let device = Self::device_get_or_create(
-                &mut self.devices,
-                request.device_id,
-                &self.config.devices,
-                self.storage.get_settings_store(),
-            )
-            .await;
+        } else if let Some(bus) = bus {
+            let device = if create_device {
+                // Get or instantiate device
+                Some(
+                    Self::device_get_or_create(
+                        &mut self.devices,
+                        bus,
+                        request.device_id,
+                        &self.config.devices,
+                        self.storage.get_settings_store(),
+                    )
+                    .await,
+                )
+            } else {
+                self.devices.get_mut(&(bus, request.device_id))
+            };
 
             // update device stats
-            match request.data {
-                RequestData::Telemetry { .. } => device.stats.telemetry_tx += 1,
-                RequestData::Command { .. } => device.stats.command_tx += 1,
-                RequestData::AttributeRead { .. } => device.stats.attribute_rx += 1,
-                RequestData::AttributeWrite { .. } => device.stats.attribute_tx += 1,
+            if let Some(device) = device {
+                match request.data {
+                    RequestData::Telemetry { .. } => device.stats.telemetry_tx += 1,
+                    RequestData::Command { .. } => device.stats.command_tx += 1,
+                    RequestData::AttributeRead { .. } => device.stats.attribute_rx += 1,
+                    RequestData::AttributeWrite { .. } => device.stats.attribute_tx += 1,
+                }
+            }
+        }
+
+        match bus {
+            Some(bus) => Self::iface_send_caniot_frame(
+                &mut self.buses[bus],
+                &mut self.stats,
+                request,
+                priority,
+            ),
+            None => {
+                // Keep sending on the other buses if one fails, report the last error
+                let mut result = Ok(());
+                for bus in self.buses.iter_mut() {
+                    if let Err(err) =
+                        Self::iface_send_caniot_frame(bus, &mut self.stats, request, priority)
+                    {
+                        error!("Failed to send CANIOT frame on bus {}: {}", bus.name, err);
+                        result = Err(err);
+                    }
+                }
+                result
             }
         }
+    }
 
-        Self::iface_send_caniot_frame(&mut self.iface, &mut self.stats, request).await
+    // Bus to send requests to the device on: the bus it was seen on, the first bus if unknown
+    fn route(&self, did: &DeviceId) -> Result<CanBusId, CaniotControllerError> {
+        let mut buses = self
+            .devices
+            .keys()
+            .filter(|(_, d)| d == did)
+            .map(|(bus, _)| *bus);
+        match (buses.next(), buses.next()) {
+            (None, _) => Ok(0),
+            (Some(bus), None) => Ok(bus),
+            (Some(_), Some(_)) => Err(CaniotControllerError::AmbiguousDevice(*did)),
+        }
     }
 
+    // Send a request and wait for its response, if the request cannot be sent the
+    // tenant is ended with an error. A tenant returned is to be continued by the caller.
     async fn send_pend_request(
         &mut self,
+        bus: Option<CanBusId>,
         request: caniot::Request,
         timeout_ms: Option<u32>,
         tenant: PendingQueryTenant,
-    ) {
+    ) -> Option<PendingQueryTenant> {
         let timeout_ms = timeout_ms.unwrap_or(
             self.config
                 .pending_queries_default_timeout
                 .unwrap_or(PENDING_QUERY_DEFAULT_TIMEOUT_MS),
         );
 
-        if request.device_id == DeviceId::BROADCAST {
-            error!("BROADCAST query not supported");
-            let _ = tenant.end_with_error(CaniotControllerError::UnsupportedQuery);
+        let is_broadcast_tenant = matches!(tenant, PendingQueryTenant::Broadcast(_));
+        let is_discovery_tenant = matches!(tenant, PendingQueryTenant::Discovery(_));
+        if request.is_broadcast() != is_broadcast_tenant {
+            // Only broadcast tenants can collect multiple responses
+            error!("BROADCAST query not supported for this tenant");
+            tenant.end_with_error(CaniotControllerError::UnsupportedQuery)
         } else if self
             .pending_queries
             .iter()
-            .any(|pq| are_requests_concurrent(&pq.query, &request))
+            .any(|pq| pq.is_concurrent(bus, &request))
         {
             // Attempt to send the same query multiple times:
             // queries for which response cannot be differentiated must not
             // be sent as pending queries multiple times.
             error!("Duplicate pending query: response undifferentiable, request not sent");
             self.stats.pq_duplicate_dropped += 1;
-            tenant.end_with_error(CaniotControllerError::UndifferentiablePendingQuery);
-        } else if let Err(err) = self.send_caniot_frame(&request).await {
+            tenant.end_with_error(CaniotControllerError::UndifferentiablePendingQuery)
+        } else if let Err(err) = self
+            .send_caniot_frame_inner(bus, &request, tenant.tx_priority(), !is_discovery_tenant)
+            .await
+        {
             error!("Failed to send CANIOT frame: {:?}", err);
-            let _ = tenant.end_with_error(err);
+            tenant.end_with_error(err)
         } else {
             self.pending_queries
-                .push(PendingQuery::new(request, timeout_ms, tenant));
+                .push(PendingQuery::new(bus, request, timeout_ms, tenant));
             self.stats.pq_pushed += 1;
+            None
+        }
+    }
+
+    async fn send_pend_string_attribute_request(&mut self, pending_read: PendingStringAttribute) {
+        let request = pending_read.next_request();
+        let bus = Some(pending_read.bus);
+        let timeout_ms = pending_read.timeout_ms;
+        let tenant = PendingQueryTenant::StringAttribute(pending_read);
+        self.send_pend_request(bus, request, timeout_ms, tenant)
+            .await;
+    }
+
+    async fn send_pend_attributes_report_request(
+        &mut self,
+        pending_report: PendingAttributesReport,
+    ) {
+        if let Some(request) = pending_report.next_request() {
+            let bus = Some(pending_report.bus);
+            let timeout_ms = pending_report.timeout_ms;
+            let tenant = PendingQueryTenant::AttributesReport(pending_report);
+            self.send_pend_request(bus, request, timeout_ms, tenant)
+                .await;
+        } else {
+            pending_report.complete();
+        }
+    }
+
+    fn is_discovery_in_progress(&self) -> bool {
+        self.discovery_parked.is_some()
+            || self
+                .pending_queries
+                .iter()
+                .any(|pq| matches!(pq.tenant, PendingQueryTenant::Discovery(_)))
+    }
+
+    async fn send_pend_discovery_request(&mut self, mut pending_discovery: PendingDiscovery) {
+        while !pending_discovery.is_complete() {
+            // Rate limit the probes, the controller loop resumes the discovery when due
+            if pending_discovery
+                .time_to_next_request(&Instant::now())
+                .is_some()
+            {
+                self.discovery_parked = Some(pending_discovery);
+                return;
+            }
+
+            let Some((bus, request)) = pending_discovery.next_request() else {
+                break;
+            };
+            let timeout_ms = Some(pending_discovery.timeout_ms);
+            let tenant = PendingQueryTenant::Discovery(pending_discovery);
+            match self
+                .send_pend_request(Some(bus), request, timeout_ms, tenant)
+                .await
+            {
+                Some(PendingQueryTenant::Discovery(pending)) => pending_discovery = pending,
+                _ => return,
+            }
+        }
+
+        info!("Discovery completed");
+        pending_discovery.complete();
+    }
+
+    // Start the reconciliation of the device attributes with the configuration
+    async fn reconcile_device_attributes(&mut self, bus: CanBusId, did: DeviceId) {
+        let bus_name = &self.buses[bus].name;
+        let (attributes, unknown) = match self.config.devices.get_device_attributes(bus_name, &did)
+        {
+            Some(attributes_config) => attributes_config.get_attributes(),
+            None => return,
+        };
+
+        info!(
+            "Reconciling attributes of device {} on bus {}",
+            did, bus_name
+        );
+        let pending_config =
+            PendingAttributesConfig::new(did, bus, attributes).with_unknown_attributes(unknown);
+        self.send_pend_attributes_config_request(pending_config)
+            .await;
+    }
+
+    async fn send_pend_attributes_config_request(
+        &mut self,
+        mut pending_config: PendingAttributesConfig,
+    ) {
+        // Requests failing to be sent end the current attribute, continue with the next one
+        while let Some(request) = pending_config.next_request() {
+            let bus = Some(pending_config.bus);
+            let tenant = PendingQueryTenant::AttributesConfig(pending_config);
+            match self.send_pend_request(bus, request, None, tenant).await {
+                Some(PendingQueryTenant::AttributesConfig(pending)) => pending_config = pending,
+                _ => return,
+            }
+        }
+
+        // All attributes reconciled, apply the outcome to the device
+        let (bus, did) = (pending_config.bus, pending_config.did);
+        let status = pending_config.into_status();
+        if !status.failed.is_empty() || !status.drifted.is_empty() || !status.unknown.is_empty() {
+            warn!(
+                "Device {} attributes reconciled, drifted: {:?} failed: {:?} unknown: {:?}",
+                did, status.drifted, status.failed, status.unknown
+            );
+        }
+        if let Some(device) = self.devices.get_mut(&(bus, did)) {
+            device.attributes_config = Some(status);
+        }
+    }
+
+    // Start the synchronization of the device clock with the controller time
+    async fn sync_device_time(&mut self, bus: CanBusId, did: DeviceId, mode: TimeSyncMode) {
+        let Some(device) = self.devices.get(&(bus, did)) else {
+            return;
+        };
+
+        let max_drift = self
+            .config
+            .time_sync_max_drift
+            .unwrap_or(TIME_SYNC_DEFAULT_MAX_DRIFT_S);
+        let pending_sync =
+            PendingTimeSync::new(did, bus, mode, max_drift, device.time_sync.clone());
+        self.send_pend_time_sync_request(pending_sync).await;
+    }
+
+    async fn send_pend_time_sync_request(&mut self, mut pending_sync: PendingTimeSync) {
+        // A request failing to be sent ends the synchronization
+        while let Some(request) = pending_sync.next_request() {
+            let bus = Some(pending_sync.bus);
+            let tenant = PendingQueryTenant::TimeSync(pending_sync);
+            match self.send_pend_request(bus, request, None, tenant).await {
+                Some(PendingQueryTenant::TimeSync(pending)) => pending_sync = pending,
+                _ => return,
+            }
+        }
+
+        let (bus, did) = (pending_sync.bus, pending_sync.did);
+        let status = pending_sync.into_status();
+        if let Some(ref error) = status.error {
+            warn!("Device {} time synchronization failed: {}", did, error);
+        }
+        if let Some(device) = self.devices.get_mut(&(bus, did)) {
+            device.time_sync = status;
         }
     }
 
@@ -250,21 +528,30 @@ impl<IF: CanInterfaceTrait> CaniotDevicesController<IF> {
 
     pub async fn handle_caniot_frame(
         &mut self,
+        bus: CanBusId,
         frame: caniot::Response,
     ) -> Result<(), CaniotControllerError> {
         self.stats.iface_rx += 1;
         // TODO if multiple actions are pending, only the first one will be answered
         // For the other, the channel sender will be dropped and the response will be lost
         let mut answered_pending_action: Option<PendingAction> = None;
-
-        // Find pending queries that can be answered by this frame
-        // TODO broadcast should be handled differently as the oneshot channel cannot be used to send multiple responses
-        let pivot = self
+        let mut pending_string_attributes: Vec<PendingStringAttribute> = Vec::new();
+        let mut pending_reports: Vec<PendingAttributesReport> = Vec::new();
+        let mut pending_configs: Vec<PendingAttributesConfig> = Vec::new();
+        let mut pending_discoveries: Vec<PendingDiscovery> = Vec::new();
+        let mut pending_syncs: Vec<PendingTimeSync> = Vec::new();
+
+        // Broadcast queries collect the response and stay pending until their window closes
+        for pq in self
             .pending_queries
-            .partition_point(|pq| pq.match_response(&frame));
+            .iter_mut()
+            .filter(|pq| pq.is_broadcast() && pq.match_response(bus, &frame))
+        {
+            pq.collect_response(bus, &frame);
+        }
 
         // if a frame can answer multiple pending queries, remove all of them
-        for pq in self.pending_queries.drain(..pivot) {
+        for pq in take_answered_queries(&mut self.pending_queries, bus, &frame) {
             self.stats.pq_answered += 1;
             if let Some(pq_tenant) = pq.end_with_frame(frame.clone()) {
                 match pq_tenant {
@@ -281,15 +568,58 @@ impl<IF: CanInterfaceTrait> CaniotDevicesController<IF> {
                             );
                         }
                     }
+                    PendingQueryTenant::StringAttribute(pending_read) => {
+                        pending_string_attributes.push(pending_read);
+                    }
+                    PendingQueryTenant::AttributesReport(pending_report) => {
+                        pending_reports.push(pending_report);
+                    }
+                    PendingQueryTenant::AttributesConfig(pending_config) => {
+                        pending_configs.push(pending_config);
+                    }
+                    PendingQueryTenant::Discovery(pending_discovery) => {
+                        pending_discoveries.push(pending_discovery);
+                    }
+                    PendingQueryTenant::TimeSync(pending_sync) => {
+                        pending_syncs.push(pending_sync);
+                    }
                     _ => {}
                 }
             }
         }
 
+        // Continue reading multi-part attributes with the next part
+        for pending_read in pending_string_attributes {
+            self.send_pend_string_attribute_request(pending_read).await;
+        }
+
+        // Continue attributes reports with the next attribute
+        for pending_report in pending_reports {
+            self.send_pend_attributes_report_request(pending_report)
+                .await;
+        }
+
+        // Continue attributes reconciliations with the next step
+        for pending_config in pending_configs {
+            self.send_pend_attributes_config_request(pending_config)
+                .await;
+        }
+
+        // Continue the discovery with the next probe
+        for pending_discovery in pending_discoveries {
+            self.send_pend_discovery_request(pending_discovery).await;
+        }
+
+        // Continue clocks synchronizations with the next step
+        for pending_sync in pending_syncs {
+            self.send_pend_time_sync_request(pending_sync).await;
+        }
+
         // Get or create device
         let device_did = frame.device_id;
         let device = Self::device_get_or_create(
             &mut self.devices,
+            bus,
             device_did,
             &self.config.devices,
             self.storage.get_settings_store(),
@@ -299,13 +629,14 @@ impl<IF: CanInterfaceTrait> CaniotDevicesController<IF> {
 
         // Let the device handle the frame
         let verdict = device.handle_frame(&frame.data, &None, &mut device_ctx)?;
-        match verdict {
-            Verdict::None => {}
-            Verdict::Request(request) => {
-                let request = Request::new(device_did, request);
-                Self::iface_send_caniot_frame(&mut self.iface, &mut self.stats, &request).await?;
-            }
-        }
+        self.scheduled_requests
+            .extend(ScheduledRequest::from_verdict(
+                bus,
+                device_did,
+                verdict,
+                TxPriority::Normal,
+                &Instant::now(),
+            ));
 
         Self::device_update_from_context(device, device_ctx).await?;
 
@@ -315,9 +646,13 @@ impl<IF: CanInterfaceTrait> CaniotDevicesController<IF> {
                 .response
                 .take()
                 .expect("Response not set for action");
-            let action_result =
-                device.handle_action_result(&answered_action.action, completed_by)?;
-            answered_action.send(Ok(action_result));
+            if let Some(error) = CaniotControllerError::from_response(&completed_by.data) {
+                answered_action.send(Err(error));
+            } else {
+                let action_result =
+                    device.handle_action_result(&answered_action.action, completed_by)?;
+                answered_action.send(Ok(action_result));
+            }
         }
 
         Ok(())
@@ -332,12 +667,56 @@ impl<IF: CanInterfaceTrait> CaniotDevicesController<IF> {
 
         // send timeout to all timed out queries
         for pq in timed_out_queries {
-            self.stats.pq_timeout += 1;
-            warn!(
-                "Pending query {} timed out after {} ms",
-                pq.query, pq.timeout_ms
-            );
-            pq.end_with_error(CaniotControllerError::Timeout);
+            // The window of broadcast queries closing is not an error
+            if !pq.is_broadcast() {
+                self.stats.pq_timeout += 1;
+                warn!(
+                    "Pending query {} timed out after {} ms",
+                    pq.query, pq.timeout_ms
+                );
+            }
+            let tenant = pq.end_with_error(CaniotControllerError::Timeout);
+            self.continue_pending_tenant(tenant).await;
+        }
+    }
+
+    // End the queries whose frame has been dropped from the TX queue of their bus,
+    // rather than waiting for their timeout
+    async fn handle_dropped_frames(&mut self, dropped: Vec<(CanBusId, socketcan::CanDataFrame)>) {
+        for (bus, frame) in dropped {
+            let Some(index) = self
+                .pending_queries
+                .iter()
+                .position(|pq| pq.is_dropped(bus, &frame))
+            else {
+                continue;
+            };
+
+            let pq = self.pending_queries.remove(index);
+            warn!("Pending query {} dropped on bus {}", pq.query, bus);
+            let tenant = pq.end_with_error(CaniotControllerError::Dropped);
+            self.continue_pending_tenant(tenant).await;
+        }
+    }
+
+    // Send the next request of a tenant returned by an ended query
+    async fn continue_pending_tenant(&mut self, tenant: Option<PendingQueryTenant>) {
+        match tenant {
+            Some(PendingQueryTenant::AttributesReport(pending_report)) => {
+                self.send_pend_attributes_report_request(pending_report)
+                    .await;
+            }
+            Some(PendingQueryTenant::AttributesConfig(pending_config)) => {
+                self.send_pend_attributes_config_request(pending_config)
+                    .await;
+            }
+            Some(PendingQueryTenant::Discovery(pending_discovery)) => {
+                self.send_pend_discovery_request(pending_discovery).await;
+            }
+            Some(PendingQueryTenant::TimeSync(pending_sync)) => {
+                self.send_pend_time_sync_request(pending_sync).await;
+            }
+            _ => {}
         }
     }
 
@@ -347,7 +726,9 @@ impl<IF: CanInterfaceTrait> CaniotDevicesController<IF> {
         now: &DateTime<Utc>,
     ) -> Result<(), CaniotControllerError> {
         let storage = self.storage.clone();
-        for (did, device) in self
+        let mut reconciliations = Vec::new();
+        let mut time_syncs = Vec::new();
+        for ((bus, did), device) in self
             .devices
             .iter_mut()
             .filter(|(_, device)| device.is_expired(now))
@@ -360,17 +741,22 @@ impl<IF: CanInterfaceTrait> CaniotDevicesController<IF> {
                 let mut device_ctx = ProcessContext::new(None, storage.clone());
 
                 if let Some(verdict) = device.process_one_job(&mut device_ctx).transpose()? {
-                    match verdict {
-                        Verdict::None => {}
-                        Verdict::Request(request) => {
-                            let request = Request::new(*did, request);
-                            Self::iface_send_caniot_frame(
-                                &mut self.iface,
-                                &mut self.stats,
-                                &request,
-                            )
-                            .await?;
-                        }
+                    // Jobs are mostly periodic polling
+                    self.scheduled_requests
+                        .extend(ScheduledRequest::from_verdict(
+                            *bus,
+                            *did,
+                            verdict,
+                            TxPriority::Low,
+                            &Instant::now(),
+                        ));
+
+                    if device_ctx.request_attributes_reconciliation {
+                        reconciliations.push((*bus, *did));
+                    }
+
+                    if let Some(mode) = device_ctx.request_time_sync {
+                        time_syncs.push((*bus, *did, mode));
                     }
 
                     Self::device_update_from_context(device, device_ctx).await?;
@@ -380,9 +766,57 @@ impl<IF: CanInterfaceTrait> CaniotDevicesController<IF> {
             }
         }
 
+        for (bus, did) in reconciliations {
+            self.reconcile_device_attributes(bus, did).await;
+        }
+
+        for (bus, did, mode) in time_syncs {
+            self.sync_device_time(bus, did, mode).await;
+        }
+
         Ok(())
     }
 
+    // Queue the scheduled requests which are due, in the order they were scheduled
+    fn send_scheduled_requests(&mut self, now: &Instant) {
+        if !self
+            .scheduled_requests
+            .iter()
+            .any(|scheduled| scheduled.is_due(now))
+        {
+            return;
+        }
+
+        // Stable sort, requests due at the same time keep their order
+        self.scheduled_requests
+            .sort_by_key(|scheduled| scheduled.send_at);
+        let split_index = self
+            .scheduled_requests
+            .partition_point(|scheduled| scheduled.is_due(now));
+
+        let due: Vec<ScheduledRequest> = self.scheduled_requests.drain(..split_index).collect();
+        for scheduled in due {
+            let result = match scheduled.bus {
+                Some(bus) => Ok(bus),
+                None => self.route(&scheduled.request.device_id),
+            }
+            .and_then(|bus| {
+                Self::iface_send_caniot_frame(
+                    &mut self.buses[bus],
+                    &mut self.stats,
+                    &scheduled.request,
+                    scheduled.priority,
+                )
+            });
+            if let Err(err) = result {
+                error!(
+                    "Failed to send scheduled request {}: {}",
+                    scheduled.request, err
+                );
+            }
+        }
+    }
+
     // Return a list of devices with given filter
     fn get_devices_infos(&self, filter: DeviceFilter) -> Vec<DeviceInfos> {
         let filter_function = filter.get_filter_function();
@@ -397,8 +831,9 @@ impl<IF: CanInterfaceTrait> CaniotDevicesController<IF> {
     }
 
     fn get_device_by_did(&mut self, did: &DeviceId) -> Result<&mut Device, CaniotControllerError> {
+        let bus = self.route(did)?;
         self.devices
-            .get_mut(did)
+            .get_mut(&(bus, *did))
             .ok_or(CaniotControllerError::NoSuchDevice)
     }
 
@@ -431,15 +866,35 @@ impl<IF: CanInterfaceTrait> CaniotDevicesController<IF> {
         respond_to: Sender<Result<DeviceActionResult, CaniotControllerError>>,
         timeout_ms: Option<u32>,
     ) {
+        // Reading all attributes spans multiple requests, handle it here
+        if let DeviceAction::ReadAllAttributes = action {
+            let target = did
+                .ok_or(CaniotControllerError::GenericDeviceActionNeedsDID)
+                .and_then(|did| Ok((self.route(&did)?, did)));
+            match target {
+                Ok((bus, did)) => {
+                    let pending_report =
+                        PendingAttributesReport::new(did, bus, timeout_ms, respond_to);
+                    self.send_pend_attributes_report_request(pending_report)
+                        .await;
+                }
+                Err(err) => {
+                    let _ = respond_to.send(Err(err));
+                }
+            }
+            return;
+        }
+
         let result = self.handle_api_device_action_inner(did, action).await;
 
         match result {
             Ok(ActionResultOrPending::Result(result)) => {
                 let _ = respond_to.send(Ok(result));
             }
-            Ok(ActionResultOrPending::Pending(action, request)) => {
+            Ok(ActionResultOrPending::Pending(action, bus, request)) => {
                 let tenant = PendingQueryTenant::Action(PendingAction::new(action, respond_to));
                 self.send_pend_request(
+                    Some(bus),
                     request,
                     Some(
                         timeout_ms.unwrap_or(
@@ -475,7 +930,7 @@ impl<IF: CanInterfaceTrait> CaniotDevicesController<IF> {
             Ok(verdict) => match verdict {
                 ActionVerdict::ActionPendingOn(request) => {
                     let request = Request::new(device.did, request);
-                    Ok(ActionResultOrPending::Pending(action, request))
+                    Ok(ActionResultOrPending::Pending(action, device.bus, request))
                 }
                 ActionVerdict::ActionResult(result) => Ok(ActionResultOrPending::Result(result)),
                 ActionVerdict::ActionRejected(reason) => Err(DeviceError::ActionRejected(reason)),
@@ -502,11 +957,78 @@ impl<IF: CanInterfaceTrait> CaniotDevicesController<IF> {
                 timeout_ms,
                 respond_to,
             } => {
-                if let Some(respond_to) = respond_to {
-                    let tenant = PendingQueryTenant::Query(respond_to);
-                    self.send_pend_request(query, timeout_ms, tenant).await;
+                // Broadcast queries are sent on all buses
+                let bus = if query.is_broadcast() {
+                    Ok(None)
+                } else {
+                    self.route(&query.device_id).map(Some)
+                };
+                match (bus, respond_to) {
+                    (Ok(bus), Some(respond_to)) => {
+                        let tenant = PendingQueryTenant::Query(respond_to);
+                        self.send_pend_request(bus, query, timeout_ms, tenant).await;
+                    }
+                    (Ok(bus), None) => {
+                        let _ = self.send_caniot_frame(bus, &query, TxPriority::High).await;
+                    }
+                    (Err(err), Some(respond_to)) => {
+                        let _ = respond_to.send(Err(err));
+                    }
+                    (Err(err), None) => error!("Query not sent: {}", err),
+                }
+            }
+            CaniotApiMessage::Discover {
+                probe_timeout_ms,
+                interval_ms,
+                respond_to,
+            } => {
+                if self.is_discovery_in_progress() {
+                    let _ = respond_to.send(Err(CaniotControllerError::DiscoveryInProgress));
+                } else {
+                    info!("Starting bus discovery");
+                    let pending_discovery = PendingDiscovery::new(
+                        self.buses.len(),
+                        probe_timeout_ms,
+                        interval_ms,
+                        respond_to,
+                    );
+                    self.send_pend_discovery_request(pending_discovery).await;
+                }
+            }
+            CaniotApiMessage::BroadcastQuery {
+                query,
+                window_ms,
+                respond_to,
+            } => {
+                let window_ms = window_ms.unwrap_or(
+                    self.config
+                        .broadcast_default_window
+                        .unwrap_or(BROADCAST_DEFAULT_WINDOW_MS),
+                );
+                let tenant = PendingQueryTenant::Broadcast(PendingBroadcast::new(respond_to));
+                self.send_pend_request(None, query, Some(window_ms), tenant)
+                    .await;
+            }
+            CaniotApiMessage::ReadStringAttribute {
+                did,
+                attribute,
+                timeout_ms,
+                respond_to,
+            } => {
+                if !attribute.is_multi_part() {
+                    let _ = respond_to.send(Err(CaniotControllerError::UnsupportedQuery));
                 } else {
-                    let _ = self.send_caniot_frame(&query).await;
+                    match self.route(&did) {
+                        Ok(bus) => {
+                            let pending_read = PendingStringAttribute::new(
+                                did, bus, attribute, timeout_ms, respond_to,
+                            );
+                            self.send_pend_string_attribute_request(pending_read).await;
+                        }
+                        Err(err) => {
+                            let _ = respond_to.send(Err(err));
+                        }
+                    }
                 }
             }
             CaniotApiMessage::DeviceAction {
@@ -533,20 +1055,39 @@ impl<IF: CanInterfaceTrait> CaniotDevicesController<IF> {
             }
             #[cfg(feature = "can-tunnel")]
             CaniotApiMessage::EstablishCanTunnel {
+                config,
                 rx_queue,
                 tx_queue,
                 respond_to,
             } => {
-                let result = self
-                    .tunnel_server
-                    .establish_can_tunnel(rx_queue, tx_queue)
-                    .map_err(Into::into);
+                let result = if config.bus < self.buses.len() {
+                    Ok(self
+                        .tunnel_server
+                        .establish_can_tunnel(config, rx_queue, tx_queue))
+                } else {
+                    Err(CanTunnelError::NoSuchBus(config.bus).into())
+                };
+                let _ = respond_to.send(result);
+            }
+            #[cfg(feature = "can-tunnel")]
+            CaniotApiMessage::GetCanTunnels { respond_to } => {
+                let _ = respond_to.send(self.tunnel_server.get_tunnels());
+            }
+            #[cfg(feature = "can-tunnel")]
+            CaniotApiMessage::CloseCanTunnel { id, respond_to } => {
+                let result = self.tunnel_server.close_tunnel(id).map_err(Into::into);
                 let _ = respond_to.send(result);
             }
-            #[cfg(feature = "emu")]
-            CaniotApiMessage::EmulationRequest { event } => self
-                .iface
-                .ioctl(CAN_IOCTL_SEND_EMU_EVENT, Into::<i32>::into(event) as u32)?,
+            CaniotApiMessage::EmulationRequest { event } => {
+                let emulated = self
+                    .buses
+                    .iter_mut()
+                    .filter(|bus| bus.backend == CanBackend::Emulated);
+                for bus in emulated {
+                    bus.iface
+                        .ioctl(CAN_IOCTL_SEND_EMU_EVENT, Into::<i32>::into(event) as u32)?;
+                }
+            }
         }
 
         Ok(())
@@ -557,34 +1098,77 @@ impl<IF: CanInterfaceTrait> CaniotDevicesController<IF> {
             endpoint: caniot::Endpoint::BoardControl,
         }
         .into_broadcast();
-        self.send_caniot_frame(&frame).await
+        self.send_caniot_frame(None, &frame, TxPriority::Low).await
     }
 
-    pub async fn handle_can_frame(&mut self, frame: CanDataFrame) {
-        // Send frame to tunnel if established
+    pub async fn handle_can_frame(&mut self, bus: CanBusId, rx_frame: CanRxFrame) {
+        let CanRxFrame { frame, timestamp } = rx_frame;
+
+        if let Some(recorder) = self.buses[bus].recorder.as_mut() {
+            let since_epoch = Duration::from_micros(timestamp.timestamp_micros().max(0) as u64);
+            recorder.record_at(since_epoch, CanDirection::Rx, &frame);
+        }
+
+        // Send frame to the tunnels attached to the bus
         #[cfg(feature = "can-tunnel")]
-        self.tunnel_server.notify_rx(frame.clone());
+        self.tunnel_server.notify_rx(
+            bus,
+            &CanRxFrame {
+                frame: frame.clone(),
+                timestamp,
+            },
+        );
 
         // Process the frame in the current controller
-        match caniot::Response::try_from(frame) {
+        match caniot::Response::try_from(frame.clone()) {
             Ok(frame) => {
-                info!("RX {}", frame);
+                let frame = frame.with_timestamp(timestamp);
+                info!("RX {} {}", self.buses[bus].name, frame);
 
-                let result = self.handle_caniot_frame(frame).await;
+                let result = self.handle_caniot_frame(bus, frame).await;
                 if let Err(err) = result {
                     error!("Failed to handle CANIOT frame {}", err);
                 }
             }
             Err(err) => {
                 self.stats.iface_malformed += 1;
-                error!("Failed to convert into CANIOT frame: {}", err)
+                match self.buses[bus].sniffer.as_mut() {
+                    Some(sniffer) => {
+                        debug!("Sniffed {:?} on bus {}", frame, self.buses[bus].name);
+                        sniffer.push(&frame, timestamp, err.to_string());
+                    }
+                    None => error!("Failed to convert into CANIOT frame: {}", err),
+                }
             }
         }
     }
 
+    /// Send a frame received from a tunnel on the bus it is attached to
+    pub fn handle_tunnel_frame(&mut self, bus: CanBusId, frame: socketcan::CanDataFrame) {
+        let Some(bus) = self.buses.get_mut(bus) else {
+            return;
+        };
+
+        if let Err(err) = bus.send(frame, TxPriority::Normal) {
+            error!("Failed to send tunnel frame on bus {}: {}", bus.name, err);
+        }
+    }
+
     pub async fn loop_process(&mut self, sys_now: &Instant, utc_now: &DateTime<Utc>) -> Duration {
+        // Resume the discovery if its next probe is due
+        if let Some(pending_discovery) = self.discovery_parked.take() {
+            if pending_discovery.time_to_next_request(sys_now).is_none() {
+                self.send_pend_discovery_request(pending_discovery).await;
+            } else {
+                self.discovery_parked = Some(pending_discovery);
+            }
+        }
+
         let sleep_time = ttl(&[
             self.pending_queries.iter().ttl(sys_now),
+            self.discovery_parked
+                .as_ref()
+                .map(|discovery| discovery.time_to_next_request(sys_now).unwrap_or_default()),
             self.devices.values().ttl(&utc_now).map(|chrono_duration| {
                 chrono_duration
                     .to_std()
@@ -600,21 +1184,48 @@ impl<IF: CanInterfaceTrait> CaniotDevicesController<IF> {
             error!("Failed to process devices: {}", err);
         }
 
-        sleep_time
-    }
-
-    pub fn tunnel_poll_tx(&mut self) -> Pending<Option<CanDataFrame>> {
-        let tunnel_poll_tx: Pending<Option<CanDataFrame>> = {
-            #[cfg(feature = "can-tunnel")]
-            {
-                self.tunnel_server.poll_tx()
-            }
-            #[cfg(not(feature = "can-tunnel"))]
-            {
-                futures::future::pending()
+        // Queue the requests of the devices verdicts which are due, including
+        // those scheduled after sys_now by the jobs just processed
+        let now = Instant::now();
+        self.send_scheduled_requests(&now);
+        let sleep_time = self
+            .scheduled_requests
+            .ttl(&now)
+            .map_or(sleep_time, |scheduled_sleep_time| {
+                sleep_time.min(scheduled_sleep_time)
+            });
+
+        // Send the frames queued so far, including those queued since the last loop,
+        // the timeout of the pending queries starts once their frame is sent
+        let mut dropped = Vec::new();
+        for (bus, can_bus) in self.buses.iter_mut().enumerate() {
+            for frame in can_bus.flush(sys_now).await {
+                let now = Instant::now();
+                for pq in self.pending_queries.iter_mut() {
+                    if pq.handle_sent(bus, &frame, &now) {
+                        break;
+                    }
+                }
             }
-        };
+            dropped.extend(can_bus.take_dropped().into_iter().map(|frame| (bus, frame)));
+        }
+        self.handle_dropped_frames(dropped).await;
+
+        // The queries just sent time out sooner than while they were queued
+        let sleep_time = self
+            .pending_queries
+            .iter()
+            .ttl(&Instant::now())
+            .map_or(sleep_time, |pending_sleep_time| {
+                sleep_time.min(pending_sleep_time)
+            });
+
+        let tx_sleep_time = ttl(&self
+            .buses
+            .iter()
+            .map(|bus| bus.time_to_flush(sys_now))
+            .collect::<Vec<_>>());
 
-        tunnel_poll_tx
+        tx_sleep_time.map_or(sleep_time, |tx_sleep_time| sleep_time.min(tx_sleep_time))
     }
 }
@@ -4,5 +4,25 @@ pub mod auto_attach;
 pub mod caniot_devices_controller;
 pub mod device_filter;
 pub mod pending_action;
+pub mod pending_attributes_config;
+pub mod pending_attributes_report;
+pub mod pending_broadcast;
+pub mod pending_discovery;
 pub mod pending_query;
+pub mod pending_string_attribute;
+pub mod pending_time_sync;
+pub mod scheduled_request;
 pub mod stats;
+
+#[cfg(test)]
+mod pending_attributes_config_test;
+#[cfg(test)]
+mod pending_attributes_report_test;
+#[cfg(test)]
+mod pending_discovery_test;
+#[cfg(test)]
+mod pending_query_test;
+#[cfg(test)]
+mod pending_time_sync_test;
+#[cfg(test)]
+mod scheduled_request_test;
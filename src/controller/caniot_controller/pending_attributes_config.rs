@@ -0,0 +1,147 @@
+use std::{collections::VecDeque, fmt::Debug};
+
+use crate::{
+    bus::CanBusId,
+    caniot::{self, DeviceId, ResponseData},
+    controller::AttributesConfigStatus,
+};
+
+use super::caniot_devices_controller::CaniotControllerError;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Step {
+    // Read the current value
+    Read,
+    // Write the configured value
+    Write,
+    // Read back the written value
+    Verify,
+}
+
+/// Reconciliation of the device attributes with the configured values.
+///
+/// Each attribute is read, written if it differs from the configuration and
+/// read back to verify the device applied it.
+pub struct PendingAttributesConfig {
+    pub did: DeviceId,
+    pub bus: CanBusId,
+
+    // Attributes still to be reconciled, the front one is being processed
+    attributes: VecDeque<(caniot::Attribute, u32)>,
+    step: Step,
+
+    status: AttributesConfigStatus,
+}
+
+impl PendingAttributesConfig {
+    pub fn new(did: DeviceId, bus: CanBusId, attributes: Vec<(caniot::Attribute, u32)>) -> Self {
+        let mut status = AttributesConfigStatus::new();
+
+        // Attributes which cannot be written fail immediately
+        let attributes = attributes
+            .into_iter()
+            .filter(|(attr, _)| {
+                if !attr.is_writable() {
+                    status.failed.push((*attr, "read-only".to_string()));
+                    false
+                } else if !attr.is_available_for_class(did.class) {
+                    status.failed.push((*attr, "wrong class".to_string()));
+                    false
+                } else {
+                    true
+                }
+            })
+            .collect();
+
+        Self {
+            did,
+            bus,
+            attributes,
+            step: Step::Read,
+            status,
+        }
+    }
+
+    /// Attribute names of the configuration which do not exist, they fail the reconciliation
+    pub fn with_unknown_attributes(mut self, names: Vec<String>) -> Self {
+        self.status.unknown = names;
+        self
+    }
+
+    /// Next request to send, None if all attributes have been reconciled
+    pub fn next_request(&self) -> Option<caniot::Request> {
+        self.attributes
+            .front()
+            .map(|(attr, value)| match self.step {
+                Step::Read | Step::Verify => {
+                    caniot::build_attribute_read_request(self.did, attr.key())
+                }
+                Step::Write => caniot::build_attribute_write_request(self.did, attr.key(), *value),
+            })
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.attributes.is_empty()
+    }
+
+    fn next_attribute(&mut self) {
+        self.attributes.pop_front();
+        self.step = Step::Read;
+    }
+
+    fn fail(&mut self, reason: String) {
+        if let Some((attr, _)) = self.attributes.front() {
+            self.status.failed.push((*attr, reason));
+        }
+        self.next_attribute();
+    }
+
+    pub fn handle_response(&mut self, data: &ResponseData) {
+        let Some((attr, expected)) = self.attributes.front().copied() else {
+            return;
+        };
+
+        match (self.step, data) {
+            (Step::Read, ResponseData::Attribute { value, .. }) => {
+                if *value == expected {
+                    self.next_attribute();
+                } else {
+                    self.status.drifted.push(attr);
+                    self.step = Step::Write;
+                }
+            }
+            (Step::Write, ResponseData::Attribute { .. }) => {
+                self.step = Step::Verify;
+            }
+            (Step::Verify, ResponseData::Attribute { value, .. }) => {
+                if *value == expected {
+                    self.next_attribute();
+                } else {
+                    self.fail(format!("value {} after write", value));
+                }
+            }
+            (_, ResponseData::Error { error, .. }) => {
+                self.fail(format!("error {:?}", error));
+            }
+            (_, ResponseData::Telemetry { .. }) => {}
+        }
+    }
+
+    pub fn handle_error(&mut self, error: CaniotControllerError) {
+        self.fail(error.to_string());
+    }
+
+    pub fn into_status(self) -> AttributesConfigStatus {
+        self.status
+    }
+}
+
+impl Debug for PendingAttributesConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PendingAttributesConfig")
+            .field("did", &self.did)
+            .field("remaining", &self.attributes.len())
+            .field("step", &self.step)
+            .finish()
+    }
+}
@@ -0,0 +1,146 @@
+use crate::caniot::{Attribute, DeviceId, ErrorCode, ErrorSource, RequestData, ResponseData};
+
+use super::{
+    caniot_devices_controller::CaniotControllerError,
+    pending_attributes_config::PendingAttributesConfig,
+};
+
+fn attribute(attr: Attribute, value: u32) -> ResponseData {
+    ResponseData::Attribute {
+        key: attr.key(),
+        value,
+    }
+}
+
+fn next_request(pending: &PendingAttributesConfig) -> Option<RequestData> {
+    pending.next_request().map(|request| request.data)
+}
+
+#[test]
+fn test_attributes_config_filtered() {
+    // Class 0 device
+    let pending = PendingAttributesConfig::new(
+        DeviceId::from_u8(1),
+        0,
+        vec![
+            (Attribute::Version, 1),
+            (Attribute::ConfigCls1GpioPulseDurationPc0, 500),
+        ],
+    );
+    assert!(pending.is_complete());
+    assert!(pending.next_request().is_none());
+
+    let status = pending.into_status();
+    assert!(status.drifted.is_empty());
+    assert_eq!(
+        status.failed,
+        vec![
+            (Attribute::Version, "read-only".to_string()),
+            (
+                Attribute::ConfigCls1GpioPulseDurationPc0,
+                "wrong class".to_string()
+            ),
+        ]
+    );
+}
+
+#[test]
+fn test_attributes_config_reconcile() {
+    let period = Attribute::ConfigTelemetryPeriod;
+    let delay = Attribute::ConfigTelemetryDelay;
+    let mut pending =
+        PendingAttributesConfig::new(DeviceId::from_u8(1), 0, vec![(period, 60000), (delay, 100)]);
+
+    // Already configured, Read only
+    assert!(matches!(
+        next_request(&pending),
+        Some(RequestData::AttributeRead { key }) if key == period.key()
+    ));
+    pending.handle_response(&attribute(period, 60000));
+
+    // Drifted, Read -> Write -> Verify
+    assert!(matches!(
+        next_request(&pending),
+        Some(RequestData::AttributeRead { key }) if key == delay.key()
+    ));
+    pending.handle_response(&attribute(delay, 0));
+    assert!(matches!(
+        next_request(&pending),
+        Some(RequestData::AttributeWrite { key, value: 100 }) if key == delay.key()
+    ));
+    pending.handle_response(&attribute(delay, 100));
+    assert!(matches!(
+        next_request(&pending),
+        Some(RequestData::AttributeRead { key }) if key == delay.key()
+    ));
+    pending.handle_response(&attribute(delay, 100));
+    assert!(pending.is_complete());
+
+    let status = pending.into_status();
+    assert_eq!(status.drifted, vec![delay]);
+    assert!(status.failed.is_empty());
+}
+
+#[test]
+fn test_attributes_config_failures() {
+    let period = Attribute::ConfigTelemetryPeriod;
+    let delay = Attribute::ConfigTelemetryDelay;
+    let flags = Attribute::ConfigFlags;
+    let mut pending = PendingAttributesConfig::new(
+        DeviceId::from_u8(1),
+        0,
+        vec![(period, 60000), (delay, 100), (flags, 1)],
+    );
+
+    // Value not applied by the device
+    pending.handle_response(&attribute(period, 1000));
+    pending.handle_response(&attribute(period, 60000));
+    pending.handle_response(&attribute(period, 1000));
+
+    // Error response, then no response, the next attribute is processed
+    pending.handle_response(&ResponseData::Error {
+        source: ErrorSource::Attribute(Some(delay.key())),
+        error: Some(ErrorCode::Ekey),
+    });
+    assert!(matches!(
+        next_request(&pending),
+        Some(RequestData::AttributeRead { key }) if key == flags.key()
+    ));
+    pending.handle_error(CaniotControllerError::Timeout);
+    assert!(pending.is_complete());
+
+    let status = pending.into_status();
+    assert_eq!(status.drifted, vec![period]);
+    assert_eq!(
+        status.failed,
+        vec![
+            (period, "value 1000 after write".to_string()),
+            (delay, "error Some(Ekey)".to_string()),
+            (flags, CaniotControllerError::Timeout.to_string()),
+        ]
+    );
+}
+
+#[test]
+fn test_attributes_config_unknown() {
+    let mut pending = PendingAttributesConfig::new(
+        DeviceId::from_u8(1),
+        0,
+        vec![(Attribute::ConfigTelemetryPeriod, 60000)],
+    )
+    .with_unknown_attributes(vec!["ConfigTelemetryPeriode".to_string()]);
+
+    // Known attributes are still reconciled
+    pending.handle_response(&attribute(Attribute::ConfigTelemetryPeriod, 60000));
+    assert!(pending.is_complete());
+
+    let status = pending.into_status();
+    assert!(status.failed.is_empty());
+    assert_eq!(status.unknown, vec!["ConfigTelemetryPeriode".to_string()]);
+    let alert = status.get_alert().unwrap();
+    assert_eq!(alert.name, "Configuration non appliquée");
+    assert_eq!(
+        alert.description.as_deref(),
+        Some("ConfigTelemetryPeriode: attribut inconnu")
+    );
+}
@@ -0,0 +1,138 @@
+use std::{collections::VecDeque, fmt::Debug};
+
+use strum::IntoEnumIterator;
+use tokio::sync::oneshot;
+
+use crate::{
+    bus::CanBusId,
+    caniot::{self, AttributeValue, DeviceId, ResponseData},
+    controller::{AttributeReadError, DeviceActionResult, DeviceAttributesReport},
+};
+
+use super::caniot_devices_controller::CaniotControllerError;
+
+// Attributes not answered in a row after which the device is considered unresponsive
+const ATTRIBUTES_REPORT_MAX_CONSECUTIVE_TIMEOUTS: u8 = 3;
+
+/// Read of all attributes of a device, attributes (and their parts) are read one after the other
+pub struct PendingAttributesReport {
+    pub did: DeviceId,
+    pub bus: CanBusId,
+    pub timeout_ms: Option<u32>,
+
+    // Attributes still to be read, the front one is being read
+    attributes: VecDeque<caniot::Attribute>,
+
+    // Part being read and bytes received for the current multi-part attribute
+    part: u8,
+    buffer: Vec<u8>,
+
+    // Attributes not answered since the last response
+    consecutive_timeouts: u8,
+
+    report: DeviceAttributesReport,
+
+    send_to: oneshot::Sender<Result<DeviceActionResult, CaniotControllerError>>,
+}
+
+impl PendingAttributesReport {
+    pub fn new(
+        did: DeviceId,
+        bus: CanBusId,
+        timeout_ms: Option<u32>,
+        send_to: oneshot::Sender<Result<DeviceActionResult, CaniotControllerError>>,
+    ) -> Self {
+        let attributes = caniot::Attribute::iter()
+            .filter(|attr| attr.is_available_for_class(did.class) && !attr.is_reserved())
+            .collect();
+
+        Self {
+            did,
+            bus,
+            timeout_ms,
+            attributes,
+            part: 0,
+            buffer: Vec::new(),
+            consecutive_timeouts: 0,
+            report: DeviceAttributesReport::new(did),
+            send_to,
+        }
+    }
+
+    /// Request reading the next attribute (part), None if all attributes have been read
+    pub fn next_request(&self) -> Option<caniot::Request> {
+        self.attributes
+            .front()
+            .map(|attr| caniot::build_attribute_read_request(self.did, attr.part_key(self.part)))
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.attributes.is_empty()
+    }
+
+    fn record(&mut self, value: Result<AttributeValue, AttributeReadError>) {
+        if let Some(attribute) = self.attributes.pop_front() {
+            self.report.push(attribute, value);
+        }
+        self.part = 0;
+        self.buffer.clear();
+    }
+
+    pub fn handle_response(&mut self, data: &ResponseData) {
+        let Some(attribute) = self.attributes.front().copied() else {
+            return;
+        };
+
+        self.consecutive_timeouts = 0;
+
+        match data {
+            ResponseData::Attribute { value, .. } if attribute.is_multi_part() => {
+                self.buffer.extend_from_slice(&value.to_le_bytes());
+                self.part += 1;
+                if attribute.is_string_complete(&self.buffer) {
+                    let value = attribute.decode_string(&self.buffer);
+                    self.record(Ok(AttributeValue::String(value)));
+                }
+            }
+            ResponseData::Attribute { value, .. } => {
+                let value = attribute
+                    .decode(*value)
+                    .map_err(|_| AttributeReadError::Decode);
+                self.record(value);
+            }
+            ResponseData::Error { error, .. } => {
+                self.record(Err(AttributeReadError::Device(*error)));
+            }
+            ResponseData::Telemetry { .. } => {}
+        }
+    }
+
+    pub fn handle_timeout(&mut self) {
+        self.consecutive_timeouts += 1;
+        self.record(Err(AttributeReadError::Timeout));
+    }
+
+    /// Whether the last attributes were not answered, the report is to be aborted
+    pub fn is_unresponsive(&self) -> bool {
+        self.consecutive_timeouts >= ATTRIBUTES_REPORT_MAX_CONSECUTIVE_TIMEOUTS
+    }
+
+    pub fn complete(self) {
+        let result = DeviceActionResult::AttributesReport(self.report);
+        self.send(Ok(result));
+    }
+
+    pub fn send(self, result: Result<DeviceActionResult, CaniotControllerError>) {
+        let _ = self.send_to.send(result);
+    }
+}
+
+impl Debug for PendingAttributesReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PendingAttributesReport")
+            .field("did", &self.did)
+            .field("remaining", &self.attributes.len())
+            .field("part", &self.part)
+            .finish()
+    }
+}
@@ -0,0 +1,125 @@
+use tokio::sync::oneshot;
+
+use crate::{
+    caniot::{
+        Attribute, AttributeValue, DeviceId, ErrorCode, ErrorSource, RequestData, ResponseData,
+    },
+    controller::{AttributeReadError, DeviceActionResult, DeviceAttributesReport},
+};
+
+use super::{
+    caniot_devices_controller::CaniotControllerError,
+    pending_attributes_report::PendingAttributesReport, pending_query::PendingQueryTenant,
+};
+
+type ReportReceiver = oneshot::Receiver<Result<DeviceActionResult, CaniotControllerError>>;
+
+fn report() -> (PendingAttributesReport, ReportReceiver) {
+    let (sender, receiver) = oneshot::channel();
+    let pending = PendingAttributesReport::new(DeviceId::from_u8(1), 0, None, sender);
+    (pending, receiver)
+}
+
+fn requested_key(pending: &PendingAttributesReport) -> u16 {
+    let RequestData::AttributeRead { key } = pending.next_request().unwrap().data else {
+        panic!("Not an attribute read request");
+    };
+    key
+}
+
+fn attribute(key: u16, value: u32) -> ResponseData {
+    ResponseData::Attribute { key, value }
+}
+
+fn into_report(receiver: &mut ReportReceiver) -> DeviceAttributesReport {
+    let Ok(DeviceActionResult::AttributesReport(report)) = receiver.try_recv().unwrap() else {
+        panic!("Attributes report not received");
+    };
+    report
+}
+
+#[test]
+fn test_attributes_report() {
+    let (mut pending, mut receiver) = report();
+
+    assert_eq!(requested_key(&pending), Attribute::NodeId.key());
+    pending.handle_response(&attribute(Attribute::NodeId.key(), 1));
+
+    // Error response, the next attribute is read
+    assert_eq!(requested_key(&pending), Attribute::Version.key());
+    pending.handle_response(&ResponseData::Error {
+        source: ErrorSource::Attribute(Some(Attribute::Version.key())),
+        error: Some(ErrorCode::Ekey),
+    });
+
+    // Multi-part attribute, parts are read until the end of the string
+    assert_eq!(requested_key(&pending), Attribute::Name.key());
+    pending.handle_response(&attribute(
+        Attribute::Name.key(),
+        u32::from_le_bytes(*b"gara"),
+    ));
+    assert_eq!(requested_key(&pending), Attribute::Name.part_key(1));
+    pending.handle_response(&attribute(
+        Attribute::Name.part_key(1),
+        u32::from_le_bytes(*b"ge\0\0"),
+    ));
+
+    // No response
+    assert_eq!(requested_key(&pending), Attribute::MagicNumber.key());
+    pending.handle_timeout();
+    assert!(!pending.is_unresponsive());
+
+    // Reserved and other class attributes are not read
+    while !pending.is_complete() {
+        let key = requested_key(&pending);
+        let attr = Attribute::try_from(key).unwrap();
+        assert!(!attr.is_reserved());
+        assert!(attr.is_available_for_class(0));
+        pending.handle_response(&attribute(key, 0));
+    }
+    pending.complete();
+
+    let report = into_report(&mut receiver);
+    assert!(report.get(Attribute::NodeId).unwrap().value.is_ok());
+    assert_eq!(
+        report.get(Attribute::Version).unwrap().value,
+        Err(AttributeReadError::Device(Some(ErrorCode::Ekey)))
+    );
+    assert_eq!(
+        report.get(Attribute::Name).unwrap().value,
+        Ok(AttributeValue::String("garage".to_string()))
+    );
+    assert_eq!(
+        report.get(Attribute::MagicNumber).unwrap().value,
+        Err(AttributeReadError::Timeout)
+    );
+    assert!(report.get(Attribute::SystemUnused4).is_none());
+    assert!(report
+        .get(Attribute::ConfigCls1GpioPulseDurationPc0)
+        .is_none());
+}
+
+#[test]
+fn test_attributes_report_unresponsive() {
+    let (mut pending, _receiver) = report();
+
+    // A response resets the count of attributes not answered
+    pending.handle_timeout();
+    pending.handle_timeout();
+    pending.handle_response(&attribute(requested_key(&pending), 0));
+    assert!(!pending.is_unresponsive());
+
+    // The report is aborted once the device stops answering
+    let (pending, mut receiver) = report();
+    let mut tenant = Some(PendingQueryTenant::AttributesReport(pending));
+    let mut timeouts = 0;
+    while let Some(t) = tenant {
+        timeouts += 1;
+        tenant = t.end_with_error(CaniotControllerError::Timeout);
+    }
+    assert_eq!(timeouts, 3);
+    assert!(matches!(
+        receiver.try_recv().unwrap(),
+        Err(CaniotControllerError::Timeout)
+    ));
+}
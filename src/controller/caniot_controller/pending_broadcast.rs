@@ -0,0 +1,55 @@
+use std::fmt::Debug;
+
+use tokio::sync::oneshot;
+
+use crate::{bus::CanBusId, caniot};
+
+use super::caniot_devices_controller::CaniotControllerError;
+
+/// Broadcast query collecting the responses of all devices until its window closes
+pub struct PendingBroadcast {
+    // Responses received so far and the bus they were received on, one per device
+    // of each bus, devices with the same did on different buses are distinct
+    responses: Vec<(CanBusId, caniot::Response)>,
+
+    send_to: oneshot::Sender<Result<Vec<(CanBusId, caniot::Response)>, CaniotControllerError>>,
+}
+
+impl PendingBroadcast {
+    pub fn new(
+        send_to: oneshot::Sender<Result<Vec<(CanBusId, caniot::Response)>, CaniotControllerError>>,
+    ) -> Self {
+        Self {
+            responses: Vec::new(),
+            send_to,
+        }
+    }
+
+    /// Collect a response received on the bus, only the first response of each device is kept
+    pub fn push(&mut self, bus: CanBusId, response: caniot::Response) {
+        if !self
+            .responses
+            .iter()
+            .any(|(b, r)| *b == bus && r.device_id == response.device_id)
+        {
+            self.responses.push((bus, response));
+        }
+    }
+
+    pub fn complete(self) {
+        let responses = self.responses;
+        let _ = self.send_to.send(Ok(responses));
+    }
+
+    pub fn send(self, result: Result<Vec<(CanBusId, caniot::Response)>, CaniotControllerError>) {
+        let _ = self.send_to.send(result);
+    }
+}
+
+impl Debug for PendingBroadcast {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PendingBroadcast")
+            .field("responses", &self.responses.len())
+            .finish()
+    }
+}
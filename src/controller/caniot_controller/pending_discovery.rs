@@ -0,0 +1,196 @@
+use std::{
+    collections::VecDeque,
+    fmt::Debug,
+    time::{Duration, Instant},
+};
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use tokio::sync::oneshot;
+
+use crate::{
+    bus::CanBusId,
+    caniot::{self, AttributeValue, DeviceId, ResponseData},
+};
+
+use super::caniot_devices_controller::CaniotControllerError;
+
+pub const DISCOVERY_DEFAULT_PROBE_TIMEOUT_MS: u32 = 100;
+pub const DISCOVERY_DEFAULT_INTERVAL_MS: u32 = 20;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DiscoveredNode {
+    pub did: DeviceId,
+    pub bus: CanBusId,
+    pub class: u8,
+    // Firmware version, None if the Version attribute could not be read
+    pub version: Option<caniot::Version>,
+    // Round-trip time of the telemetry probe
+    pub rtt: Duration,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DiscoveryReport {
+    pub started_at: DateTime<Utc>,
+    pub duration: Duration,
+    // Count of device ids probed, on all buses
+    pub probed: usize,
+    pub nodes: Vec<DiscoveredNode>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Probe {
+    // Telemetry request, nodes not answering are considered absent
+    Telemetry,
+    // Version attribute read, only for nodes which answered the telemetry probe
+    Version,
+}
+
+/// Bus scan probing all device ids of all buses one after the other.
+///
+/// Only one request is in flight at a time and requests are spaced by at least
+/// the configured interval so that the bus is not flooded.
+pub struct PendingDiscovery {
+    pub timeout_ms: u32,
+    interval: Duration,
+
+    // Device ids still to be probed, the front one is being probed
+    dids: VecDeque<(CanBusId, DeviceId)>,
+    probe: Probe,
+
+    started_at: Instant,
+    // Time the last probe was actually sent on the bus, not when it was queued
+    last_sent_at: Option<Instant>,
+
+    report: DiscoveryReport,
+
+    send_to: oneshot::Sender<Result<DiscoveryReport, CaniotControllerError>>,
+}
+
+impl PendingDiscovery {
+    pub fn new(
+        buses: usize,
+        timeout_ms: Option<u32>,
+        interval_ms: Option<u32>,
+        send_to: oneshot::Sender<Result<DiscoveryReport, CaniotControllerError>>,
+    ) -> Self {
+        let dids: VecDeque<(CanBusId, DeviceId)> = (0..buses)
+            .flat_map(|bus| {
+                (0..=u8::MAX)
+                    .filter_map(|did| DeviceId::try_from_u8(did).ok())
+                    .filter(|did| !did.is_broadcast())
+                    .map(move |did| (bus, did))
+            })
+            .collect();
+
+        Self {
+            timeout_ms: timeout_ms.unwrap_or(DISCOVERY_DEFAULT_PROBE_TIMEOUT_MS),
+            interval: Duration::from_millis(
+                interval_ms.unwrap_or(DISCOVERY_DEFAULT_INTERVAL_MS) as u64
+            ),
+            report: DiscoveryReport {
+                started_at: Utc::now(),
+                duration: Duration::ZERO,
+                probed: dids.len(),
+                nodes: Vec::new(),
+            },
+            dids,
+            probe: Probe::Telemetry,
+            started_at: Instant::now(),
+            last_sent_at: None,
+            send_to,
+        }
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.dids.is_empty()
+    }
+
+    /// Time to wait before the next request can be sent, None if it can be sent now
+    pub fn time_to_next_request(&self, now: &Instant) -> Option<Duration> {
+        self.last_sent_at
+            .map(|sent_at| sent_at + self.interval)
+            .and_then(|due| due.checked_duration_since(*now))
+            .filter(|wait| !wait.is_zero())
+    }
+
+    /// Request for the current probe and the bus to send it on, to be sent now
+    pub fn next_request(&mut self) -> Option<(CanBusId, caniot::Request)> {
+        let (bus, did) = *self.dids.front()?;
+        let request = match self.probe {
+            Probe::Telemetry => {
+                caniot::build_telemetry_request(did, caniot::Endpoint::BoardControl)
+            }
+            Probe::Version => {
+                caniot::build_attribute_read_request(did, caniot::Attribute::Version.key())
+            }
+        };
+        Some((bus, request))
+    }
+
+    /// The request of the current probe left the TX queue of the bus
+    pub fn handle_sent(&mut self, now: Instant) {
+        self.last_sent_at = Some(now);
+    }
+
+    fn next_device(&mut self) {
+        self.dids.pop_front();
+        self.probe = Probe::Telemetry;
+    }
+
+    pub fn handle_response(&mut self, data: &ResponseData) {
+        let Some((bus, did)) = self.dids.front().copied() else {
+            return;
+        };
+
+        match self.probe {
+            Probe::Telemetry => {
+                // Any response, even an error, means the node is present
+                let rtt = self
+                    .last_sent_at
+                    .map(|sent_at| sent_at.elapsed())
+                    .unwrap_or_default();
+                self.report.nodes.push(DiscoveredNode {
+                    did,
+                    bus,
+                    class: did.class,
+                    version: None,
+                    rtt,
+                });
+                self.probe = Probe::Version;
+            }
+            Probe::Version => {
+                if let ResponseData::Attribute { value, .. } = data {
+                    if let Ok(AttributeValue::Version(version)) =
+                        caniot::Attribute::Version.decode(*value)
+                    {
+                        if let Some(node) = self.report.nodes.last_mut() {
+                            node.version = Some(version);
+                        }
+                    }
+                }
+                self.next_device();
+            }
+        }
+    }
+
+    /// No response (or the request could not be sent) for the current probe
+    pub fn handle_error(&mut self, _error: CaniotControllerError) {
+        self.next_device();
+    }
+
+    pub fn complete(mut self) {
+        self.report.duration = self.started_at.elapsed();
+        let _ = self.send_to.send(Ok(self.report));
+    }
+}
+
+impl Debug for PendingDiscovery {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PendingDiscovery")
+            .field("remaining", &self.dids.len())
+            .field("probe", &self.probe)
+            .field("found", &self.report.nodes.len())
+            .finish()
+    }
+}
@@ -0,0 +1,86 @@
+use std::time::{Duration, Instant};
+
+use tokio::sync::oneshot;
+
+use crate::caniot::{
+    self, Attribute, DeviceId, Endpoint, ErrorCode, ErrorSource, RequestData, ResponseData,
+};
+
+use super::{
+    caniot_devices_controller::CaniotControllerError, pending_discovery::PendingDiscovery,
+};
+
+#[test]
+fn test_discovery_probes() {
+    let (sender, mut receiver) = oneshot::channel();
+    let mut pending = PendingDiscovery::new(2, None, Some(20), sender);
+    let now = Instant::now();
+
+    // Nothing sent yet, the first probe is due now
+    assert!(pending.time_to_next_request(&now).is_none());
+    let (bus, request) = pending.next_request().unwrap();
+    assert_eq!((bus, request.device_id), (0, DeviceId::from_u8(0)));
+    assert!(matches!(request.data, RequestData::Telemetry { .. }));
+
+    // The interval starts when the probe is sent, not when it is queued
+    let sent_at = now - Duration::from_millis(50);
+    pending.handle_sent(sent_at);
+    assert!(pending.time_to_next_request(&now).is_none());
+    pending.handle_sent(now);
+    assert_eq!(
+        pending.time_to_next_request(&now),
+        Some(Duration::from_millis(20))
+    );
+    pending.handle_sent(sent_at);
+
+    // Any response to the telemetry probe means the node is present
+    pending.handle_response(&ResponseData::Error {
+        source: ErrorSource::Telemetry(Endpoint::BoardControl, None),
+        error: Some(ErrorCode::Ecmd),
+    });
+    let (bus, request) = pending.next_request().unwrap();
+    assert_eq!((bus, request.device_id), (0, DeviceId::from_u8(0)));
+    assert!(matches!(
+        request.data,
+        RequestData::AttributeRead { key } if key == Attribute::Version.key()
+    ));
+    pending.handle_response(&ResponseData::Attribute {
+        key: Attribute::Version.key(),
+        value: 0x0102,
+    });
+
+    // Absent node
+    let (_, request) = pending.next_request().unwrap();
+    assert_eq!(request.device_id, DeviceId::from_u8(1));
+    pending.handle_error(CaniotControllerError::Timeout);
+
+    // Node present on the second bus, the version cannot be read
+    let mut probes = 2;
+    while let Some((bus, request)) = pending.next_request() {
+        probes += 1;
+        if (bus, request.device_id) == (1, DeviceId::from_u8(16)) {
+            pending.handle_response(&ResponseData::Attribute {
+                key: Attribute::Version.key(),
+                value: 0,
+            });
+        }
+        pending.handle_error(CaniotControllerError::Timeout);
+    }
+    assert!(pending.is_complete());
+    pending.complete();
+
+    // All device ids but the broadcast one, on both buses
+    let report = receiver.try_recv().unwrap().unwrap();
+    assert_eq!(report.probed, 2 * 63);
+    assert_eq!(probes, report.probed);
+    assert_eq!(report.nodes.len(), 2);
+
+    let node = &report.nodes[0];
+    assert_eq!((node.bus, node.did), (0, DeviceId::from_u8(0)));
+    assert_eq!(node.version, Some(caniot::Version { major: 1, minor: 2 }));
+    assert!(node.rtt >= Duration::from_millis(50));
+
+    let node = &report.nodes[1];
+    assert_eq!((node.bus, node.did), (1, DeviceId::from_u8(16)));
+    assert_eq!(node.version, None);
+}
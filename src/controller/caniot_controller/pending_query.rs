@@ -1,9 +1,27 @@
 use std::time::{Duration, Instant};
 
-use crate::{caniot, utils::expirable::ExpirableTrait};
+use embedded_can::Frame as EmbeddedFrame;
+use itertools::partition;
+use socketcan::CanDataFrame;
+
+use crate::{
+    bus::{CanBusId, TxPriority},
+    caniot,
+    utils::expirable::ExpirableTrait,
+};
 use tokio::sync::oneshot;
 
-use super::{caniot_devices_controller::CaniotControllerError, pending_action::PendingAction};
+use super::{
+    caniot_devices_controller::CaniotControllerError, pending_action::PendingAction,
+    pending_attributes_config::PendingAttributesConfig,
+    pending_attributes_report::PendingAttributesReport, pending_broadcast::PendingBroadcast,
+    pending_discovery::PendingDiscovery, pending_string_attribute::PendingStringAttribute,
+    pending_time_sync::PendingTimeSync,
+};
+
+// Time a query may wait in the TX queue of the bus before its own timeout
+// starts, the frame may be dropped or held while the bus is off
+const PENDING_QUERY_MAX_QUEUED_MS: u64 = 5000;
 
 /// Initiator of a pending query, it represents the entity that is waiting for the query to be answered
 #[derive(Debug)]
@@ -11,37 +29,161 @@ pub enum PendingQueryTenant {
     // channel to reply to when query is answered
     Query(oneshot::Sender<Result<caniot::Response, CaniotControllerError>>),
 
-    // channel to reply when responses are received
-    // Broadcast(mpsc::Sender<Result<caniot::Response, ControllerError>>),
+    // Broadcast query collecting responses until its window closes (timeout)
+    Broadcast(PendingBroadcast),
 
     // The pending action the query is associated with
     Action(PendingAction),
+
+    // The multi-part attribute read the query is a part of
+    StringAttribute(PendingStringAttribute),
+
+    // The attributes report the query is a part of
+    AttributesReport(PendingAttributesReport),
+
+    // The attributes reconciliation the query is a part of,
+    // the controller continues it or applies its outcome to the device
+    AttributesConfig(PendingAttributesConfig),
+
+    // The bus discovery scan the query is a probe of, the controller continues it
+    Discovery(PendingDiscovery),
+
+    // The device clock synchronization the query is a part of,
+    // the controller continues it or applies its outcome to the device
+    TimeSync(PendingTimeSync),
 }
 
 impl PendingQueryTenant {
+    /// Priority of the requests sent for the tenant
+    pub fn tx_priority(&self) -> TxPriority {
+        match self {
+            Self::Query(_)
+            | Self::Action(_)
+            | Self::StringAttribute(_)
+            | Self::AttributesReport(_) => TxPriority::High,
+            Self::AttributesConfig(_) | Self::TimeSync(_) => TxPriority::Normal,
+            Self::Broadcast(_) | Self::Discovery(_) => TxPriority::Low,
+        }
+    }
+
     pub fn end_with_error(self, error: CaniotControllerError) -> Option<PendingQueryTenant> {
         match self {
             Self::Query(sender) => {
                 let _ = sender.send(Err(error)); // Do not panic if receiver is dropped
                 None
             }
+            Self::Broadcast(pending_broadcast) => {
+                match error {
+                    // End of the collection window
+                    CaniotControllerError::Timeout => pending_broadcast.complete(),
+                    error => pending_broadcast.send(Err(error)),
+                }
+                None
+            }
             Self::Action(pending_action) => {
                 pending_action.send(Err(error));
                 None
             }
+            Self::StringAttribute(pending_read) => {
+                pending_read.send(Err(error));
+                None
+            }
+            Self::AttributesReport(mut pending_report) => match error {
+                // An attribute not answered does not fail the whole report,
+                // unless the device stopped answering
+                CaniotControllerError::Timeout => {
+                    pending_report.handle_timeout();
+                    if pending_report.is_unresponsive() {
+                        pending_report.send(Err(CaniotControllerError::Timeout));
+                        None
+                    } else {
+                        Self::continue_attributes_report(pending_report)
+                    }
+                }
+                error => {
+                    pending_report.send(Err(error));
+                    None
+                }
+            },
+            Self::AttributesConfig(mut pending_config) => {
+                pending_config.handle_error(error);
+                Some(Self::AttributesConfig(pending_config))
+            }
+            Self::Discovery(mut pending_discovery) => {
+                pending_discovery.handle_error(error);
+                Some(Self::Discovery(pending_discovery))
+            }
+            Self::TimeSync(mut pending_sync) => {
+                pending_sync.handle_error(error);
+                Some(Self::TimeSync(pending_sync))
+            }
         }
     }
 
     pub fn end_with_frame(self, frame: caniot::Response) -> Option<PendingQueryTenant> {
         match self {
             Self::Query(sender) => {
-                let _ = sender.send(Ok(frame)); // Do not panic if receiver is dropped
+                let result = match CaniotControllerError::from_response(&frame.data) {
+                    Some(error) => Err(error),
+                    None => Ok(frame),
+                };
+                let _ = sender.send(result); // Do not panic if receiver is dropped
+                None
+            }
+            Self::Broadcast(pending_broadcast) => {
+                // Responses are collected with collect_response(), which knows their bus
+                pending_broadcast.complete();
                 None
             }
             Self::Action(mut pending_action) => {
                 pending_action.set_response(frame);
                 Some(PendingQueryTenant::Action(pending_action))
             }
+            Self::StringAttribute(mut pending_read) => match frame.data {
+                caniot::ResponseData::Attribute { value, .. } => {
+                    if pending_read.push_part(value) {
+                        pending_read.complete();
+                        None
+                    } else {
+                        // More parts to read
+                        Some(PendingQueryTenant::StringAttribute(pending_read))
+                    }
+                }
+                ref data => {
+                    // A failure on any part fails the whole read
+                    let error = CaniotControllerError::from_response(data)
+                        .unwrap_or(CaniotControllerError::UnexpectedResponse);
+                    pending_read.send(Err(error));
+                    None
+                }
+            },
+            Self::AttributesReport(mut pending_report) => {
+                pending_report.handle_response(&frame.data);
+                Self::continue_attributes_report(pending_report)
+            }
+            Self::AttributesConfig(mut pending_config) => {
+                pending_config.handle_response(&frame.data);
+                Some(Self::AttributesConfig(pending_config))
+            }
+            Self::Discovery(mut pending_discovery) => {
+                pending_discovery.handle_response(&frame.data);
+                Some(Self::Discovery(pending_discovery))
+            }
+            Self::TimeSync(mut pending_sync) => {
+                pending_sync.handle_response(&frame.data);
+                Some(Self::TimeSync(pending_sync))
+            }
+        }
+    }
+
+    fn continue_attributes_report(
+        pending_report: PendingAttributesReport,
+    ) -> Option<PendingQueryTenant> {
+        if pending_report.is_complete() {
+            pending_report.complete();
+            None
+        } else {
+            Some(PendingQueryTenant::AttributesReport(pending_report))
         }
     }
 }
@@ -53,19 +195,32 @@ pub struct PendingQuery {
     // query pending
     pub query: caniot::Request,
 
+    // bus the query was sent on, None if sent on all buses
+    pub bus: Option<CanBusId>,
+
     // timeout in milliseconds
     pub timeout_ms: u32,
 
-    // time when query was sent
-    sent_at: std::time::Instant,
+    // time when query was queued to be sent
+    queued_at: Instant,
+
+    // time when query was actually sent on the bus, the timeout starts then
+    sent_at: Option<Instant>,
 }
 
 impl PendingQuery {
-    pub fn new(query: caniot::Request, timeout_ms: u32, tenant: PendingQueryTenant) -> Self {
+    pub fn new(
+        bus: Option<CanBusId>,
+        query: caniot::Request,
+        timeout_ms: u32,
+        tenant: PendingQueryTenant,
+    ) -> Self {
         Self {
             query,
+            bus,
             timeout_ms,
-            sent_at: std::time::Instant::now(),
+            queued_at: Instant::now(),
+            sent_at: None,
             tenant,
         }
     }
@@ -89,19 +244,80 @@ impl PendingQuery {
         }
     }
 
-    /// Check whether the response matches the query
-    pub fn match_response(&self, response: &caniot::Response) -> bool {
-        caniot::is_response_to(&self.query, response).is_response()
+    pub fn is_broadcast(&self) -> bool {
+        matches!(self.tenant, PendingQueryTenant::Broadcast(_))
+    }
+
+    /// Collect a response received on the bus to a broadcast query, the query stays
+    /// pending until its window closes. Returns false if the query is not a broadcast.
+    pub fn collect_response(&mut self, bus: CanBusId, frame: &caniot::Response) -> bool {
+        match &mut self.tenant {
+            PendingQueryTenant::Broadcast(pending_broadcast) => {
+                pending_broadcast.push(bus, frame.clone());
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Check whether the response received on the bus matches the query
+    pub fn match_response(&self, bus: CanBusId, response: &caniot::Response) -> bool {
+        self.bus.map_or(true, |query_bus| query_bus == bus)
+            && caniot::is_response_to(&self.query, response).is_response()
+    }
+
+    /// Check whether a request to be sent on the bus is concurrent with the query
+    pub fn is_concurrent(&self, bus: Option<CanBusId>, request: &caniot::Request) -> bool {
+        let same_bus = match (self.bus, bus) {
+            (Some(query_bus), Some(bus)) => query_bus == bus,
+            _ => true,
+        };
+        same_bus && caniot::are_requests_concurrent(&self.query, request)
+    }
+
+    pub fn is_sent(&self) -> bool {
+        self.sent_at.is_some()
+    }
+
+    /// The frame sent on the bus is the one of the query, the timeout starts
+    pub fn handle_sent(&mut self, bus: CanBusId, frame: &CanDataFrame, now: &Instant) -> bool {
+        if self.is_sent()
+            || self.bus.is_some_and(|query_bus| query_bus != bus)
+            || !self.is_query_frame(frame)
+        {
+            return false;
+        }
+
+        self.sent_at = Some(*now);
+        if let PendingQueryTenant::Discovery(pending_discovery) = &mut self.tenant {
+            pending_discovery.handle_sent(*now);
+        }
+        true
+    }
+
+    /// The frame dropped from the TX queue of the bus is the one of the query, it will
+    /// never be answered. Broadcast queries are sent on the other buses regardless.
+    pub fn is_dropped(&self, bus: CanBusId, frame: &CanDataFrame) -> bool {
+        !self.is_sent() && self.bus == Some(bus) && self.is_query_frame(frame)
+    }
+
+    fn is_query_frame(&self, frame: &CanDataFrame) -> bool {
+        let query_frame: CanDataFrame = (&self.query).into();
+        query_frame.id() == frame.id() && query_frame.data() == frame.data()
     }
 
     /// Get the instant when the query will timeout
     pub fn get_timeout_instant(&self) -> std::time::Instant {
-        self.sent_at + Duration::from_millis(self.timeout_ms as u64)
+        let timeout = Duration::from_millis(self.timeout_ms as u64);
+        match self.sent_at {
+            Some(sent_at) => sent_at + timeout,
+            None => self.queued_at + Duration::from_millis(PENDING_QUERY_MAX_QUEUED_MS) + timeout,
+        }
     }
 
     /// Check whether the query has timed out
     pub fn has_timed_out(&self, now: &Instant) -> bool {
-        now.duration_since(self.sent_at) >= Duration::from_millis(self.timeout_ms as u64)
+        *now >= self.get_timeout_instant()
     }
 }
 
@@ -118,3 +334,16 @@ impl ExpirableTrait<Duration> for PendingQuery {
         }
     }
 }
+
+/// Remove the queries answered by the response received on the bus, broadcast
+/// queries stay pending until their window closes
+pub fn take_answered_queries(
+    pending_queries: &mut Vec<PendingQuery>,
+    bus: CanBusId,
+    response: &caniot::Response,
+) -> Vec<PendingQuery> {
+    let split_index = partition(pending_queries.iter_mut(), |pq| {
+        pq.is_broadcast() || !pq.match_response(bus, response)
+    });
+    pending_queries.split_off(split_index)
+}
@@ -0,0 +1,174 @@
+use std::time::{Duration, Instant};
+
+use socketcan::CanDataFrame;
+use tokio::sync::oneshot;
+
+use crate::caniot::{self, DeviceId, RequestData, ResponseData};
+
+use super::{
+    caniot_devices_controller::CaniotControllerError,
+    pending_broadcast::PendingBroadcast,
+    pending_query::{take_answered_queries, PendingQuery, PendingQueryTenant},
+};
+
+const KEY: u16 = 0x1010;
+
+type QueryReceiver = oneshot::Receiver<Result<caniot::Response, CaniotControllerError>>;
+
+fn query(did: DeviceId) -> (PendingQuery, QueryReceiver) {
+    let (sender, receiver) = oneshot::channel();
+    let request = caniot::Request::new(did, RequestData::AttributeRead { key: KEY });
+    let pq = PendingQuery::new(Some(0), request, 1000, PendingQueryTenant::Query(sender));
+    (pq, receiver)
+}
+
+fn response(did: DeviceId) -> caniot::Response {
+    caniot::Response::new(
+        did,
+        ResponseData::Attribute {
+            key: KEY,
+            value: did.to_u8() as u32,
+        },
+    )
+}
+
+#[test]
+fn test_take_answered_queries_interleaved() {
+    let (dev1, dev2, dev3) = (
+        DeviceId::from_u8(1),
+        DeviceId::from_u8(2),
+        DeviceId::from_u8(3),
+    );
+
+    let (query1, mut receiver1) = query(dev1);
+    let (query2, mut receiver2) = query(dev2);
+    let (query3, mut receiver3) = query(dev3);
+    let (broadcast_sender, _broadcast_receiver) = oneshot::channel();
+    let broadcast = PendingQuery::new(
+        None,
+        caniot::Request::new(DeviceId::BROADCAST, RequestData::AttributeRead { key: KEY }),
+        1000,
+        PendingQueryTenant::Broadcast(PendingBroadcast::new(broadcast_sender)),
+    );
+    let mut pending_queries = vec![broadcast, query1, query3, query2];
+
+    // Response of the last query, the broadcast and the other queries stay pending
+    let answered = take_answered_queries(&mut pending_queries, 0, &response(dev2));
+    assert_eq!(answered.len(), 1);
+    assert_eq!(answered[0].query.device_id, dev2);
+    for pq in answered {
+        assert!(pq.end_with_frame(response(dev2)).is_none());
+    }
+    assert_eq!(receiver2.try_recv().unwrap().unwrap().device_id, dev2);
+    assert_eq!(pending_queries.len(), 3);
+    assert!(receiver1.try_recv().is_err());
+    assert!(receiver3.try_recv().is_err());
+
+    // Response on another bus
+    assert!(take_answered_queries(&mut pending_queries, 1, &response(dev1)).is_empty());
+
+    // Response of the first device query, after the broadcast
+    let answered = take_answered_queries(&mut pending_queries, 0, &response(dev1));
+    assert_eq!(answered.len(), 1);
+    assert_eq!(answered[0].query.device_id, dev1);
+
+    let answered = take_answered_queries(&mut pending_queries, 0, &response(dev3));
+    assert_eq!(answered.len(), 1);
+    assert_eq!(answered[0].query.device_id, dev3);
+
+    assert_eq!(pending_queries.len(), 1);
+    assert!(pending_queries[0].is_broadcast());
+}
+
+#[test]
+fn test_pending_query_timeout_starts_when_sent() {
+    let did = DeviceId::from_u8(1);
+    let (mut pq, _receiver) = query(did);
+    let other_frame: CanDataFrame = (&caniot::Request::new(
+        DeviceId::from_u8(2),
+        RequestData::AttributeRead { key: KEY },
+    ))
+        .into();
+    let frame: CanDataFrame = (&pq.query).into();
+
+    // Still queued, the timeout is not running yet
+    let sent_at = Instant::now() + Duration::from_secs(2);
+    assert!(!pq.has_timed_out(&(Instant::now() + Duration::from_millis(1000))));
+
+    // Frame of another query or sent on another bus
+    assert!(!pq.handle_sent(0, &other_frame, &sent_at));
+    assert!(!pq.handle_sent(1, &frame, &sent_at));
+    assert!(!pq.is_sent());
+
+    assert!(pq.handle_sent(0, &frame, &sent_at));
+    assert!(pq.is_sent());
+    assert_eq!(
+        pq.get_timeout_instant(),
+        sent_at + Duration::from_millis(1000)
+    );
+    assert!(!pq.has_timed_out(&(sent_at + Duration::from_millis(999))));
+    assert!(pq.has_timed_out(&(sent_at + Duration::from_millis(1000))));
+
+    // The timeout starts once
+    assert!(!pq.handle_sent(0, &frame, &(sent_at + Duration::from_secs(1))));
+}
+
+#[test]
+fn test_broadcast_responses_per_bus() {
+    let (dev1, dev2) = (DeviceId::from_u8(1), DeviceId::from_u8(2));
+    let (sender, mut receiver) = oneshot::channel();
+    let mut pq = PendingQuery::new(
+        None,
+        caniot::Request::new(DeviceId::BROADCAST, RequestData::AttributeRead { key: KEY }),
+        1000,
+        PendingQueryTenant::Broadcast(PendingBroadcast::new(sender)),
+    );
+
+    // Same device id on both buses, the first response of each device is kept
+    assert!(pq.collect_response(0, &response(dev1)));
+    assert!(pq.collect_response(1, &response(dev1)));
+    assert!(pq.collect_response(0, &response(dev1)));
+    assert!(pq.collect_response(1, &response(dev2)));
+    assert!(pq.end_with_error(CaniotControllerError::Timeout).is_none());
+
+    let responses: Vec<_> = receiver
+        .try_recv()
+        .unwrap()
+        .unwrap()
+        .into_iter()
+        .map(|(bus, response)| (bus, response.device_id))
+        .collect();
+    assert_eq!(responses, vec![(0, dev1), (1, dev1), (1, dev2)]);
+
+    // Not a broadcast
+    let (mut pq, _receiver) = query(dev1);
+    assert!(!pq.collect_response(0, &response(dev1)));
+}
+
+#[test]
+fn test_pending_query_dropped() {
+    let did = DeviceId::from_u8(1);
+    let (pq, _receiver) = query(did);
+    let frame: CanDataFrame = (&pq.query).into();
+
+    // Dropped from the TX queue of another bus
+    assert!(!pq.is_dropped(1, &frame));
+    assert!(pq.is_dropped(0, &frame));
+
+    // Broadcast queries are sent on the other buses
+    let (sender, _receiver) = oneshot::channel();
+    let request =
+        caniot::Request::new(DeviceId::BROADCAST, RequestData::AttributeRead { key: KEY });
+    let broadcast = PendingQuery::new(
+        None,
+        request.clone(),
+        1000,
+        PendingQueryTenant::Broadcast(PendingBroadcast::new(sender)),
+    );
+    assert!(!broadcast.is_dropped(0, &(&request).into()));
+
+    // Already sent, an identical frame dropped later is not the one of the query
+    let (mut pq, _receiver) = query(did);
+    assert!(pq.handle_sent(0, &frame, &Instant::now()));
+    assert!(!pq.is_dropped(0, &frame));
+}
@@ -0,0 +1,77 @@
+use std::fmt::Debug;
+
+use tokio::sync::oneshot;
+
+use crate::{
+    bus::CanBusId,
+    caniot::{self, DeviceId},
+};
+
+use super::caniot_devices_controller::CaniotControllerError;
+
+/// Read of a multi-part (string) attribute, parts are read one after the other
+pub struct PendingStringAttribute {
+    pub did: DeviceId,
+    pub bus: CanBusId,
+    pub attribute: caniot::Attribute,
+    pub timeout_ms: Option<u32>,
+
+    // Part to be read next
+    next_part: u8,
+
+    // Bytes of the parts received so far
+    buffer: Vec<u8>,
+
+    send_to: oneshot::Sender<Result<String, CaniotControllerError>>,
+}
+
+impl PendingStringAttribute {
+    pub fn new(
+        did: DeviceId,
+        bus: CanBusId,
+        attribute: caniot::Attribute,
+        timeout_ms: Option<u32>,
+        send_to: oneshot::Sender<Result<String, CaniotControllerError>>,
+    ) -> Self {
+        Self {
+            did,
+            bus,
+            attribute,
+            timeout_ms,
+            next_part: 0,
+            buffer: Vec::new(),
+            send_to,
+        }
+    }
+
+    /// Request reading the next part of the attribute
+    pub fn next_request(&self) -> caniot::Request {
+        caniot::build_attribute_read_request(self.did, self.attribute.part_key(self.next_part))
+    }
+
+    /// Append the value of the part received, returns true if the string is complete
+    pub fn push_part(&mut self, value: u32) -> bool {
+        self.buffer.extend_from_slice(&value.to_le_bytes());
+        self.next_part += 1;
+        self.attribute.is_string_complete(&self.buffer)
+    }
+
+    pub fn complete(self) {
+        let value = self.attribute.decode_string(&self.buffer);
+        self.send(Ok(value));
+    }
+
+    pub fn send(self, result: Result<String, CaniotControllerError>) {
+        let _ = self.send_to.send(result);
+    }
+}
+
+impl Debug for PendingStringAttribute {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PendingStringAttribute")
+            .field("did", &self.did)
+            .field("attribute", &self.attribute)
+            .field("next_part", &self.next_part)
+            .finish()
+    }
+}
@@ -0,0 +1,139 @@
+use std::fmt::Debug;
+
+use chrono::Utc;
+
+use crate::{
+    bus::CanBusId,
+    caniot::{self, DeviceId, ResponseData},
+    controller::{TimeSyncMode, TimeSyncStatus},
+};
+
+use super::caniot_devices_controller::CaniotControllerError;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Step {
+    // Read the device time to measure its drift
+    Check,
+    // Write the controller time
+    Write,
+    // Read back the device time to measure the remaining offset
+    Verify,
+    Done,
+}
+
+/// Synchronization of the device clock with the controller UTC time.
+///
+/// In check mode, the device time is read first and only rewritten if it
+/// drifted by more than the allowed offset.
+pub struct PendingTimeSync {
+    pub did: DeviceId,
+    pub bus: CanBusId,
+
+    // Maximum offset allowed before the device clock is resynchronized (s)
+    max_drift: u32,
+    step: Step,
+
+    status: TimeSyncStatus,
+}
+
+impl PendingTimeSync {
+    pub fn new(
+        did: DeviceId,
+        bus: CanBusId,
+        mode: TimeSyncMode,
+        max_drift: u32,
+        status: TimeSyncStatus,
+    ) -> Self {
+        Self {
+            did,
+            bus,
+            max_drift,
+            step: match mode {
+                TimeSyncMode::Check => Step::Check,
+                TimeSyncMode::Sync => Step::Write,
+            },
+            status: TimeSyncStatus {
+                error: None,
+                ..status
+            },
+        }
+    }
+
+    /// Next request to send, None if the synchronization is complete
+    pub fn next_request(&self) -> Option<caniot::Request> {
+        let key = caniot::Attribute::SystemTime.key();
+        match self.step {
+            Step::Check | Step::Verify => Some(caniot::build_attribute_read_request(self.did, key)),
+            Step::Write => Some(caniot::build_attribute_write_request(
+                self.did,
+                key,
+                Utc::now().timestamp() as u32,
+            )),
+            Step::Done => None,
+        }
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.step == Step::Done
+    }
+
+    fn fail(&mut self, reason: String) {
+        self.status.error = Some(reason);
+        self.step = Step::Done;
+    }
+
+    // Measure the device clock offset from the time it reported
+    fn measure_offset(&mut self, device_time: u32) -> i64 {
+        let now = Utc::now();
+        let offset = device_time as i64 - now.timestamp();
+        self.status.checked_at = Some(now);
+        self.status.offset = Some(offset);
+        offset
+    }
+
+    pub fn handle_response(&mut self, data: &ResponseData) {
+        match (self.step, data) {
+            (Step::Check, ResponseData::Attribute { value, .. }) => {
+                if self.measure_offset(*value).unsigned_abs() > self.max_drift as u64 {
+                    self.status.drift_resyncs += 1;
+                    self.step = Step::Write;
+                } else {
+                    self.step = Step::Done;
+                }
+            }
+            (Step::Write, ResponseData::Attribute { .. }) => {
+                self.status.synced_at = Some(Utc::now());
+                self.step = Step::Verify;
+            }
+            (Step::Verify, ResponseData::Attribute { value, .. }) => {
+                let offset = self.measure_offset(*value);
+                if offset.unsigned_abs() > self.max_drift as u64 {
+                    self.fail(format!("offset {} s after write", offset));
+                } else {
+                    self.step = Step::Done;
+                }
+            }
+            (_, ResponseData::Error { error, .. }) => {
+                self.fail(format!("error {:?}", error));
+            }
+            _ => {}
+        }
+    }
+
+    pub fn handle_error(&mut self, error: CaniotControllerError) {
+        self.fail(error.to_string());
+    }
+
+    pub fn into_status(self) -> TimeSyncStatus {
+        self.status
+    }
+}
+
+impl Debug for PendingTimeSync {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PendingTimeSync")
+            .field("did", &self.did)
+            .field("step", &self.step)
+            .finish()
+    }
+}
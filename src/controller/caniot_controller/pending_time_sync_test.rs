@@ -0,0 +1,127 @@
+use chrono::Utc;
+
+use crate::{
+    caniot::{self, DeviceId, ErrorCode, ErrorSource, RequestData, ResponseData},
+    controller::{TimeSyncMode, TimeSyncStatus},
+};
+
+use super::{caniot_devices_controller::CaniotControllerError, pending_time_sync::PendingTimeSync};
+
+const MAX_DRIFT: u32 = 5;
+
+fn time_sync(mode: TimeSyncMode) -> PendingTimeSync {
+    PendingTimeSync::new(
+        DeviceId::from_u8(1),
+        0,
+        mode,
+        MAX_DRIFT,
+        TimeSyncStatus {
+            error: Some("previous error".to_string()),
+            ..Default::default()
+        },
+    )
+}
+
+fn device_time(offset: i64) -> ResponseData {
+    ResponseData::Attribute {
+        key: caniot::Attribute::SystemTime.key(),
+        value: (Utc::now().timestamp() + offset) as u32,
+    }
+}
+
+fn is_read(request: Option<caniot::Request>) -> bool {
+    matches!(
+        request.map(|request| request.data),
+        Some(RequestData::AttributeRead { key }) if key == caniot::Attribute::SystemTime.key()
+    )
+}
+
+fn is_write(request: Option<caniot::Request>) -> bool {
+    matches!(
+        request.map(|request| request.data),
+        Some(RequestData::AttributeWrite { key, .. }) if key == caniot::Attribute::SystemTime.key()
+    )
+}
+
+#[test]
+fn test_time_sync_check_in_sync() {
+    let mut pending = time_sync(TimeSyncMode::Check);
+    assert!(is_read(pending.next_request()));
+
+    // Within the allowed drift, the device time is not rewritten
+    pending.handle_response(&device_time(2));
+    assert!(pending.is_complete());
+    assert!(pending.next_request().is_none());
+
+    let status = pending.into_status();
+    assert!(status.error.is_none());
+    assert!(status.checked_at.is_some());
+    assert!(status.synced_at.is_none());
+    assert_eq!(status.drift_resyncs, 0);
+    assert!(status
+        .offset
+        .is_some_and(|offset| (1..=3).contains(&offset)));
+}
+
+#[test]
+fn test_time_sync_check_drift_resync() {
+    let mut pending = time_sync(TimeSyncMode::Check);
+
+    // Drifted, Check -> Write -> Verify
+    pending.handle_response(&device_time(-60));
+    assert!(is_write(pending.next_request()));
+    pending.handle_response(&device_time(0));
+    assert!(is_read(pending.next_request()));
+    pending.handle_response(&device_time(0));
+    assert!(pending.is_complete());
+
+    let status = pending.into_status();
+    assert!(status.error.is_none());
+    assert!(status.synced_at.is_some());
+    assert_eq!(status.drift_resyncs, 1);
+    assert!(status.offset.is_some_and(|offset| offset.abs() <= 1));
+}
+
+#[test]
+fn test_time_sync_failure_after_write() {
+    let mut pending = time_sync(TimeSyncMode::Sync);
+    assert!(is_write(pending.next_request()));
+    pending.handle_response(&device_time(0));
+
+    // The device did not apply the time written
+    pending.handle_response(&device_time(3600));
+    assert!(pending.is_complete());
+
+    let status = pending.into_status();
+    assert!(status.synced_at.is_some());
+    assert_eq!(status.drift_resyncs, 0);
+    assert!(status
+        .error
+        .is_some_and(|error| error.contains("after write")));
+}
+
+#[test]
+fn test_time_sync_errors() {
+    // Error response from the device
+    let mut pending = time_sync(TimeSyncMode::Check);
+    pending.handle_response(&ResponseData::Error {
+        source: ErrorSource::Attribute(Some(caniot::Attribute::SystemTime.key())),
+        error: Some(ErrorCode::Ekey),
+    });
+    assert!(pending.is_complete());
+    let status = pending.into_status();
+    assert!(status.error.is_some_and(|error| error.contains("Ekey")));
+    assert!(status.checked_at.is_none());
+
+    // No response
+    let mut pending = time_sync(TimeSyncMode::Sync);
+    pending.handle_error(CaniotControllerError::Timeout);
+    assert!(pending.is_complete());
+    assert!(pending.next_request().is_none());
+    let status = pending.into_status();
+    assert_eq!(
+        status.error,
+        Some(CaniotControllerError::Timeout.to_string())
+    );
+    assert!(status.synced_at.is_none());
+}
@@ -0,0 +1,60 @@
+use std::time::{Duration, Instant};
+
+use crate::{
+    bus::{CanBusId, TxPriority},
+    caniot::{DeviceId, Request},
+    controller::Verdict,
+    utils::expirable::ExpirableTrait,
+};
+
+/// Request of a device controller verdict waiting for its delay to elapse
+#[derive(Debug)]
+pub struct ScheduledRequest {
+    // Bus of the device which returned the verdict, None for a request to
+    // another device, routed when sent
+    pub bus: Option<CanBusId>,
+    pub request: Request,
+    pub priority: TxPriority,
+    pub send_at: Instant,
+}
+
+impl ScheduledRequest {
+    /// Requests of the verdict returned by device did, the delay of each
+    /// request adds up to the delays of the previous ones
+    pub fn from_verdict(
+        bus: CanBusId,
+        did: DeviceId,
+        verdict: Verdict,
+        priority: TxPriority,
+        now: &Instant,
+    ) -> Vec<Self> {
+        let mut send_at = *now;
+        verdict
+            .into_requests()
+            .into_iter()
+            .map(|verdict_request| {
+                send_at += verdict_request.delay.unwrap_or_default();
+                let request_did = verdict_request.did.unwrap_or(did);
+                Self {
+                    bus: (request_did == did).then_some(bus),
+                    request: Request::new(request_did, verdict_request.data),
+                    priority,
+                    send_at,
+                }
+            })
+            .collect()
+    }
+
+    pub fn is_due(&self, now: &Instant) -> bool {
+        self.send_at <= *now
+    }
+}
+
+impl ExpirableTrait<Duration> for ScheduledRequest {
+    const ZERO: Duration = Duration::ZERO;
+    type Instant = Instant;
+
+    fn ttl(&self, now: &Instant) -> Option<Duration> {
+        Some(self.send_at.saturating_duration_since(*now))
+    }
+}
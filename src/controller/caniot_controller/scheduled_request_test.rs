@@ -0,0 +1,67 @@
+use std::time::{Duration, Instant};
+
+use crate::{
+    bus::{CanBusId, TxPriority},
+    caniot::{DeviceId, Endpoint, RequestData},
+    controller::{Verdict, VerdictRequest},
+    utils::expirable::ExpirableTrait,
+};
+
+use super::scheduled_request::ScheduledRequest;
+
+fn telemetry(endpoint: Endpoint) -> RequestData {
+    RequestData::Telemetry { endpoint }
+}
+
+#[test]
+fn test_scheduled_requests_from_verdict() {
+    let now = Instant::now();
+    let did = DeviceId::from_u8(1);
+    let other = DeviceId::from_u8(2);
+
+    assert!(
+        ScheduledRequest::from_verdict(0, did, Verdict::None, TxPriority::Low, &now).is_empty()
+    );
+
+    let verdict = Verdict::Requests(vec![
+        VerdictRequest::new(telemetry(Endpoint::Application1)),
+        VerdictRequest::new(telemetry(Endpoint::BoardControl))
+            .with_delay(Duration::from_millis(500)),
+        VerdictRequest::to_device(other, telemetry(Endpoint::ApplicationDefault)),
+        VerdictRequest::new(telemetry(Endpoint::Application2)).with_delay(Duration::from_secs(1)),
+    ]);
+    let scheduled = ScheduledRequest::from_verdict(1, did, verdict, TxPriority::Normal, &now);
+
+    // Delays add up, requests without delay are sent right after the previous one
+    let offsets: Vec<Duration> = scheduled
+        .iter()
+        .map(|scheduled| scheduled.send_at - now)
+        .collect();
+    assert_eq!(
+        offsets,
+        vec![
+            Duration::ZERO,
+            Duration::from_millis(500),
+            Duration::from_millis(500),
+            Duration::from_millis(1500),
+        ]
+    );
+
+    let dids: Vec<DeviceId> = scheduled
+        .iter()
+        .map(|scheduled| scheduled.request.device_id)
+        .collect();
+    assert_eq!(dids, vec![did, did, other, did]);
+
+    // Requests to another device are routed when sent
+    let buses: Vec<Option<CanBusId>> = scheduled.iter().map(|scheduled| scheduled.bus).collect();
+    assert_eq!(buses, vec![Some(1), Some(1), None, Some(1)]);
+
+    assert!(scheduled[0].is_due(&now));
+    assert!(!scheduled[1].is_due(&now));
+    assert_eq!(scheduled.ttl(&now), Some(Duration::ZERO));
+    assert_eq!(
+        scheduled[1..].iter().ttl(&now),
+        Some(Duration::from_millis(500))
+    );
+}
@@ -1,6 +1,8 @@
 use crate::caniot::{self, Response};
 
-use super::{ActionResultTrait, ActionTrait, ActionWrapperTrait};
+use super::{
+    attributes_report::DeviceAttributesReport, ActionResultTrait, ActionTrait, ActionWrapperTrait,
+};
 
 #[derive(Debug)]
 pub enum DeviceAction {
@@ -10,8 +12,17 @@ pub enum DeviceAction {
     ResetSettings,
     // Inhibit device from performing control actions (e.g. siren, lights
     InhibitControl(caniot::TSP),
+    // Enable or disable the device watchdog
+    WatchdogControl(bool),
     // Ping (request telemetry)
     Ping(caniot::Endpoint),
+    // Read all attributes available for the device class
+    ReadAllAttributes,
+    // Apply a command to a single board output (class 0 or class 1)
+    SetOutput {
+        output: caniot::BoardOutput,
+        xps: caniot::Xps,
+    },
     // Action to pass to the underlying device
     Inner(Box<dyn ActionWrapperTrait>),
 }
@@ -36,8 +47,12 @@ pub enum DeviceActionResult {
     ResetSettingsSent,
     // Inhibit control command has been sent to the device and telemetry has been received
     InhibitControlSent,
+    // Watchdog control command has been sent to the device and telemetry has been received
+    WatchdogControlSent,
     // Pong response from the device
     Pong(Response),
+    // Values of all attributes of the device
+    AttributesReport(DeviceAttributesReport),
     // Inner action result
     Inner(Box<dyn ActionResultTrait>),
 }
@@ -0,0 +1,59 @@
+use chrono::{DateTime, Utc};
+
+use crate::{caniot, controller::DeviceAlert};
+
+/// Outcome of the reconciliation of the device attributes with the configuration
+#[derive(Debug, Clone)]
+pub struct AttributesConfigStatus {
+    pub checked_at: DateTime<Utc>,
+
+    // Attributes found different from the configuration (rewritten)
+    pub drifted: Vec<caniot::Attribute>,
+
+    // Attributes which could not be read, written or verified, with the reason
+    pub failed: Vec<(caniot::Attribute, String)>,
+
+    // Attribute names of the configuration which do not exist (e.g. misspelled)
+    pub unknown: Vec<String>,
+}
+
+impl AttributesConfigStatus {
+    pub fn new() -> Self {
+        Self {
+            checked_at: Utc::now(),
+            drifted: Vec::new(),
+            failed: Vec::new(),
+            unknown: Vec::new(),
+        }
+    }
+
+    pub fn get_alert(&self) -> Option<DeviceAlert> {
+        if !self.failed.is_empty() || !self.unknown.is_empty() {
+            let description = self
+                .failed
+                .iter()
+                .map(|(attr, reason)| format!("{:?}: {}", attr, reason))
+                .chain(
+                    self.unknown
+                        .iter()
+                        .map(|name| format!("{}: attribut inconnu", name)),
+                )
+                .collect::<Vec<_>>()
+                .join(", ");
+            Some(
+                DeviceAlert::new_error("Configuration non appliquée")
+                    .with_description(&description),
+            )
+        } else if !self.drifted.is_empty() {
+            let description = self
+                .drifted
+                .iter()
+                .map(|attr| format!("{:?}", attr))
+                .collect::<Vec<_>>()
+                .join(", ");
+            Some(DeviceAlert::new_warning("Configuration corrigée").with_description(&description))
+        } else {
+            None
+        }
+    }
+}
@@ -0,0 +1,53 @@
+use chrono::{DateTime, Utc};
+
+use crate::caniot::{self, AttributeValue, DeviceId, ErrorCode};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum AttributeReadError {
+    // Error response from the device (e.g. Ekey, Eclsattr)
+    Device(Option<ErrorCode>),
+    // No response from the device
+    Timeout,
+    // Value returned cannot be decoded as the attribute type
+    Decode,
+}
+
+#[derive(Debug, Clone)]
+pub struct AttributeReportEntry {
+    pub attribute: caniot::Attribute,
+    pub value: Result<AttributeValue, AttributeReadError>,
+}
+
+/// Values of all attributes of a device, read one after the other
+#[derive(Debug, Clone)]
+pub struct DeviceAttributesReport {
+    pub did: DeviceId,
+    // Time when the last attribute was read
+    pub timestamp: DateTime<Utc>,
+    pub entries: Vec<AttributeReportEntry>,
+}
+
+impl DeviceAttributesReport {
+    pub fn new(did: DeviceId) -> Self {
+        Self {
+            did,
+            timestamp: Utc::now(),
+            entries: Vec::new(),
+        }
+    }
+
+    pub fn push(
+        &mut self,
+        attribute: caniot::Attribute,
+        value: Result<AttributeValue, AttributeReadError>,
+    ) {
+        self.entries.push(AttributeReportEntry { attribute, value });
+        self.timestamp = Utc::now();
+    }
+
+    pub fn get(&self, attribute: caniot::Attribute) -> Option<&AttributeReportEntry> {
+        self.entries
+            .iter()
+            .find(|entry| entry.attribute == attribute)
+    }
+}
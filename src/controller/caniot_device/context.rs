@@ -7,7 +7,7 @@ use crate::{
     database::{SettingsStore, Storage},
 };
 
-use super::{DeviceError, JobTrait};
+use super::{DeviceError, JobTrait, TimeSyncMode};
 
 pub struct ProcessContext<'f> {
     // Received frame timestamp
@@ -22,6 +22,12 @@ pub struct ProcessContext<'f> {
     // Request jobs update
     pub request_jobs_update: bool,
 
+    // Request the reconciliation of the device attributes with the configuration
+    pub request_attributes_reconciliation: bool,
+
+    // Request the synchronization of the device clock
+    pub request_time_sync: Option<TimeSyncMode>,
+
     // Settings store
     pub storage: Arc<Storage>,
 
@@ -46,6 +52,8 @@ impl<'f> ProcessContext<'f> {
             frame_received_at: received_at,
             new_jobs: vec![],
             request_jobs_update: false,
+            request_attributes_reconciliation: false,
+            request_time_sync: None,
             storage,
             update_attributes: HashMap::new(),
             storage_update_future: None,
@@ -63,6 +71,15 @@ impl<'f> ProcessContext<'f> {
         self.request_jobs_update = true;
     }
 
+    pub fn request_attributes_reconciliation(&mut self) {
+        self.request_attributes_reconciliation = true;
+    }
+
+    // A sync supersedes a check
+    pub fn request_time_sync(&mut self, mode: TimeSyncMode) {
+        self.request_time_sync = self.request_time_sync.max(Some(mode));
+    }
+
     pub fn get_settings_store<'s>(&'s self) -> SettingsStore<'s> {
         self.storage.get_settings_store()
     }
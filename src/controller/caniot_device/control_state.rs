@@ -0,0 +1,109 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+
+use crate::{
+    caniot::{SysCtrl, TS, TSP},
+    controller::DeviceAlert,
+};
+
+// Default estimated duration of an inhibit pulse, the device does not report it,
+// configurable with caniot.devices.inhibit_pulse_duration
+pub const INHIBIT_PULSE_DEFAULT_DURATION_S: u32 = 60;
+
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize)]
+pub enum InhibitState {
+    // Never set by the controller since the device (or the controller) started
+    #[default]
+    Unknown,
+    Released,
+    Inhibited,
+    // Inhibited by a pulse, released by the device when it ends,
+    // the end is estimated from the configured pulse duration
+    InhibitedUntil(DateTime<Utc>),
+}
+
+/// Watchdog and inhibit state of a device, as last commanded by the controller
+#[derive(Debug, Clone, Serialize)]
+pub struct DeviceControlState {
+    // None if never set by the controller
+    pub watchdog_enabled: Option<bool>,
+    pub inhibit: InhibitState,
+    pub updated_at: Option<DateTime<Utc>>,
+
+    // Estimated duration of an inhibit pulse
+    #[serde(skip)]
+    inhibit_pulse_duration: Duration,
+}
+
+impl Default for DeviceControlState {
+    fn default() -> Self {
+        Self::new(None)
+    }
+}
+
+impl DeviceControlState {
+    pub fn new(inhibit_pulse_duration_s: Option<u32>) -> Self {
+        let duration_s = inhibit_pulse_duration_s.unwrap_or(INHIBIT_PULSE_DEFAULT_DURATION_S);
+        Self {
+            watchdog_enabled: None,
+            inhibit: InhibitState::default(),
+            updated_at: None,
+            inhibit_pulse_duration: Duration::seconds(duration_s as i64),
+        }
+    }
+
+    /// Apply a system control command acknowledged by the device
+    pub fn apply(&mut self, sys_ctrl: &SysCtrl, at: DateTime<Utc>) {
+        if sys_ctrl.hardware_reset {
+            // The device restarts with its default state
+            self.watchdog_enabled = None;
+            self.inhibit = InhibitState::Unknown;
+            self.updated_at = Some(at);
+            return;
+        }
+
+        self.watchdog_enabled = match sys_ctrl.watchdog_enable {
+            TS::None => self.watchdog_enabled,
+            TS::Set => Some(true),
+            TS::Reset => Some(false),
+            TS::Toggle => self.watchdog_enabled.map(|enabled| !enabled),
+        };
+
+        self.inhibit = match sys_ctrl.inhibit {
+            TSP::None => self.inhibit,
+            TSP::Set => InhibitState::Inhibited,
+            TSP::Reset => InhibitState::Released,
+            TSP::Pulse => InhibitState::InhibitedUntil(at + self.inhibit_pulse_duration),
+        };
+
+        self.updated_at = Some(at);
+    }
+
+    pub fn is_inhibited(&self, now: &DateTime<Utc>) -> bool {
+        match self.inhibit {
+            InhibitState::Inhibited => true,
+            InhibitState::InhibitedUntil(until) => until > *now,
+            InhibitState::Unknown | InhibitState::Released => false,
+        }
+    }
+
+    /// Estimated remaining time of an inhibit pulse, None if not inhibited by a pulse
+    pub fn inhibit_remaining(&self, now: &DateTime<Utc>) -> Option<Duration> {
+        match self.inhibit {
+            InhibitState::InhibitedUntil(until) if until > *now => Some(until - *now),
+            _ => None,
+        }
+    }
+
+    pub fn get_alert(&self, now: &DateTime<Utc>) -> Option<DeviceAlert> {
+        if !self.is_inhibited(now) {
+            None
+        } else if let Some(remaining) = self.inhibit_remaining(now) {
+            let seconds = (remaining.num_milliseconds() + 999) / 1000;
+            let description = format!("Fin estimée dans {} s", seconds);
+            Some(DeviceAlert::new_inhibitted().with_description(&description))
+        } else {
+            Some(DeviceAlert::new_inhibitted())
+        }
+    }
+}
@@ -0,0 +1,97 @@
+use chrono::{Duration, Utc};
+
+use crate::{
+    caniot::{SysCtrl, TS, TSP},
+    controller::DeviceAlertType,
+};
+
+use super::control_state::{DeviceControlState, InhibitState};
+
+#[test]
+fn test_control_state_apply() {
+    let at = Utc::now();
+    let mut state = DeviceControlState::default();
+    assert_eq!(state.watchdog_enabled, None);
+    assert_eq!(state.inhibit, InhibitState::Unknown);
+
+    // Toggling an unknown state keeps it unknown
+    state.apply(&SysCtrl::watchdog_control(TS::Toggle), at);
+    assert_eq!(state.watchdog_enabled, None);
+    assert_eq!(state.updated_at, Some(at));
+
+    state.apply(&SysCtrl::watchdog_control(TS::Set), at);
+    assert_eq!(state.watchdog_enabled, Some(true));
+    state.apply(&SysCtrl::watchdog_control(TS::Toggle), at);
+    assert_eq!(state.watchdog_enabled, Some(false));
+
+    // Inhibit commands leave the watchdog state untouched
+    state.apply(&SysCtrl::inhibit_control(TSP::Set), at);
+    assert_eq!(state.inhibit, InhibitState::Inhibited);
+    assert_eq!(state.watchdog_enabled, Some(false));
+    state.apply(&SysCtrl::inhibit_control(TSP::None), at);
+    assert_eq!(state.inhibit, InhibitState::Inhibited);
+    state.apply(&SysCtrl::inhibit_control(TSP::Reset), at);
+    assert_eq!(state.inhibit, InhibitState::Released);
+
+    // The device restarts with its default state
+    let reset_at = at + Duration::seconds(1);
+    state.apply(&SysCtrl::HARDWARE_RESET, reset_at);
+    assert_eq!(state.watchdog_enabled, None);
+    assert_eq!(state.inhibit, InhibitState::Unknown);
+    assert_eq!(state.updated_at, Some(reset_at));
+}
+
+#[test]
+fn test_control_state_inhibit_pulse() {
+    let at = Utc::now();
+    let mut state = DeviceControlState::new(Some(10));
+
+    state.apply(&SysCtrl::inhibit_control(TSP::Pulse), at);
+    assert_eq!(
+        state.inhibit,
+        InhibitState::InhibitedUntil(at + Duration::seconds(10))
+    );
+
+    let now = at + Duration::milliseconds(2500);
+    assert!(state.is_inhibited(&now));
+    assert_eq!(
+        state.inhibit_remaining(&now),
+        Some(Duration::milliseconds(7500))
+    );
+    let alert = state.get_alert(&now).unwrap();
+    assert_eq!(alert.alert_type, DeviceAlertType::Inhibitted);
+    assert_eq!(alert.description.as_deref(), Some("Fin estimée dans 8 s"));
+
+    // End of the pulse
+    let now = at + Duration::seconds(10);
+    assert!(!state.is_inhibited(&now));
+    assert_eq!(state.inhibit_remaining(&now), None);
+    assert!(state.get_alert(&now).is_none());
+
+    // The pulse duration is kept across a device reset
+    state.apply(&SysCtrl::HARDWARE_RESET, at);
+    state.apply(&SysCtrl::inhibit_control(TSP::Pulse), at);
+    assert_eq!(
+        state.inhibit,
+        InhibitState::InhibitedUntil(at + Duration::seconds(10))
+    );
+}
+
+#[test]
+fn test_control_state_alert() {
+    let now = Utc::now();
+    let mut state = DeviceControlState::default();
+    assert!(!state.is_inhibited(&now));
+    assert!(state.get_alert(&now).is_none());
+
+    state.apply(&SysCtrl::inhibit_control(TSP::Set), now);
+    assert!(state.is_inhibited(&now));
+    assert_eq!(state.inhibit_remaining(&now), None);
+    let alert = state.get_alert(&now).unwrap();
+    assert_eq!(alert.alert_type, DeviceAlertType::Inhibitted);
+    assert!(alert.description.is_none());
+
+    // Default pulse duration
+    state.apply(&SysCtrl::inhibit_control(TSP::Pulse), now);
+    assert_eq!(state.inhibit_remaining(&now), Some(Duration::seconds(60)));
+}
@@ -3,27 +3,33 @@ use chrono::{DateTime, Duration, Utc};
 use log::warn;
 
 use crate::{
+    bus::CanBusId,
     caniot::{
         self, classes, BoardClassTelemetry, DeviceId, Endpoint, Response, ResponseData, SysCtrl,
-        TSP,
+        TS, TSP,
     },
-    controller::{ActionTrait, DeviceAlert, JobTrait},
+    controller::{cmp_severity, ActionTrait, DeviceAlert, JobTrait},
     utils::expirable::ExpirableTrait,
 };
 
 use super::{
     actions::{DeviceAction, DeviceActionResult},
+    attributes_config::AttributesConfigStatus,
     context::ProcessContext,
     downcast_job_as,
     traits::ActionWrapperTrait,
     verdict::{ActionVerdict, Verdict},
-    DeviceControllerWrapperTrait, DeviceError, DeviceJobWrapper, DeviceJobsContext, DeviceMeasures,
-    DeviceMeasuresResetJob, DeviceStats, UpdateJobVerdict,
+    DeviceControlState, DeviceControllerWrapperTrait, DeviceError, DeviceJobWrapper,
+    DeviceJobsContext, DeviceMeasures, DeviceMeasuresResetJob, DeviceStats, DeviceTimeSyncJob,
+    TimeSyncMode, TimeSyncStatus, UpdateJobVerdict,
 };
 #[derive(Debug)]
 pub struct Device {
     pub did: DeviceId,
 
+    // Bus the device was seen on, requests to the device are sent on it
+    pub bus: CanBusId,
+
     // Stats
     pub last_seen: Option<DateTime<Utc>>,
     pub stats: DeviceStats,
@@ -36,20 +42,37 @@ pub struct Device {
 
     // Last class telemetry values
     pub measures: DeviceMeasures,
+
+    // Result of the last attributes reconciliation with the configuration
+    pub attributes_config: Option<AttributesConfigStatus>,
+
+    // Synchronization state of the device clock
+    pub time_sync: TimeSyncStatus,
+
+    // Watchdog and inhibit state
+    pub control: DeviceControlState,
 }
 
 impl Device {
-    pub fn new(did: DeviceId, controller: Option<Box<dyn DeviceControllerWrapperTrait>>) -> Self {
+    pub fn new(
+        did: DeviceId,
+        bus: CanBusId,
+        controller: Option<Box<dyn DeviceControllerWrapperTrait>>,
+    ) -> Self {
         // TODO remove/move
         let now = Utc::now();
 
         Self {
             did,
+            bus,
             last_seen: None,
             stats: DeviceStats::default(),
             controller,
             measures: DeviceMeasures::default(),
             jobs: DeviceJobsContext::new(now),
+            attributes_config: None,
+            time_sync: TimeSyncStatus::default(),
+            control: DeviceControlState::default(),
         }
     }
 
@@ -99,6 +122,31 @@ impl Device {
         Ok(ActionVerdict::ActionPendingOn(req))
     }
 
+    fn handle_action_watchdog_control(
+        &mut self,
+        enable: bool,
+    ) -> Result<ActionVerdict<DeviceAction>, DeviceError> {
+        let req = Self::watchdog_sys_ctrl(enable).into_board_request();
+        Ok(ActionVerdict::ActionPendingOn(req))
+    }
+
+    fn watchdog_sys_ctrl(enable: bool) -> SysCtrl {
+        SysCtrl::watchdog_control(if enable { TS::Set } else { TS::Reset })
+    }
+
+    fn handle_action_set_output(
+        &mut self,
+        output: &caniot::BoardOutput,
+        xps: caniot::Xps,
+    ) -> Result<ActionVerdict<DeviceAction>, DeviceError> {
+        if output.class_id() != self.did.class {
+            return Err(DeviceError::OutputClassMismatch(self.did.class));
+        }
+
+        let req = output.into_command_request(xps);
+        Ok(ActionVerdict::ActionPendingOn(req))
+    }
+
     fn handle_action_ping(
         &mut self,
         endpoint: Endpoint,
@@ -120,7 +168,11 @@ impl Device {
             DeviceAction::Reset => self.handle_action_reset(),
             DeviceAction::ResetSettings => self.handle_action_reset_settings(),
             DeviceAction::InhibitControl(inhibit) => self.handle_action_inhibit_control(*inhibit),
+            DeviceAction::WatchdogControl(enable) => self.handle_action_watchdog_control(*enable),
             DeviceAction::Ping(endpoint) => self.handle_action_ping(*endpoint),
+            // Spans multiple requests, sequenced by the devices controller
+            DeviceAction::ReadAllAttributes => Err(DeviceError::UnsupportedAction),
+            DeviceAction::SetOutput { output, xps } => self.handle_action_set_output(output, *xps),
             DeviceAction::Inner(inner_action) => {
                 if let Some(inner_device) = self.controller.as_mut() {
                     let inner_verdict = inner_device.wrapper_handle_action(inner_action, ctx)?;
@@ -132,16 +184,32 @@ impl Device {
         }
     }
 
+    // The system control commands acknowledged by the device update its control state
     pub fn handle_action_result(
-        &self,
+        &mut self,
         delayed_action: &DeviceAction,
         completed_by: Response,
     ) -> Result<<DeviceAction as ActionTrait>::Result, DeviceError> {
         match delayed_action {
-            DeviceAction::Reset => Ok(DeviceActionResult::ResetSent),
+            DeviceAction::Reset => {
+                self.control
+                    .apply(&SysCtrl::HARDWARE_RESET, completed_by.timestamp);
+                Ok(DeviceActionResult::ResetSent)
+            }
             DeviceAction::ResetSettings => Ok(DeviceActionResult::ResetSettingsSent),
-            DeviceAction::InhibitControl(_inhibit) => Ok(DeviceActionResult::InhibitControlSent),
+            DeviceAction::InhibitControl(inhibit) => {
+                self.control
+                    .apply(&SysCtrl::inhibit_control(*inhibit), completed_by.timestamp);
+                Ok(DeviceActionResult::InhibitControlSent)
+            }
+            DeviceAction::WatchdogControl(enable) => {
+                self.control
+                    .apply(&Self::watchdog_sys_ctrl(*enable), completed_by.timestamp);
+                Ok(DeviceActionResult::WatchdogControlSent)
+            }
             DeviceAction::Ping(_endpoint) => Ok(DeviceActionResult::Pong(completed_by)),
+            DeviceAction::ReadAllAttributes => Err(DeviceError::UnsupportedAction),
+            DeviceAction::SetOutput { .. } => Ok(DeviceActionResult::Done),
             DeviceAction::Inner(inner_action) => {
                 if let Some(inner_device) = self.controller.as_ref() {
                     let result = inner_device
@@ -166,7 +234,12 @@ impl Device {
         match frame {
             ResponseData::Telemetry { .. } => self.stats.telemetry_rx += 1,
             ResponseData::Attribute { .. } => self.stats.attribute_rx += 1,
-            ResponseData::Error { .. } => self.stats.err_rx += 1,
+            ResponseData::Error { error, .. } => {
+                self.stats.err_rx += 1;
+                if let Some(code) = error {
+                    *self.stats.err_codes.entry(*code).or_default() += 1;
+                }
+            }
         }
 
         // Ty to parse the telemetry frame as a class telemetry if possible
@@ -202,10 +275,20 @@ impl Device {
         if let Some(pending_job) = self.jobs.pop_pending() {
             /* Handle special jobs */
             match pending_job.definition {
+                DeviceJobWrapper::DeviceAdd => {
+                    ctx.request_attributes_reconciliation();
+                    ctx.request_time_sync(TimeSyncMode::Sync);
+                }
                 DeviceJobWrapper::Scheduled(ref job) => {
                     if downcast_job_as::<DeviceMeasuresResetJob>(job).is_some() {
                         self.measures.reset_minmax();
                     }
+
+                    // Handled by the devices controller, not by the inner controller
+                    if let Some(time_sync_job) = downcast_job_as::<DeviceTimeSyncJob>(job) {
+                        ctx.request_time_sync(time_sync_job.get_mode());
+                        return Some(Ok(Verdict::default()));
+                    }
                 }
                 _ => {}
             };
@@ -223,6 +306,8 @@ impl Device {
                     "No controller to process job {:?} for device {}",
                     pending_job.definition, self.did
                 );
+                // Special jobs may still have requested something through the context
+                return Some(Ok(Verdict::default()));
             }
         }
 
@@ -258,10 +343,27 @@ impl Device {
             // TODO not fully implemented for now
             warn!("Get alert on unseen device");
             Some(DeviceAlert::new_error("Capteur non détecté"))
-        } else if let Some(inner) = self.controller.as_ref() {
-            inner.wrapper_get_alert()
         } else {
-            None
+            let inner_alert = self
+                .controller
+                .as_ref()
+                .and_then(|inner| inner.wrapper_get_alert());
+            let config_alert = self
+                .attributes_config
+                .as_ref()
+                .and_then(|status| status.get_alert());
+            let control_alert = self.control.get_alert(&Utc::now());
+
+            // Report the most severe alert
+            [config_alert, control_alert]
+                .into_iter()
+                .fold(inner_alert, |most_severe, alert| {
+                    if cmp_severity(&alert, &most_severe).is_gt() {
+                        alert
+                    } else {
+                        most_severe
+                    }
+                })
         }
     }
 
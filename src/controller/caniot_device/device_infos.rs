@@ -4,6 +4,7 @@ use chrono::{DateTime, Utc};
 use serde::Serialize;
 
 use crate::{
+    bus::CanBusId,
     caniot::{self, traits::TempSensType},
     controller::DeviceAlert,
     utils::{join_labels, DeviceLabel, PrometheusExporterTrait},
@@ -14,6 +15,7 @@ use super::{Device, DeviceStats};
 #[derive(Debug, Clone, Serialize)]
 pub struct DeviceInfos {
     pub did: caniot::DeviceId,
+    pub bus: CanBusId,
     pub is_seen: bool,
     pub last_seen: Option<DateTime<Utc>>,
     pub last_seen_from_now: Option<u32>, // seconds
@@ -22,7 +24,11 @@ pub struct DeviceInfos {
     pub controller_display_name: Option<String>,
     pub controller_metrics: Vec<String>,
     pub stats: DeviceStats,
-    pub measures: Option<caniot::BoardClassTelemetry>,
+    pub io: Option<caniot::IoSnapshot>,
+
+    // clock synchronization
+    pub clock_offset: Option<i64>, // seconds, positive if the device is ahead
+    pub clock_synced_at: Option<DateTime<Utc>>,
 
     // measures
     pub board_temperature: Option<f32>,
@@ -34,6 +40,11 @@ pub struct DeviceInfos {
     pub outside_temp_max: Option<f32>,
     pub outside_temp_avg: Option<f32>,
 
+    // watchdog and inhibit state
+    pub watchdog_enabled: Option<bool>,
+    pub inhibited: bool,
+    pub inhibit_remaining: Option<u32>, // seconds, estimated
+
     // current alert
     pub active_alert: Option<DeviceAlert>,
 
@@ -46,7 +57,6 @@ impl Into<DeviceInfos> for &Device {
         // If controller get the controller infos
         let mut controller_display_name = None;
         let mut controller_name = None;
-        let mut active_alert = None;
         let mut controller_attached = false;
         let mut ui_view_name = None;
         if let Some(controller) = &self.controller {
@@ -55,14 +65,18 @@ impl Into<DeviceInfos> for &Device {
             let infos = controller.wrapper_get_infos();
             controller_name = Some(infos.name);
             controller_display_name = infos.display_name;
-            active_alert = controller.wrapper_get_alert();
             ui_view_name = infos.ui_view_name;
         }
 
+        // Same alert as the one the devices are filtered and sorted with
+        let now = Utc::now();
+        let active_alert = self.get_alert();
+
         let class_last_telemetry = self.measures.get_class_telemetry();
 
         DeviceInfos {
             did: self.did,
+            bus: self.bus,
             last_seen: self.last_seen,
             controller_attached: controller_attached,
             controller_name,
@@ -70,8 +84,10 @@ impl Into<DeviceInfos> for &Device {
             controller_metrics: self.get_controller_metrics(),
             is_seen: self.is_seen(),
             last_seen_from_now: self.last_seen_from_now(),
-            stats: self.stats,
-            measures: *class_last_telemetry,
+            stats: self.stats.clone(),
+            io: class_last_telemetry.as_ref().map(|m| m.io_snapshot()),
+            clock_offset: self.time_sync.offset,
+            clock_synced_at: self.time_sync.synced_at,
             board_temperature: class_last_telemetry
                 .and_then(|m| m.get_temperature(TempSensType::BoardSensor)),
             board_temp_min: self.measures.get_board_temp_monitor().get_min().cloned(),
@@ -82,6 +98,12 @@ impl Into<DeviceInfos> for &Device {
             outside_temp_avg: self.measures.get_outside_temp_monitor().get_avg().cloned(),
             outside_temperature: class_last_telemetry
                 .and_then(|m| m.get_temperature(TempSensType::AnyExternal)),
+            watchdog_enabled: self.control.watchdog_enabled,
+            inhibited: self.control.is_inhibited(&now),
+            inhibit_remaining: self
+                .control
+                .inhibit_remaining(&now)
+                .map(|remaining| remaining.num_seconds() as u32),
             active_alert,
             ui_view_name,
         }
@@ -145,6 +167,40 @@ impl<'a> PrometheusExporterTrait<'a> for DeviceInfos {
         )
         .unwrap();
 
+        if let Some(clock_offset) = self.clock_offset {
+            writeln!(
+                &mut buf,
+                "device_clock_offset {{{str_labels}}} {}",
+                clock_offset
+            )
+            .unwrap();
+        }
+
+        if let Some(clock_synced_at) = self.clock_synced_at {
+            writeln!(
+                &mut buf,
+                "device_clock_synced_at {{{str_labels}}} {}",
+                clock_synced_at.timestamp()
+            )
+            .unwrap();
+        }
+
+        if let Some(watchdog_enabled) = self.watchdog_enabled {
+            writeln!(
+                &mut buf,
+                "device_watchdog_enabled {{{str_labels}}} {}",
+                watchdog_enabled as u8
+            )
+            .unwrap();
+        }
+
+        writeln!(
+            &mut buf,
+            "device_inhibited {{{str_labels}}} {}",
+            self.inhibited as u8
+        )
+        .unwrap();
+
         if let Some(board_temperature) = self.board_temperature {
             writeln!(
                 &mut buf,
@@ -163,6 +219,37 @@ impl<'a> PrometheusExporterTrait<'a> for DeviceInfos {
             .unwrap();
         }
 
+        if let Some(io) = &self.io {
+            for state in io.ios.iter() {
+                writeln!(
+                    &mut buf,
+                    "device_io {{{str_labels},io=\"{}\",kind=\"{:?}\"}} {}",
+                    state.name, state.kind, state.state as u8
+                )
+                .unwrap();
+            }
+
+            for pulse in io.pulses.iter() {
+                writeln!(
+                    &mut buf,
+                    "device_io_pulse {{{str_labels},output=\"{}\"}} {}",
+                    pulse.output, pulse.active as u8
+                )
+                .unwrap();
+            }
+
+            for sensor in io.temperatures.iter() {
+                if let Some(value) = sensor.value {
+                    writeln!(
+                        &mut buf,
+                        "device_io_temperature {{{str_labels},sensor=\"{}\"}} {}",
+                        sensor.name, value
+                    )
+                    .unwrap();
+                }
+            }
+        }
+
         for metric in &self.controller_metrics {
             writeln!(&mut buf, "{}", metric).unwrap();
         }
@@ -0,0 +1,61 @@
+use chrono::Utc;
+
+use crate::{
+    caniot::{Attribute, DeviceId, SysCtrl, TSP},
+    controller::{AttributesConfigStatus, DeviceAlertType},
+};
+
+use super::{Device, DeviceInfos};
+
+fn active_alert(device: &Device) -> Option<(DeviceAlertType, String)> {
+    let infos: DeviceInfos = device.into();
+    infos
+        .active_alert
+        .map(|alert| (alert.alert_type, alert.name))
+}
+
+#[test]
+fn test_device_infos_active_alert() {
+    let mut device = Device::new(DeviceId::from_u8(1), 0, None);
+    device.last_seen = Some(Utc::now());
+    assert_eq!(active_alert(&device), None);
+
+    let mut status = AttributesConfigStatus::new();
+    status.drifted.push(Attribute::ConfigTelemetryPeriod);
+    device.attributes_config = Some(status.clone());
+    assert_eq!(
+        active_alert(&device),
+        Some((
+            DeviceAlertType::Warning,
+            "Configuration corrigée".to_string()
+        ))
+    );
+
+    // An inhibited device reports it over a corrected configuration
+    device
+        .control
+        .apply(&SysCtrl::inhibit_control(TSP::Set), Utc::now());
+    assert_eq!(
+        active_alert(&device).map(|(alert_type, _)| alert_type),
+        Some(DeviceAlertType::Inhibitted)
+    );
+
+    // A configuration not applied is more severe
+    status
+        .failed
+        .push((Attribute::ConfigTelemetryDelay, "error".to_string()));
+    device.attributes_config = Some(status);
+    assert_eq!(
+        active_alert(&device),
+        Some((
+            DeviceAlertType::Error,
+            "Configuration non appliquée".to_string()
+        ))
+    );
+
+    // Same alert as the one devices are filtered with
+    assert_eq!(
+        device.get_alert().map(|alert| alert.name),
+        Some("Configuration non appliquée".to_string())
+    );
+}
@@ -7,7 +7,7 @@ use log::debug;
 
 use crate::utils::{expirable::ExpirableTrait, Scheduling};
 
-use super::DeviceMeasuresResetJob;
+use super::{DeviceMeasuresResetJob, DeviceTimeSyncJob};
 
 pub trait JobTrait: AsAny + Send + Debug + DynClone {
     fn get_scheduling(&self) -> Scheduling {
@@ -94,6 +94,8 @@ impl DeviceJobsContext {
         let init_jobs = vec![
             DeviceJobWrapper::DeviceAdd,
             DeviceJobWrapper::Scheduled(Box::new(DeviceMeasuresResetJob::default())),
+            DeviceJobWrapper::Scheduled(Box::new(DeviceTimeSyncJob::default())),
+            DeviceJobWrapper::Scheduled(Box::new(DeviceTimeSyncJob::Check)),
         ];
 
         let init_eval_in = init_jobs.ttl(&first_eval);
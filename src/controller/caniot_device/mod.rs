@@ -1,21 +1,34 @@
 pub mod actions;
+pub mod attributes_config;
+pub mod attributes_report;
 pub mod context;
+pub mod control_state;
 pub mod device;
 pub mod device_infos;
 pub mod jobs;
 pub mod standard_measures;
 pub mod stats;
+pub mod time_sync;
 pub mod traits;
 pub mod types;
 pub mod verdict;
 
 pub use actions::*;
+pub use attributes_config::*;
+pub use attributes_report::*;
 pub use context::*;
+pub use control_state::*;
 pub use device::*;
 pub use device_infos::*;
 pub use jobs::*;
 pub use standard_measures::*;
 pub use stats::*;
+pub use time_sync::*;
 pub use traits::*;
 pub use types::*;
 pub use verdict::*;
+
+#[cfg(test)]
+mod control_state_test;
+#[cfg(test)]
+mod device_infos_test;
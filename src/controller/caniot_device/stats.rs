@@ -1,8 +1,11 @@
+use std::collections::BTreeMap;
+
 use serde::Serialize;
 
+use crate::caniot::ErrorCode;
 use crate::utils::{join_labels, DeviceLabel, PrometheusExporterTrait};
 
-#[derive(Serialize, Debug, Clone, Copy, Default)]
+#[derive(Serialize, Debug, Clone, Default)]
 pub struct DeviceStats {
     pub rx: usize,
     pub tx: usize,
@@ -13,6 +16,9 @@ pub struct DeviceStats {
     pub attribute_tx: usize,
     pub err_rx: usize,
 
+    // error responses received per error code
+    pub err_codes: BTreeMap<ErrorCode, usize>,
+
     pub reset_requested: usize,
     pub reset_settings_requested: usize,
 
@@ -25,7 +31,7 @@ impl<'a> PrometheusExporterTrait<'a> for DeviceStats {
     type Label = DeviceLabel;
     fn export(&self, labels: impl AsRef<[&'a Self::Label]>) -> String {
         let labels = join_labels(labels);
-        format!(
+        let mut export = format!(
             "device_stats_rx {{{labels}}} {}\n\
             device_stats_tx {{{labels}}} {}\n\
             device_stats_telemetry_rx {{{labels}}} {}\n\
@@ -50,6 +56,18 @@ impl<'a> PrometheusExporterTrait<'a> for DeviceStats {
             self.reset_settings_requested,
             self.jobs_currently_scheduled,
             self.jobs_processed,
-        )
+        );
+
+        for (code, count) in self.err_codes.iter() {
+            let code_label = format!("code=\"{:?}\"", code);
+            let labels = if labels.is_empty() {
+                code_label
+            } else {
+                format!("{labels},{code_label}")
+            };
+            export.push_str(&format!("device_stats_err_code {{{labels}}} {count}\n"));
+        }
+
+        export
     }
 }
@@ -0,0 +1,66 @@
+use chrono::{DateTime, NaiveTime, Utc};
+
+use crate::utils::Scheduling;
+
+use super::JobTrait;
+
+/// Synchronization requested to the devices controller
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum TimeSyncMode {
+    // Read the device time back, resynchronize only if it drifted
+    Check,
+    // Write the controller time to the device
+    Sync,
+}
+
+/// Clock synchronization state of a device
+#[derive(Debug, Clone, Default)]
+pub struct TimeSyncStatus {
+    // Last time the controller time was written to the device
+    pub synced_at: Option<DateTime<Utc>>,
+
+    // Last time the device time was read back
+    pub checked_at: Option<DateTime<Utc>>,
+
+    // Device clock offset relative to the controller (s), positive if the device is ahead
+    pub offset: Option<i64>,
+
+    // Synchronizations triggered by a drift of the device clock
+    pub drift_resyncs: usize,
+
+    // Reason of the failure, if the last synchronization failed
+    pub error: Option<String>,
+}
+
+/// Periodic synchronization of the device clock
+#[derive(Debug, Clone)]
+pub enum DeviceTimeSyncJob {
+    // Daily write of the controller time
+    Sync(NaiveTime),
+    // Hourly check of the device clock drift
+    Check,
+}
+
+impl DeviceTimeSyncJob {
+    pub fn get_mode(&self) -> TimeSyncMode {
+        match self {
+            DeviceTimeSyncJob::Sync(_) => TimeSyncMode::Sync,
+            DeviceTimeSyncJob::Check => TimeSyncMode::Check,
+        }
+    }
+}
+
+impl Default for DeviceTimeSyncJob {
+    fn default() -> Self {
+        Self::Sync(NaiveTime::from_hms_opt(3, 0, 0).unwrap())
+    }
+}
+
+impl JobTrait for DeviceTimeSyncJob {
+    fn get_scheduling(&self) -> Scheduling {
+        match self {
+            DeviceTimeSyncJob::Sync(time) => Scheduling::Daily(*time),
+            DeviceTimeSyncJob::Check => Scheduling::Hourly,
+        }
+    }
+}
@@ -81,6 +81,8 @@ pub trait DeviceControllerTrait: Send + Debug + Default {
     // TODO add a config type to the trait
     // type Config;
 
+    // The requests of the verdict are sent in order, possibly to other devices
+    // of the bus (see VerdictRequest)
     fn handle_frame(
         &mut self,
         _frame: &caniot::ResponseData,
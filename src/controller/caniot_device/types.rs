@@ -10,6 +10,8 @@ pub enum DeviceError {
     NoInnerDevice,
     #[error("Unsupported action for device")]
     UnsupportedAction,
+    #[error("Output not available for device class {0}")]
+    OutputClassMismatch(u8),
     #[error("Unsupported process type")]
     UnsupportedProcessType,
     #[error("No action result")]
@@ -1,15 +1,67 @@
+use std::time::Duration;
+
 use crate::{
-    caniot::RequestData,
+    caniot::{DeviceId, RequestData},
     controller::{ActionResultTrait, ActionTrait},
 };
 
 use super::actions::{DeviceAction, DeviceActionResult};
 
+/// Request of a verdict, sent once the previous requests of the verdict are sent
+#[derive(Debug, Clone)]
+pub struct VerdictRequest {
+    // Device the request is sent to, the device itself (on its bus) if None.
+    // Requests to another device are routed to the bus it was seen on, the first
+    // bus if unknown, and are logged and dropped if it is known on several buses.
+    pub did: Option<DeviceId>,
+    pub data: RequestData,
+
+    // Delay after the previous request of the verdict, sent right after it if None
+    pub delay: Option<Duration>,
+}
+
+impl VerdictRequest {
+    pub fn new(data: RequestData) -> Self {
+        Self {
+            did: None,
+            data,
+            delay: None,
+        }
+    }
+
+    pub fn to_device(did: DeviceId, data: RequestData) -> Self {
+        Self {
+            did: Some(did),
+            ..Self::new(data)
+        }
+    }
+
+    pub fn with_delay(self, delay: Duration) -> Self {
+        Self {
+            delay: Some(delay),
+            ..self
+        }
+    }
+}
+
 #[derive(Debug, Default)]
 pub enum Verdict {
     #[default]
     None,
+    // Single request to the device, sent immediately
     Request(RequestData),
+    // Requests sent in order, each one after its delay
+    Requests(Vec<VerdictRequest>),
+}
+
+impl Verdict {
+    pub fn into_requests(self) -> Vec<VerdictRequest> {
+        match self {
+            Verdict::None => vec![],
+            Verdict::Request(data) => vec![VerdictRequest::new(data)],
+            Verdict::Requests(requests) => requests,
+        }
+    }
 }
 
 #[derive(Debug)]
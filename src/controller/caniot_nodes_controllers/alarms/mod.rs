@@ -1,7 +1,7 @@
 mod actions;
 mod jobs;
 pub mod outdoor;
-mod types;
+pub(super) mod types;
 
 pub use actions::{Action, AlarmEnable, LightAction, LightsActions, SirenAction};
 pub use outdoor::*;
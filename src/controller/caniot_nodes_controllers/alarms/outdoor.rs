@@ -1,5 +1,5 @@
 use chrono::{DateTime, Duration, Utc};
-use log::{info, warn};
+use log::{error, info, warn};
 use serde::{Deserialize, Serialize};
 
 use super::{
@@ -9,7 +9,8 @@ use super::{
     AlarmPartialConfig,
 };
 use crate::{
-    caniot::{self, RequestData, Response, Xps},
+    board_inputs,
+    caniot::{self, class0, BoardIo, RequestData, Response, Xps},
     controller::{
         alarms::{actions::SirenAction, types::OutdoorAlarmCommand},
         ActionResultTrait, ActionTrait, ActionVerdict, ConfigTrait, DeviceAlert,
@@ -87,12 +88,20 @@ impl NightLightsContext {
     }
 }
 
-#[derive(Debug, Clone, Default)]
-pub struct DeviceIOState {
-    pub siren: bool,          // true if siren is on
-    pub detectors: [bool; 2], // east, south (true if presence detected)
-    pub lights: [bool; 2],    // south, east (true if lights are on)
-    pub sabotage: bool,       // false if sabotage detected
+board_inputs! {
+    #[derive(Debug, Clone, Default)]
+    pub struct DeviceIOState {
+        // true if siren is on
+        siren: BoardIo::Class0Output(class0::Output::Rl1),
+        // true if presence detected
+        east_detector: BoardIo::Class0Input(class0::Input::In1),
+        south_detector: BoardIo::Class0Input(class0::Input::In2),
+        // true if lights are on
+        south_light: BoardIo::Class0Output(class0::Output::Oc1),
+        east_light: BoardIo::Class0Output(class0::Output::Oc2),
+        // false if sabotage detected
+        sabotage: BoardIo::Class0Input(class0::Input::In4),
+    }
 }
 
 impl DeviceIOState {
@@ -101,11 +110,11 @@ impl DeviceIOState {
     }
 
     pub fn get_south_detector(&self) -> bool {
-        self.detectors[1]
+        self.south_detector
     }
 
     pub fn get_east_detector(&self) -> bool {
-        self.detectors[0]
+        self.east_detector
     }
 
     pub fn get_sabotage(&self) -> bool {
@@ -113,11 +122,11 @@ impl DeviceIOState {
     }
 
     pub fn get_south_light(&self) -> bool {
-        self.lights[0]
+        self.south_light
     }
 
     pub fn get_east_light(&self) -> bool {
-        self.lights[1]
+        self.east_light
     }
 }
 
@@ -254,7 +263,14 @@ impl AlarmController {
             }
         }
 
-        command.has_effect().then(|| command.into_request())
+        if !command.has_effect() {
+            return None;
+        }
+
+        command
+            .into_request()
+            .map_err(|err| error!("Failed to build the outdoor alarm command: {}", err))
+            .ok()
     }
 
     /// Returns the current state of the device.
@@ -434,7 +450,7 @@ impl DeviceControllerTrait for AlarmController {
                 if set_alarm_result.is_falling() {
                     let mut command = OutdoorAlarmCommand::default();
                     command.set_siren(Xps::Reset);
-                    return Ok(ActionVerdict::ActionPendingOn(command.into_request()));
+                    return Ok(ActionVerdict::ActionPendingOn(command.into_request()?));
                 } else if set_alarm_result.is_rising() {
                     if self.ios.sabotage {
                         self.alarm.set_enable(&AlarmEnable::Disarmed);
@@ -449,7 +465,7 @@ impl DeviceControllerTrait for AlarmController {
                 command.set_east_light((&action.east).into());
                 command.set_south_light((&action.south).into());
 
-                return Ok(ActionVerdict::ActionPendingOn(command.into_request()));
+                return Ok(ActionVerdict::ActionPendingOn(command.into_request()?));
             }
             Action::SirenAction(action) => {
                 let mut command = OutdoorAlarmCommand::default();
@@ -459,7 +475,7 @@ impl DeviceControllerTrait for AlarmController {
                     }
                 }
 
-                return Ok(ActionVerdict::ActionPendingOn(command.into_request()));
+                return Ok(ActionVerdict::ActionPendingOn(command.into_request()?));
             }
         }
 
@@ -473,14 +489,10 @@ impl DeviceControllerTrait for AlarmController {
         as_class_blc: &Option<crate::caniot::BoardClassTelemetry>,
         _ctx: &mut ProcessContext,
     ) -> Result<Verdict, DeviceError> {
-        if let Some(caniot::BoardClassTelemetry::Class0(telemetry)) = as_class_blc {
-            let new_state = DeviceIOState {
-                siren: telemetry.rl1,
-                detectors: [telemetry.in1, telemetry.in2],
-                lights: [telemetry.oc1, telemetry.oc2],
-                sabotage: telemetry.in4,
-            };
-
+        if let Some(new_state) = as_class_blc
+            .as_ref()
+            .and_then(DeviceIOState::from_telemetry)
+        {
             let now = Utc::now();
 
             return Ok(self
@@ -1,36 +1,35 @@
-use crate::caniot::{self, class0, traits::ClassCommandTrait, Xps};
+use crate::{
+    board_outputs,
+    caniot::{class0, BoardOutput, Xps},
+};
 
-#[derive(Default)]
-pub struct OutdoorAlarmCommand(pub class0::Command);
+board_outputs! {
+    pub struct OutdoorAlarmCommand {
+        south_light: BoardOutput::Class0(class0::Output::Oc1),
+        east_light: BoardOutput::Class0(class0::Output::Oc2),
+        siren: BoardOutput::Class0(class0::Output::Rl1),
+    }
+}
 
 impl OutdoorAlarmCommand {
     #[allow(dead_code)]
     pub fn new(south: Xps, east: Xps, siren: Xps) -> Self {
-        OutdoorAlarmCommand(class0::Command {
-            coc1: south,
-            coc2: east,
-            crl1: siren,
-            crl2: Xps::None,
-        })
-    }
-
-    pub fn has_effect(&self) -> bool {
-        self.0.has_effect()
+        OutdoorAlarmCommand {
+            south_light: south,
+            east_light: east,
+            siren,
+        }
     }
 
     pub fn set_siren(&mut self, cmd: Xps) {
-        self.0.crl1 = cmd;
+        self.siren = cmd;
     }
 
     pub fn set_east_light(&mut self, cmd: Xps) {
-        self.0.coc2 = cmd;
+        self.east_light = cmd;
     }
 
     pub fn set_south_light(&mut self, cmd: Xps) {
-        self.0.coc1 = cmd;
-    }
-
-    pub fn into_request(self) -> caniot::RequestData {
-        self.0.into_request()
+        self.south_light = cmd;
     }
 }
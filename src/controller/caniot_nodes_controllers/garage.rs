@@ -1,4 +1,5 @@
 use crate::{
+    board_inputs, board_outputs,
     caniot::Xps,
     controller::{
         ActionResultTrait, ActionTrait, ActionVerdict, DeviceAlert, DeviceControllerInfos,
@@ -7,17 +8,13 @@ use crate::{
     utils::{format_metric, monitorable_state::StateMonitor, SensorLabel},
 };
 
-use self::traits::ClassCommandTrait;
-
 use super::super::super::caniot::*;
 
 const CONTROLLER_NAME: &str = "garage";
 
 #[derive(Debug, Default, Clone, Copy, PartialEq)]
 pub struct GarageDoorCommand {
-    // todo concept #[crl1(PulseOn)]
     pub left_door_activate: bool,
-    // todo concept #[crl2(PulseOn)]
     pub right_door_activate: bool,
 }
 
@@ -38,41 +35,31 @@ impl GarageDoorCommand {
     };
 }
 
-#[allow(clippy::all)]
-impl Into<class0::Command> for &GarageDoorCommand {
-    fn into(self) -> class0::Command {
-        class0::Command {
-            crl1: if self.left_door_activate {
-                Xps::PulseOn
-            } else {
-                Xps::None
-            },
-            crl2: if self.right_door_activate {
-                Xps::PulseOn
-            } else {
-                Xps::None
-            },
-            ..Default::default()
-        }
+board_outputs! {
+    pub struct GarageDoorOutputs {
+        left_door: BoardOutput::Class0(class0::Output::Rl1),
+        right_door: BoardOutput::Class0(class0::Output::Rl2),
     }
 }
 
-pub struct GarageIOState {
-    pub left_door_open: bool,
-    pub right_door_open: bool,
-    pub gate_open: bool,
-}
-
-impl From<&class0::Telemetry> for GarageIOState {
-    fn from(payload: &class0::Telemetry) -> Self {
+impl From<&GarageDoorCommand> for GarageDoorOutputs {
+    fn from(command: &GarageDoorCommand) -> Self {
+        let pulse_if = |activate: bool| if activate { Xps::PulseOn } else { Xps::None };
         Self {
-            left_door_open: payload.in3.into(),
-            right_door_open: payload.in4.into(),
-            gate_open: payload.in2,
+            left_door: pulse_if(command.left_door_activate),
+            right_door: pulse_if(command.right_door_activate),
         }
     }
 }
 
+board_inputs! {
+    pub struct GarageIOState {
+        left_door_open: BoardIo::Class0Input(class0::Input::In3),
+        right_door_open: BoardIo::Class0Input(class0::Input::In4),
+        gate_open: BoardIo::Class0Input(class0::Input::In2),
+    }
+}
+
 #[derive(Debug, Default, Clone, PartialEq)]
 pub struct GarageDoorStatus {
     pub left_door_status: StateMonitor<DoorState>,
@@ -213,8 +200,8 @@ impl DeviceControllerTrait for GarageController {
                     self.stats.right_door_command_sent += 1;
                 }
 
-                let blc0_command: class0::Command = command.into();
-                Ok(ActionVerdict::ActionPendingOn(blc0_command.into_request()))
+                let outputs = GarageDoorOutputs::from(command);
+                Ok(ActionVerdict::ActionPendingOn(outputs.into_request()?))
             }
         }
     }
@@ -233,14 +220,14 @@ impl DeviceControllerTrait for GarageController {
         as_class_blc: &Option<BoardClassTelemetry>,
         _ctx: &mut crate::controller::ProcessContext,
     ) -> Result<crate::controller::Verdict, crate::controller::DeviceError> {
-        if let Some(telemetry) = as_class_blc {
-            if let Some(telemetry) = telemetry.as_class0() {
-                let ios = GarageIOState::from(telemetry);
-                if let Some(ref mut status) = self.status {
-                    status.update(ios, &mut self.stats);
-                } else {
-                    self.status = Some(GarageDoorStatus::init(ios));
-                }
+        if let Some(ios) = as_class_blc
+            .as_ref()
+            .and_then(GarageIOState::from_telemetry)
+        {
+            if let Some(ref mut status) = self.status {
+                status.update(ios, &mut self.stats);
+            } else {
+                self.status = Some(GarageDoorStatus::init(ios));
             }
         }
 
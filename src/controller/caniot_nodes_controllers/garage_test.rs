@@ -7,7 +7,10 @@ fn enc() {
         left_door_activate: true,
         right_door_activate: true,
     };
-    let cmd: class0::Command = (&cmd).into();
+    let outputs = GarageDoorOutputs::from(&cmd);
+    let BoardCommand::Class0(cmd) = outputs.into_board_command().unwrap() else {
+        panic!("Garage command is not a class 0 command");
+    };
     assert_eq!(cmd.crl1, Xps::PulseOn);
     assert_eq!(cmd.crl2, Xps::PulseOn);
     assert_eq!(cmd.coc1, Xps::None);
@@ -22,7 +25,7 @@ fn dec() {
         in4: false,
         ..Default::default()
     };
-    let ios = GarageIOState::from(&payload);
+    let ios = GarageIOState::from_telemetry(&BoardClassTelemetry::Class0(payload)).unwrap();
     let status = GarageDoorStatus::init(ios);
     assert_eq!(status.left_door_status.get(), DoorState::Closed);
     assert_eq!(status.right_door_status.get(), DoorState::Closed);
@@ -10,3 +10,5 @@ pub use heaters::*;
 
 #[cfg(test)]
 mod garage_test;
+#[cfg(test)]
+mod wiring_test;
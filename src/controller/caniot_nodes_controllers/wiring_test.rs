@@ -0,0 +1,9 @@
+use super::{alarms::types::OutdoorAlarmCommand, garage::GarageDoorOutputs};
+
+// The outputs of each controller must be wired to a single supported board class,
+// otherwise its commands are rejected
+#[test]
+fn test_board_outputs_wiring() {
+    assert!(GarageDoorOutputs::default().into_board_command().is_ok());
+    assert!(OutdoorAlarmCommand::default().into_board_command().is_ok());
+}
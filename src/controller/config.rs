@@ -1,17 +1,84 @@
+use std::collections::BTreeMap;
+
 use serde::{Deserialize, Serialize};
 
+use crate::caniot::{Attribute, DeviceId};
+
+/// Desired attributes values of a device, e.g.
+///
+/// [[caniot.devices.attributes]]
+/// did = 16
+/// bus = "can0"
+/// values = { ConfigTelemetryPeriod = 60000, ConfigCls0GpioPulseDurationRl1 = 500 }
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct DeviceAttributesConfig {
+    pub did: u8,
+
+    // Name of the bus the device is on, devices with this did on any bus if None
+    pub bus: Option<String>,
+
+    // Attribute name -> raw value
+    pub values: BTreeMap<String, u32>,
+}
+
+impl DeviceAttributesConfig {
+    /// Get the configured attributes and the attribute names which do not exist
+    pub fn get_attributes(&self) -> (Vec<(Attribute, u32)>, Vec<String>) {
+        let mut unknown = Vec::new();
+        let attributes = self
+            .values
+            .iter()
+            .filter_map(|(name, value)| match name.parse::<Attribute>() {
+                Ok(attribute) => Some((attribute, *value)),
+                Err(_) => {
+                    unknown.push(name.clone());
+                    None
+                }
+            })
+            .collect();
+        (attributes, unknown)
+    }
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, Default)]
 pub struct CaniotDevicesConfig {
     pub demo_did: Option<u8>,
     pub heaters_did: Option<u8>,
     pub garage_did: Option<u8>,
     pub outdoor_alarm_did: Option<u8>,
+
+    // Estimated duration of an inhibit pulse, the devices do not report it
+    pub inhibit_pulse_duration: Option<u32>, // s
+
+    #[serde(default)]
+    pub attributes: Vec<DeviceAttributesConfig>,
+}
+
+impl CaniotDevicesConfig {
+    /// Attributes config of the device on the bus, a config for the bus
+    /// takes precedence over a config for any bus
+    pub fn get_device_attributes(
+        &self,
+        bus: &str,
+        did: &DeviceId,
+    ) -> Option<&DeviceAttributesConfig> {
+        let mut configs = self
+            .attributes
+            .iter()
+            .filter(|config| config.did == did.to_u8());
+        configs
+            .clone()
+            .find(|config| config.bus.as_deref() == Some(bus))
+            .or_else(|| configs.find(|config| config.bus.is_none()))
+    }
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, Default)]
 pub struct CaniotConfig {
     pub pending_queries_default_timeout: Option<u32>, // s
     pub action_default_timeout: Option<u32>,          // s
+    pub broadcast_default_window: Option<u32>,        // ms
+    pub time_sync_max_drift: Option<u32>,             // s
     pub inernal_api_mpsc_size: Option<u32>,
 
     pub devices: CaniotDevicesConfig,
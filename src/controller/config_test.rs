@@ -0,0 +1,41 @@
+use crate::caniot::{Attribute, DeviceId};
+
+use super::CaniotDevicesConfig;
+
+#[test]
+fn test_device_attributes_config() {
+    let config: CaniotDevicesConfig = toml::from_str(
+        r#"
+        [[attributes]]
+        did = 16
+        values = { ConfigTelemetryPeriod = 60000, ConfigCls0GpioPulseDurationRl = 500 }
+
+        [[attributes]]
+        did = 16
+        bus = "garage"
+        values = { ConfigTelemetryPeriod = 1000 }
+        "#,
+    )
+    .unwrap();
+
+    let did = DeviceId::from_u8(16);
+    assert!(config
+        .get_device_attributes("can0", &DeviceId::from_u8(1))
+        .is_none());
+
+    // Misspelled attribute names are reported, not ignored
+    let (attributes, unknown) = config
+        .get_device_attributes("can0", &did)
+        .unwrap()
+        .get_attributes();
+    assert_eq!(attributes, vec![(Attribute::ConfigTelemetryPeriod, 60000)]);
+    assert_eq!(unknown, vec!["ConfigCls0GpioPulseDurationRl".to_string()]);
+
+    // The config of the bus takes precedence
+    let (attributes, unknown) = config
+        .get_device_attributes("garage", &did)
+        .unwrap()
+        .get_attributes();
+    assert_eq!(attributes, vec![(Attribute::ConfigTelemetryPeriod, 1000)]);
+    assert!(unknown.is_empty());
+}
@@ -1,75 +1,254 @@
-use std::{pin::Pin, task::Poll};
+use std::{
+    task::Poll,
+    time::{Duration, Instant},
+};
 
-use futures::future::Pending;
+use chrono::{DateTime, Utc};
+use embedded_can::{Frame as EmbeddedFrame, Id as EmbeddedId};
+use serde::Serialize;
 use socketcan::CanDataFrame;
-use tokio::sync::{
-    mpsc::{self, error::TrySendError},
-    TryAcquireError,
-};
+use tokio::sync::mpsc::{self, error::TrySendError};
 
 use thiserror::Error;
 
+use crate::{
+    bus::{CanBusId, CanRxFrame},
+    caniot::{self, DeviceId},
+};
+
+pub type CanTunnelId = u32;
+
+// Minimum interval between two logs of the frames dropped by a tunnel
+const CAN_TUNNEL_DROP_LOG_INTERVAL: Duration = Duration::from_secs(10);
+
 #[derive(Error, Debug)]
 pub enum CanTunnelError {
-    #[error("Tunnel is already established")]
-    AlreadyEstablished,
+    #[error("Tunnel {0} not found")]
+    NotFound(CanTunnelId),
+
+    #[error("Bus {0} not found")]
+    NoSuchBus(CanBusId),
+}
+
+/// Direction of the CANIOT frames forwarded to a tunnel
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize)]
+pub enum CanTunnelDirection {
+    #[default]
+    Both,
+    Query,
+    Response,
+}
+
+/// What to do with a frame when the queue of the tunnel client is full
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize)]
+pub enum CanTunnelQueuePolicy {
+    // Drop the frame, the tunnel remains open
+    #[default]
+    Drop,
+    // Close the tunnel, for clients which cannot miss a frame
+    Close,
+}
+
+/// Frames received from the bus forwarded to a tunnel
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct CanTunnelFilter {
+    // Devices whose frames are forwarded, any device if empty
+    pub dids: Vec<DeviceId>,
+    pub direction: CanTunnelDirection,
+}
+
+impl CanTunnelFilter {
+    pub fn is_empty(&self) -> bool {
+        self.dids.is_empty() && self.direction == CanTunnelDirection::Both
+    }
+
+    /// Frames other than CANIOT ones only match the empty filter
+    pub fn matches(&self, frame: &CanDataFrame) -> bool {
+        let id = match frame.id() {
+            EmbeddedId::Standard(id) => caniot::Id::from(id.as_raw()),
+            EmbeddedId::Extended(_) => return self.is_empty(),
+        };
+
+        let direction = match self.direction {
+            CanTunnelDirection::Both => true,
+            CanTunnelDirection::Query => id.direction == caniot::Direction::Query,
+            CanTunnelDirection::Response => id.direction == caniot::Direction::Response,
+        };
+
+        direction && (self.dids.is_empty() || self.dids.contains(&id.device_id))
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct CanTunnelConfig {
+    // Name of the client, for display purpose
+    pub name: String,
+    // Bus the tunnel is attached to, frames are received from and sent to it
+    pub bus: CanBusId,
+    pub filter: CanTunnelFilter,
+    pub queue_policy: CanTunnelQueuePolicy,
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct CanTunnelStats {
+    pub rx: usize,       // Frames forwarded to the client
+    pub tx: usize,       // Frames sent to the bus by the client
+    pub filtered: usize, // Frames not matching the filter
+    pub dropped: usize,  // Frames dropped because the queue of the client is full
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CanTunnelInfos {
+    pub id: CanTunnelId,
+    pub config: CanTunnelConfig,
+    pub established_at: DateTime<Utc>,
+    pub stats: CanTunnelStats,
 }
 
 struct Tunnel {
-    rx_queue: mpsc::Sender<CanDataFrame>,
+    infos: CanTunnelInfos,
+    rx_queue: mpsc::Sender<CanRxFrame>,
     tx_queue: mpsc::Receiver<CanDataFrame>,
+
+    // Last time the frames dropped were logged
+    dropped_logged_at: Option<Instant>,
 }
 
 #[derive(Default)]
 pub struct CanTunnelContextServer {
-    tunnel: Option<Tunnel>,
+    tunnels: Vec<Tunnel>,
+    next_id: CanTunnelId,
+
+    // Index of the tunnel polled first by poll_tx(), so that no tunnel starves the others
+    next_poll: usize,
 }
 
 impl CanTunnelContextServer {
     pub fn establish_can_tunnel(
         &mut self,
-        rx_queue: mpsc::Sender<CanDataFrame>,
+        config: CanTunnelConfig,
+        rx_queue: mpsc::Sender<CanRxFrame>,
         tx_queue: mpsc::Receiver<CanDataFrame>,
-    ) -> Result<(), CanTunnelError> {
-        if self.tunnel.is_some() {
-            return Err(CanTunnelError::AlreadyEstablished);
-        }
+    ) -> CanTunnelId {
+        let id = self.next_id;
+        self.next_id = self.next_id.wrapping_add(1);
+
+        log::info!("CanTunnel {} established: {:?}", id, config);
+        self.tunnels.push(Tunnel {
+            infos: CanTunnelInfos {
+                id,
+                config,
+                established_at: Utc::now(),
+                stats: CanTunnelStats::default(),
+            },
+            rx_queue,
+            tx_queue,
+            dropped_logged_at: None,
+        });
+
+        id
+    }
 
-        self.tunnel = Some(Tunnel { rx_queue, tx_queue });
+    pub fn close_tunnel(&mut self, id: CanTunnelId) -> Result<(), CanTunnelError> {
+        let index = self
+            .tunnels
+            .iter()
+            .position(|tunnel| tunnel.infos.id == id)
+            .ok_or(CanTunnelError::NotFound(id))?;
+
+        self.tunnels.remove(index);
+        log::info!("CanTunnel {} closed", id);
         Ok(())
     }
 
-    pub fn close_tunnel(&mut self) {
-        self.tunnel = None;
+    pub fn get_tunnels(&self) -> Vec<CanTunnelInfos> {
+        self.tunnels
+            .iter()
+            .map(|tunnel| tunnel.infos.clone())
+            .collect()
     }
 
-    pub fn notify_rx(&mut self, frame: CanDataFrame) {
-        if let Some(tunnel) = &self.tunnel {
-            match tunnel.rx_queue.try_send(frame) {
-                Ok(_) => {}
-                Err(TrySendError::Closed(frame)) => {
-                    log::error!("CanTunnel rx_queue closed, closing tunnel");
-                    self.close_tunnel();
+    /// Forward the frame received on the bus to the tunnels attached to it
+    pub fn notify_rx(&mut self, bus: CanBusId, frame: &CanRxFrame) {
+        self.tunnels.retain_mut(|tunnel| {
+            let infos = &mut tunnel.infos;
+            if infos.config.bus != bus {
+                return true;
+            }
+
+            if !infos.config.filter.matches(&frame.frame) {
+                infos.stats.filtered += 1;
+                return true;
+            }
+
+            match tunnel.rx_queue.try_send(frame.clone()) {
+                Ok(_) => {
+                    infos.stats.rx += 1;
+                    true
                 }
-                Err(TrySendError::Full(frame)) => {
-                    log::error!("CanTunnel rx_queue full, dropping frame");
+                Err(TrySendError::Closed(_)) => {
+                    log::error!("CanTunnel {} rx_queue closed, closing tunnel", infos.id);
+                    false
+                }
+                Err(TrySendError::Full(_)) => {
+                    infos.stats.dropped += 1;
+                    match infos.config.queue_policy {
+                        CanTunnelQueuePolicy::Drop => {
+                            // A slow client drops many frames, do not flood the log
+                            let now = Instant::now();
+                            if tunnel.dropped_logged_at.map_or(true, |logged_at| {
+                                now.duration_since(logged_at) >= CAN_TUNNEL_DROP_LOG_INTERVAL
+                            }) {
+                                log::warn!(
+                                    "CanTunnel {} rx_queue full, {} frames dropped so far",
+                                    infos.id,
+                                    infos.stats.dropped
+                                );
+                                tunnel.dropped_logged_at = Some(now);
+                            }
+                            true
+                        }
+                        CanTunnelQueuePolicy::Close => {
+                            log::error!("CanTunnel {} rx_queue full, closing tunnel", infos.id);
+                            false
+                        }
+                    }
                 }
             }
-        }
+        });
     }
 
-    // Return futures
-    pub async fn poll_tx(&mut self) -> Option<CanDataFrame> {
-        if let Some(tunnel) = &mut self.tunnel {
-            if let Some(frame) = tunnel.tx_queue.recv().await {
-                Some(frame)
-            } else {
-                // tx_queue closed
-                self.close_tunnel();
-                None
+    // Next frame to be sent from any tunnel and the bus to send it on, None when
+    // a tunnel is closed by its client. The tunnels are polled in turn.
+    pub async fn poll_tx(&mut self) -> Option<(CanBusId, CanDataFrame)> {
+        futures::future::poll_fn(|cx| {
+            let (start, count) = (self.next_poll, self.tunnels.len());
+            let mut closed = None;
+            for index in (0..count).map(|offset| (start + offset) % count) {
+                let tunnel = &mut self.tunnels[index];
+                match tunnel.tx_queue.poll_recv(cx) {
+                    Poll::Ready(Some(frame)) => {
+                        tunnel.infos.stats.tx += 1;
+                        self.next_poll = index + 1;
+                        return Poll::Ready(Some((tunnel.infos.config.bus, frame)));
+                    }
+                    Poll::Ready(None) => {
+                        closed = Some(index);
+                        break;
+                    }
+                    Poll::Pending => {}
+                }
+            }
+
+            match closed {
+                Some(index) => {
+                    let tunnel = self.tunnels.remove(index);
+                    log::info!("CanTunnel {} closed by client", tunnel.infos.id);
+                    Poll::Ready(None)
+                }
+                None => Poll::Pending,
             }
-        } else {
-            futures::future::pending().await
-        }
+        })
+        .await
     }
 }
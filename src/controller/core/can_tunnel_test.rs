@@ -0,0 +1,151 @@
+use chrono::Utc;
+use embedded_can::{ExtendedId, Frame as EmbeddedFrame};
+use futures::FutureExt;
+use socketcan::CanDataFrame;
+use tokio::sync::mpsc;
+
+use crate::{
+    bus::CanRxFrame,
+    caniot::{self, DeviceId, Endpoint, RequestData, ResponseData},
+};
+
+use super::can_tunnel::*;
+
+fn query(did: u8) -> CanDataFrame {
+    caniot::Request {
+        device_id: DeviceId::from_u8(did),
+        data: RequestData::Telemetry {
+            endpoint: Endpoint::BoardControl,
+        },
+        timestamp: Utc::now(),
+    }
+    .into()
+}
+
+fn response(did: u8) -> CanDataFrame {
+    caniot::Response {
+        device_id: DeviceId::from_u8(did),
+        data: ResponseData::Attribute { key: 0, value: 0 },
+        timestamp: Utc::now(),
+    }
+    .into()
+}
+
+#[test]
+fn test_can_tunnel_filter() {
+    let foreign = CanDataFrame::new(ExtendedId::new(0x1234).unwrap(), &[]).unwrap();
+
+    let filter = CanTunnelFilter::default();
+    assert!(filter.matches(&query(1)));
+    assert!(filter.matches(&response(2)));
+    assert!(filter.matches(&foreign));
+
+    let filter = CanTunnelFilter {
+        dids: vec![DeviceId::from_u8(1), DeviceId::from_u8(16)],
+        direction: CanTunnelDirection::Response,
+    };
+    assert!(filter.matches(&response(1)));
+    assert!(filter.matches(&response(16)));
+    assert!(!filter.matches(&response(2)));
+    assert!(!filter.matches(&query(1)));
+    assert!(!filter.matches(&foreign));
+}
+
+#[test]
+fn test_can_tunnel_queue_policies() {
+    let mut server = CanTunnelContextServer::default();
+
+    let (rx_queue, mut sniffer_rx) = mpsc::channel(1);
+    let (_sniffer_tx, tx_queue) = mpsc::channel(1);
+    let sniffer = server.establish_can_tunnel(CanTunnelConfig::default(), rx_queue, tx_queue);
+
+    let (rx_queue, _bench_rx) = mpsc::channel(1);
+    let (_bench_tx, tx_queue) = mpsc::channel(1);
+    let bench = server.establish_can_tunnel(
+        CanTunnelConfig {
+            name: "bench".to_string(),
+            bus: 0,
+            filter: CanTunnelFilter {
+                dids: vec![DeviceId::from_u8(1)],
+                direction: CanTunnelDirection::Both,
+            },
+            queue_policy: CanTunnelQueuePolicy::Close,
+        },
+        rx_queue,
+        tx_queue,
+    );
+
+    server.notify_rx(0, &CanRxFrame::new(response(2)));
+    server.notify_rx(0, &CanRxFrame::new(response(1)));
+
+    // The sniffer drops the second frame, the bench filtered the first one
+    let tunnels = server.get_tunnels();
+    assert_eq!(tunnels.len(), 2);
+    assert_eq!(tunnels[0].id, sniffer);
+    assert_eq!(tunnels[0].stats.rx, 1);
+    assert_eq!(tunnels[0].stats.dropped, 1);
+    assert_eq!(tunnels[1].id, bench);
+    assert_eq!(tunnels[1].stats.rx, 1);
+    assert_eq!(tunnels[1].stats.filtered, 1);
+
+    // The bench queue is full and the tunnel closed
+    server.notify_rx(0, &CanRxFrame::new(response(1)));
+    let tunnels = server.get_tunnels();
+    assert_eq!(tunnels.len(), 1);
+    assert_eq!(tunnels[0].id, sniffer);
+    assert_eq!(sniffer_rx.try_recv().unwrap().frame.id(), response(2).id());
+
+    assert!(server.close_tunnel(bench).is_err());
+    assert!(server.close_tunnel(sniffer).is_ok());
+    assert!(server.get_tunnels().is_empty());
+}
+
+#[test]
+fn test_can_tunnel_buses() {
+    let mut server = CanTunnelContextServer::default();
+
+    let mut tunnels = Vec::new();
+    for bus in [0, 1, 1] {
+        let (rx_queue, rx_receiver) = mpsc::channel(8);
+        let (tx_sender, tx_queue) = mpsc::channel(8);
+        let config = CanTunnelConfig {
+            bus,
+            ..Default::default()
+        };
+        server.establish_can_tunnel(config, rx_queue, tx_queue);
+        tunnels.push((rx_receiver, tx_sender));
+    }
+
+    // Frames received on a bus are only forwarded to the tunnels attached to it
+    server.notify_rx(1, &CanRxFrame::new(response(1)));
+    assert!(tunnels[0].0.try_recv().is_err());
+    assert!(tunnels[1].0.try_recv().is_ok());
+    assert!(tunnels[2].0.try_recv().is_ok());
+    assert_eq!(server.get_tunnels()[0].stats.filtered, 0);
+
+    // Tunnels with frames to send are polled in turn, frames go to their bus
+    for (_, tx_sender) in &tunnels {
+        for did in [1, 2] {
+            tx_sender.try_send(query(did)).unwrap();
+        }
+    }
+    let polled: Vec<_> = (0..6)
+        .map(|_| server.poll_tx().now_or_never().flatten().unwrap())
+        .map(|(bus, frame)| (bus, frame.id()))
+        .collect();
+    assert_eq!(
+        polled,
+        vec![
+            (0, query(1).id()),
+            (1, query(1).id()),
+            (1, query(1).id()),
+            (0, query(2).id()),
+            (1, query(2).id()),
+            (1, query(2).id()),
+        ]
+    );
+    assert!(server.poll_tx().now_or_never().is_none());
+
+    let tunnels = server.get_tunnels();
+    assert!(tunnels.iter().all(|tunnel| tunnel.stats.tx == 2));
+}
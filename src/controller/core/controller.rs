@@ -5,14 +5,14 @@ use thiserror::Error;
 use tokio::{select, sync::mpsc, time::sleep};
 
 use crate::{
-    bus::CanInterfaceTrait,
+    bus::{recv_poll_any, CanBus, CanInterfaceTrait, CanStats},
     controller::{
         caniot_controller::caniot_devices_controller::{
             CaniotControllerError, CaniotDevicesController,
         },
         copro_controller::CoproController,
         handle::{self, ControllerMessage},
-        CaniotConfig,
+        CaniotConfig, DeviceAlert,
     },
     coprocessor::CoproHandle,
     database::Storage,
@@ -51,7 +51,7 @@ const API_CHANNEL_SIZE: u32 = 10;
 
 impl<IF: CanInterfaceTrait> Controller<IF> {
     pub(crate) fn new(
-        iface: IF,
+        buses: Vec<CanBus<IF>>,
         caniot_config: CaniotConfig,
         copro_handle: CoproHandle,
         storage: Arc<Storage>,
@@ -64,7 +64,7 @@ impl<IF: CanInterfaceTrait> Controller<IF> {
         );
 
         Ok(Self {
-            caniot: CaniotDevicesController::new(iface, caniot_config, storage)?,
+            caniot: CaniotDevicesController::new(buses, caniot_config, storage)?,
             handle: handle::ControllerHandle::new(sender),
             copro: CoproController::new(copro_handle)?,
             receiver,
@@ -86,22 +86,27 @@ impl<IF: CanInterfaceTrait> Controller<IF> {
 
             let sleep_time = self.caniot.loop_process(&sys_now, &utc_now).await;
 
-            let tunnel_poll_rx = self.caniot.tunnel_poll_tx();
+            // Frames to be sent to the bus from the tunnel, if established
+            #[cfg(feature = "can-tunnel")]
+            let tunnel_poll_tx = self.caniot.tunnel_server.poll_tx();
+            #[cfg(not(feature = "can-tunnel"))]
+            let tunnel_poll_tx = futures::future::pending::<
+                Option<(crate::bus::CanBusId, socketcan::CanDataFrame)>,
+            >();
 
             select! {
                 Some(message) = self.receiver.recv() => {
                     let _ = self.handle_api_message(message).await;
                 },
-                Some(frame) = self.caniot.iface.recv_poll() => {
-                    self.caniot.handle_can_frame(frame).await;
+                Some((bus, frame)) = recv_poll_any(&mut self.caniot.buses) => {
+                    self.caniot.handle_can_frame(bus, frame).await;
                 },
                 Some(copro_message) = self.copro.poll_message() => {
                     self.copro.handle_message(copro_message).await;
                 },
-                Some(frame) = tunnel_poll_rx => {
-                    // If frame is received from tunnel, send it to the bus
-                    #[cfg(feature = "can-tunnel")]
-                    let _ = self.caniot.iface.send(frame).await;
+                Some((bus, frame)) = tunnel_poll_tx => {
+                    // If frame is received from tunnel, send it to its bus
+                    self.caniot.handle_tunnel_frame(bus, frame);
                 },
                 _ = sleep(sleep_time) => {
                     // Timeout of pending queries handled in handle_pending_queries_timeout()
@@ -124,13 +129,50 @@ impl<IF: CanInterfaceTrait> Controller<IF> {
         self.stats.api_rx += 1;
         match message {
             ControllerMessage::GetStats { respond_to } => {
+                let buses: Vec<_> = self.caniot.buses.iter().map(CanBus::get_stats).collect();
+                let mut can = CanStats::default();
+                for bus in buses.iter() {
+                    can += &bus.stats;
+                }
+
                 let stats = ControllerStats {
                     caniot: self.caniot.stats,
                     core: self.stats,
-                    can: self.caniot.iface.get_stats(),
+                    can,
+                    buses,
                 };
                 let _ = respond_to.send(stats);
             }
+            ControllerMessage::GetCanAlert { respond_to } => {
+                // Most severe alert of all buses
+                let now = Utc::now();
+                let alert = self
+                    .caniot
+                    .buses
+                    .iter()
+                    .map(|bus| bus.get_alert(&now))
+                    .reduce(|a, b| if b.cmp_severity(&a).is_gt() { b } else { a });
+                let _ = respond_to.send(alert);
+            }
+            ControllerMessage::GetSniffedFrames { clear, respond_to } => {
+                let frames = self
+                    .caniot
+                    .buses
+                    .iter()
+                    .filter_map(CanBus::get_sniffed_frames)
+                    .collect();
+                if clear {
+                    for sniffer in self
+                        .caniot
+                        .buses
+                        .iter_mut()
+                        .filter_map(|bus| bus.sniffer.as_mut())
+                    {
+                        sniffer.clear();
+                    }
+                }
+                let _ = respond_to.send(frames);
+            }
             ControllerMessage::CaniotMessage(caniot_message) => {
                 self.caniot.handle_api_message(caniot_message).await?;
             }
@@ -3,7 +3,10 @@ use std::sync::Arc;
 use tokio::{runtime::Runtime, sync::broadcast::Sender};
 
 use crate::{
-    bus::CanInterfaceTrait, config::AppConfig, coprocessor::Coprocessor, database::Storage,
+    bus::{CanBus, CanInterfaceTrait},
+    config::AppConfig,
+    coprocessor::Coprocessor,
+    database::Storage,
     shutdown::Shutdown,
 };
 
@@ -15,16 +18,23 @@ pub fn init<IF: CanInterfaceTrait>(
     storage: &Arc<Storage>,
     notify_shutdown: &Sender<()>,
 ) -> Controller<IF> {
-    let can_iface = rt
-        .block_on(IF::new(&config.can))
-        .expect("Failed to create CAN interface");
+    let can_buses = config
+        .can
+        .iter()
+        .map(|can_config| {
+            rt.block_on(CanBus::<IF>::open(can_config))
+                .unwrap_or_else(|err| {
+                    panic!("Failed to open CAN bus {}: {}", can_config.get_name(), err)
+                })
+        })
+        .collect();
 
     let (coprocessor, copro_handle) = Coprocessor::new(config.copro.clone());
 
     rt.spawn(coprocessor.run());
 
     Controller::new(
-        can_iface,
+        can_buses,
         config.caniot.clone(),
         copro_handle,
         storage.clone(),
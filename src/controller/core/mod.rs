@@ -5,6 +5,9 @@ pub mod controller;
 pub mod init;
 pub mod stats;
 
+#[cfg(all(test, feature = "can-tunnel"))]
+mod can_tunnel_test;
+
 pub use alert::{cmp_severity, DeviceAlert, DeviceAlertType};
 
 pub use stats::*;
@@ -1,9 +1,9 @@
 use serde::Serialize;
 
 use crate::{
-    bus::CanStats,
+    bus::{CanBusStats, CanStats},
     controller::caniot_controller::stats::CaniotControllerStats,
-    utils::{PrometheusExporterTrait, PrometheusNoLabel},
+    utils::{BusLabel, PrometheusExporterTrait, PrometheusNoLabel},
 };
 
 #[derive(Serialize, Debug, Clone, Copy, Default)]
@@ -13,18 +13,20 @@ pub struct ControllerCoreStats {
     pub loop_runs: u64, // Number of times the controller loop has been executed
 }
 
-#[derive(Serialize, Debug, Clone, Copy, Default)]
+#[derive(Serialize, Debug, Clone, Default)]
 pub struct ControllerStats {
     pub caniot: CaniotControllerStats,
     pub core: ControllerCoreStats,
+    // Sum of all buses stats
     pub can: CanStats,
+    pub buses: Vec<CanBusStats>,
 }
 
 impl<'a> PrometheusExporterTrait<'a> for ControllerStats {
     type Label = PrometheusNoLabel;
 
     fn export(&self, _labels: impl AsRef<[&'a Self::Label]>) -> String {
-        format!(
+        let mut buf = format!(
             "controller_caniot_iface_rx {}\n\
             controller_caniot_iface_tx {}\n\
             controller_caniot_iface_err {}\n\
@@ -35,11 +37,7 @@ impl<'a> PrometheusExporterTrait<'a> for ControllerStats {
             controller_caniot_pq_answered {}\n\
             controller_caniot_pq_duplicate_dropped {}\n\
             controller_api_rx {}\n\
-            controller_loop_runs {}\n\
-            bus_can_rx {}\n\
-            bus_can_tx {}\n\
-            bus_can_err {}\n\
-            bus_can_unhandled {}\n",
+            controller_loop_runs {}\n",
             self.caniot.iface_rx,
             self.caniot.iface_tx,
             self.caniot.iface_err,
@@ -51,10 +49,16 @@ impl<'a> PrometheusExporterTrait<'a> for ControllerStats {
             self.caniot.pq_duplicate_dropped,
             self.core.api_rx,
             self.core.loop_runs,
-            self.can.rx,
-            self.can.tx,
-            self.can.err,
-            self.can.unhandled,
-        )
+        );
+
+        // Buses are exported individually, the sum is left to the queries
+        for bus in self.buses.iter() {
+            let label = BusLabel::Bus(bus.name.clone());
+            buf.push_str(&bus.stats.export(&[&label]));
+            buf.push_str(&bus.tx_queue.export(&[&label]));
+            buf.push_str(&bus.health.export(&[&label]));
+        }
+
+        buf
     }
 }
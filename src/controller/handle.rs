@@ -3,10 +3,16 @@ use std::ops::Deref;
 use as_any::Downcast;
 use chrono::{DateTime, Utc};
 
+#[cfg(feature = "can-tunnel")]
+use socketcan::CanDataFrame;
 use tokio::sync::{mpsc, oneshot};
 
+#[cfg(feature = "can-tunnel")]
+use crate::bus::CanRxFrame;
+use crate::bus::{CanBusId, CanBusSniffedFrames};
 use crate::caniot::{self as ct, DeviceId};
-#[cfg(feature = "emu")]
+#[cfg(feature = "can-tunnel")]
+use crate::controller::core::can_tunnel::{CanTunnelConfig, CanTunnelId, CanTunnelInfos};
 use crate::grpcserver::EmuRequest;
 use serde::Serialize;
 
@@ -18,7 +24,7 @@ use super::copro_controller::device::BleDevice;
 use super::{
     caniot_controller::{
         api_message::CaniotApiMessage, caniot_devices_controller::CaniotControllerError,
-        device_filter::DeviceFilter,
+        device_filter::DeviceFilter, pending_discovery::DiscoveryReport,
     },
     copro_controller::api_message::CoproApiMessage,
     ActionTrait, ControllerStats, DeviceAction, DeviceActionResult, DeviceAlert, DeviceInfos,
@@ -29,6 +35,13 @@ pub enum ControllerMessage {
     GetStats {
         respond_to: oneshot::Sender<ControllerStats>,
     },
+    GetCanAlert {
+        respond_to: oneshot::Sender<Option<DeviceAlert>>,
+    },
+    GetSniffedFrames {
+        clear: bool,
+        respond_to: oneshot::Sender<Vec<CanBusSniffedFrames>>,
+    },
     CaniotMessage(CaniotApiMessage),
     CoprocessorMessage(CoproApiMessage),
 }
@@ -72,6 +85,118 @@ impl ControllerHandle {
         .await
     }
 
+    /// Send a broadcast request and collect the responses of all devices (one per device
+    /// of each bus) received within the window, with the bus they were received on
+    pub async fn caniot_broadcast_request(
+        &self,
+        frame: ct::Request,
+        window_ms: Option<u32>,
+    ) -> Result<Vec<(CanBusId, ct::Response)>, CaniotControllerError> {
+        self.caniot_query(|respond_to| {
+            CaniotApiMessage::BroadcastQuery {
+                query: frame,
+                window_ms,
+                respond_to,
+            }
+            .into()
+        })
+        .await
+    }
+
+    /// Scan the bus for devices, all device ids are probed one after the other
+    pub async fn caniot_discover(
+        &self,
+        probe_timeout_ms: Option<u32>,
+        interval_ms: Option<u32>,
+    ) -> Result<DiscoveryReport, CaniotControllerError> {
+        self.caniot_query(|respond_to| {
+            CaniotApiMessage::Discover {
+                probe_timeout_ms,
+                interval_ms,
+                respond_to,
+            }
+            .into()
+        })
+        .await
+    }
+
+    /// Read an attribute and decode its value according to the attribute type
+    pub async fn read_attribute_typed(
+        &self,
+        did: DeviceId,
+        attribute: ct::Attribute,
+        timeout_ms: Option<u32>,
+    ) -> Result<ct::AttributeValue, CaniotControllerError> {
+        if !attribute.is_available_for_class(did.class) {
+            return Err(CaniotControllerError::AttributeWrongClass(
+                attribute, did.class,
+            ));
+        }
+
+        let request = ct::build_attribute_read_request(did, attribute.key());
+        let response = self.caniot_device_request(request, timeout_ms).await?;
+        Self::decode_attribute_response(attribute, response)
+    }
+
+    /// Encode and write an attribute, returns the value acknowledged by the device
+    pub async fn write_attribute_typed(
+        &self,
+        did: DeviceId,
+        attribute: ct::Attribute,
+        value: &ct::AttributeValue,
+        timeout_ms: Option<u32>,
+    ) -> Result<ct::AttributeValue, CaniotControllerError> {
+        if !attribute.is_writable() {
+            return Err(CaniotControllerError::AttributeReadOnly(attribute));
+        } else if !attribute.is_available_for_class(did.class) {
+            return Err(CaniotControllerError::AttributeWrongClass(
+                attribute, did.class,
+            ));
+        }
+
+        let raw = attribute.encode(value)?;
+        let request = ct::build_attribute_write_request(did, attribute.key(), raw);
+        let response = self.caniot_device_request(request, timeout_ms).await?;
+        Self::decode_attribute_response(attribute, response)
+    }
+
+    /// Read a multi-part attribute (e.g. Name, BuildCommit) as a whole string
+    pub async fn read_attribute_string(
+        &self,
+        did: DeviceId,
+        attribute: ct::Attribute,
+        timeout_ms: Option<u32>,
+    ) -> Result<String, CaniotControllerError> {
+        if !attribute.is_available_for_class(did.class) {
+            return Err(CaniotControllerError::AttributeWrongClass(
+                attribute, did.class,
+            ));
+        }
+
+        self.caniot_query(|respond_to| {
+            CaniotApiMessage::ReadStringAttribute {
+                did,
+                attribute,
+                timeout_ms,
+                respond_to,
+            }
+            .into()
+        })
+        .await
+    }
+
+    fn decode_attribute_response(
+        attribute: ct::Attribute,
+        response: ct::Response,
+    ) -> Result<ct::AttributeValue, CaniotControllerError> {
+        match response.data {
+            ct::ResponseData::Attribute { key, value } if key == attribute.key() => {
+                Ok(attribute.decode(value)?)
+            }
+            _ => Err(CaniotControllerError::UnexpectedResponse),
+        }
+    }
+
     /// Query a controller message
     ///
     /// Create a one-shot channel, embed it in a message using the provided closure, and send the
@@ -94,6 +219,17 @@ impl ControllerHandle {
             .await
     }
 
+    pub async fn get_can_alert(&self) -> Option<DeviceAlert> {
+        self.caniot_query(|respond_to| ControllerMessage::GetCanAlert { respond_to })
+            .await
+    }
+
+    /// Frames kept by the buses in sniff mode, cleared once read if clear is set
+    pub async fn get_sniffed_frames(&self, clear: bool) -> Vec<CanBusSniffedFrames> {
+        self.caniot_query(|respond_to| ControllerMessage::GetSniffedFrames { clear, respond_to })
+            .await
+    }
+
     pub async fn get_caniot_devices_infos_list(&self) -> Vec<DeviceInfos> {
         self.caniot_query(|respond_to| {
             CaniotApiMessage::GetDevices {
@@ -185,7 +321,6 @@ impl ControllerHandle {
         // Err(ControllerError::NotImplemented)
     }
 
-    #[cfg(feature = "emu")]
     pub async fn send_caniot_emulation_request(&self, event: EmuRequest) {
         debug!("Sending emulation request to controller: {:?}", event);
         self.sender
@@ -194,6 +329,40 @@ impl ControllerHandle {
             .expect("Failed to send emulation request to controller");
     }
 
+    /// Forward the frames received on the bus matching the filter of the tunnel
+    /// to rx_queue and send frames from tx_queue to the bus, until either queue
+    /// is closed or the tunnel is closed with close_can_tunnel()
+    #[cfg(feature = "can-tunnel")]
+    pub async fn establish_can_tunnel(
+        &self,
+        config: CanTunnelConfig,
+        rx_queue: mpsc::Sender<CanRxFrame>,
+        tx_queue: mpsc::Receiver<CanDataFrame>,
+    ) -> Result<CanTunnelId, CaniotControllerError> {
+        self.caniot_query(|respond_to| {
+            CaniotApiMessage::EstablishCanTunnel {
+                config,
+                rx_queue,
+                tx_queue,
+                respond_to,
+            }
+            .into()
+        })
+        .await
+    }
+
+    #[cfg(feature = "can-tunnel")]
+    pub async fn get_can_tunnels(&self) -> Vec<CanTunnelInfos> {
+        self.caniot_query(|respond_to| CaniotApiMessage::GetCanTunnels { respond_to }.into())
+            .await
+    }
+
+    #[cfg(feature = "can-tunnel")]
+    pub async fn close_can_tunnel(&self, id: CanTunnelId) -> Result<(), CaniotControllerError> {
+        self.caniot_query(|respond_to| CaniotApiMessage::CloseCanTunnel { id, respond_to }.into())
+            .await
+    }
+
     pub async fn reset_caniot_devices_settings(&self) -> Result<(), CaniotControllerError> {
         self.caniot_query(|respond_to| CaniotApiMessage::DevicesResetSettings { respond_to }.into())
             .await
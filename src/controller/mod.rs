@@ -13,3 +13,6 @@ pub use config::*;
 pub use core::init::init;
 pub use core::*;
 pub use handle::ControllerHandle;
+
+#[cfg(test)]
+mod config_test;
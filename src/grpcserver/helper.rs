@@ -11,6 +11,11 @@ pub fn utc_to_prost_timestamp(dt: &DateTime<Utc>) -> Timestamp {
     }
 }
 
+#[allow(dead_code)]
+pub fn prost_timestamp_to_utc(ts: &Timestamp) -> Option<DateTime<Utc>> {
+    DateTime::from_timestamp(ts.seconds, u32::try_from(ts.nanos).ok()?)
+}
+
 pub fn local_to_prost_timestamp(dt: &DateTime<Local>) -> Timestamp {
     let ts = dt.timestamp_nanos_opt().unwrap_or_default();
 
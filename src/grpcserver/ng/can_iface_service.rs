@@ -1,15 +1,28 @@
 // Help for implementing the streams RPC: https://github.com/hyperium/tonic/blob/master/examples/routeguide-tutorial.md#creating-the-server
 
-use tokio_stream::{wrappers::ReceiverStream, Stream};
+use socketcan::CanDataFrame;
+use tokio::{select, sync::mpsc};
+use tokio_stream::wrappers::ReceiverStream;
 use tonic::{Request, Response, Result, Status, Streaming};
 
-use crate::shared::SharedHandle;
+use crate::{
+    bus::{CanBusId, CanRxFrame},
+    caniot::DeviceId,
+    controller::core::can_tunnel::{
+        CanTunnelConfig, CanTunnelDirection, CanTunnelFilter, CanTunnelQueuePolicy,
+    },
+    grpcserver::utc_to_prost_timestamp,
+    shared::SharedHandle,
+};
 
 use super::model::can_iface::{
     self as m,
     can_iface_service_server::{CanIfaceService, CanIfaceServiceServer},
 };
 
+// Frames buffered in each direction of the tunnel
+const CAN_TUNNEL_QUEUE_SIZE: usize = 64;
+
 #[derive(Debug)]
 pub struct NgCanIface {
     pub shared: SharedHandle,
@@ -17,6 +30,81 @@ pub struct NgCanIface {
 
 type RxStream = ReceiverStream<Result<m::RxCanFrame, Status>>;
 
+fn get_metadata<'a, T>(request: &'a Request<T>, key: &str) -> Result<Option<&'a str>, Status> {
+    request
+        .metadata()
+        .get(key)
+        .map(|value| {
+            value
+                .to_str()
+                .map_err(|_| Status::invalid_argument(format!("Invalid {} metadata", key)))
+        })
+        .transpose()
+}
+
+// Configuration of the tunnel from the request metadata, see ng_can_iface.proto
+fn get_tunnel_config<T>(request: &Request<T>) -> Result<CanTunnelConfig, Status> {
+    let name = match get_metadata(request, "can-tunnel-name")? {
+        Some(name) => name.to_string(),
+        None => request
+            .remote_addr()
+            .map(|addr| addr.to_string())
+            .unwrap_or_default(),
+    };
+
+    let bus = match get_metadata(request, "can-tunnel-bus")? {
+        Some(bus) => bus
+            .trim()
+            .parse::<CanBusId>()
+            .map_err(|_| Status::invalid_argument(format!("Invalid bus {}", bus)))?,
+        None => 0,
+    };
+
+    let dids = match get_metadata(request, "can-tunnel-did")? {
+        Some(dids) => dids
+            .split(',')
+            .map(|did| {
+                did.trim()
+                    .parse::<u8>()
+                    .ok()
+                    .and_then(|did| DeviceId::try_from_u8(did).ok())
+                    .ok_or_else(|| Status::invalid_argument(format!("Invalid device id {}", did)))
+            })
+            .collect::<Result<Vec<_>, _>>()?,
+        None => vec![],
+    };
+
+    let direction = match get_metadata(request, "can-tunnel-direction")? {
+        None | Some("both") => CanTunnelDirection::Both,
+        Some("query") => CanTunnelDirection::Query,
+        Some("response") => CanTunnelDirection::Response,
+        Some(direction) => {
+            return Err(Status::invalid_argument(format!(
+                "Invalid tunnel direction {}",
+                direction
+            )))
+        }
+    };
+
+    let queue_policy = match get_metadata(request, "can-tunnel-queue-policy")? {
+        None | Some("drop") => CanTunnelQueuePolicy::Drop,
+        Some("close") => CanTunnelQueuePolicy::Close,
+        Some(policy) => {
+            return Err(Status::invalid_argument(format!(
+                "Invalid tunnel queue policy {}",
+                policy
+            )))
+        }
+    };
+
+    Ok(CanTunnelConfig {
+        name,
+        bus,
+        filter: CanTunnelFilter { dids, direction },
+        queue_policy,
+    })
+}
+
 #[tonic::async_trait]
 impl CanIfaceService for NgCanIface {
     type IfaceStream = RxStream;
@@ -25,7 +113,70 @@ impl CanIfaceService for NgCanIface {
         &self,
         request: Request<Streaming<m::TxCanFrame>>,
     ) -> Result<Response<Self::IfaceStream>, Status> {
-        todo!()
+        let config = get_tunnel_config(&request)?;
+        let mut tx_stream = request.into_inner();
+
+        let (rx_queue, mut rx_queue_receiver) = mpsc::channel::<CanRxFrame>(CAN_TUNNEL_QUEUE_SIZE);
+        let (tx_queue, tx_queue_receiver) = mpsc::channel::<CanDataFrame>(CAN_TUNNEL_QUEUE_SIZE);
+
+        let id = self
+            .shared
+            .controller_handle
+            .establish_can_tunnel(config, rx_queue, tx_queue_receiver)
+            .await
+            .map_err(|err| Status::invalid_argument(err.to_string()))?;
+
+        let (stream_sender, stream_receiver) = mpsc::channel(CAN_TUNNEL_QUEUE_SIZE);
+
+        // Bus -> client, until the controller closes the tunnel or the client goes away
+        tokio::spawn(async move {
+            while let Some(rx_frame) = rx_queue_receiver.recv().await {
+                let frame = m::RxCanFrame {
+                    frame: Some((&rx_frame.frame).into()),
+                    timestamp: Some(utc_to_prost_timestamp(&rx_frame.timestamp)),
+                };
+
+                if stream_sender.send(Ok(frame)).await.is_err() {
+                    break;
+                }
+            }
+        });
+
+        // Client -> bus, tx_queue is dropped when the client closes its stream,
+        // which makes the controller close the tunnel
+        tokio::spawn(async move {
+            loop {
+                let message = select! {
+                    message = tx_stream.message() => message,
+                    _ = tx_queue.closed() => break,
+                };
+
+                match message {
+                    Ok(Some(m::TxCanFrame { frame: Some(frame) })) => {
+                        match CanDataFrame::try_from(&frame) {
+                            Ok(frame) => {
+                                if tx_queue.send(frame).await.is_err() {
+                                    break;
+                                }
+                            }
+                            Err(status) => {
+                                warn!("CanIface: dropped invalid frame: {}", status.message())
+                            }
+                        }
+                    }
+                    Ok(Some(_)) => warn!("CanIface: dropped empty frame"),
+                    Ok(None) => break,
+                    Err(status) => {
+                        warn!("CanIface: client stream error: {}", status);
+                        break;
+                    }
+                }
+            }
+
+            info!("CanIface: tunnel {} client stream closed", id);
+        });
+
+        Ok(Response::new(ReceiverStream::new(stream_receiver)))
     }
 }
 
@@ -4,6 +4,7 @@ use tonic::{Request, Response, Result, Status};
 
 use crate::caniot::{self};
 use crate::controller::caniot_controller::caniot_devices_controller::CaniotControllerError;
+use crate::grpcserver::utc_to_prost_timestamp;
 use crate::shared::SharedHandle;
 
 use super::model::controller::{
@@ -27,6 +28,76 @@ where
     bytes
 }
 
+fn error_response(did: caniot::DeviceId, status: m::Status) -> m::Response {
+    m::Response {
+        did: Some(did.into()),
+        status: status as i32,
+        ..Default::default()
+    }
+}
+
+fn into_model_error(code: caniot::ErrorCode, source: &caniot::ErrorSource) -> m::DeviceError {
+    let (endpoint, key) = match source {
+        caniot::ErrorSource::Telemetry(endpoint, _) => (Some(*endpoint as i32), None),
+        caniot::ErrorSource::Attribute(key) => (None, key.map(|key| key as u32)),
+    };
+    m::DeviceError {
+        code: code.value(),
+        endpoint,
+        key,
+    }
+}
+
+fn device_error_response(
+    did: caniot::DeviceId,
+    code: caniot::ErrorCode,
+    source: &caniot::ErrorSource,
+) -> m::Response {
+    m::Response {
+        did: Some(did.into()),
+        status: m::Status::DeviceError as i32,
+        error: Some(into_model_error(code, source)),
+        ..Default::default()
+    }
+}
+
+fn into_model_response(response: &caniot::Response) -> m::Response {
+    // A node answering a broadcast with an error frame
+    if let Some(CaniotControllerError::DeviceReturnedError { code, source }) =
+        CaniotControllerError::from_response(&response.data)
+    {
+        return m::Response {
+            timestamp: Some(utc_to_prost_timestamp(&response.timestamp)),
+            ..device_error_response(response.device_id, code, &source)
+        };
+    }
+
+    let data = match &response.data {
+        caniot::ResponseData::Telemetry { endpoint, payload } => {
+            Some(m::response::Response::Telemetry(m::Telemetry {
+                endpoint: *endpoint as i32,
+                payload: payload.as_ref().iter().map(|b| *b as u32).collect(),
+            }))
+        }
+        caniot::ResponseData::Attribute { key, value } => {
+            Some(m::response::Response::Attribute(m::Attribute {
+                key: *key as u32,
+                value: *value,
+            }))
+        }
+        // Error frames are reported above
+        caniot::ResponseData::Error { .. } => None,
+    };
+
+    m::Response {
+        did: Some(response.device_id.into()),
+        status: m::Status::Ok as i32,
+        timestamp: Some(utc_to_prost_timestamp(&response.timestamp)),
+        response: data,
+        ..Default::default()
+    }
+}
+
 #[tonic::async_trait]
 impl ControllerService for NgController {
     async fn query(&self, request: Request<m::Request>) -> Result<Response<m::Response>, Status> {
@@ -51,50 +122,119 @@ impl ControllerService for NgController {
             },
         };
 
+        if caniot_did.is_broadcast() {
+            let reply = self
+                .shared
+                .controller_handle
+                .caniot_broadcast_request(query, req.timeout)
+                .await;
+
+            return Ok(Response::new(match reply {
+                Ok(responses) => m::Response {
+                    did: Some(caniot_did.into()),
+                    status: m::Status::Ok as i32,
+                    responses: responses
+                        .iter()
+                        .map(|(bus, response)| m::Response {
+                            bus: *bus as u32,
+                            ..into_model_response(response)
+                        })
+                        .collect(),
+                    ..Default::default()
+                },
+                Err(_) => error_response(caniot_did, m::Status::Nok),
+            }));
+        }
+
         let reply = self
             .shared
             .controller_handle
             .caniot_device_request(query, req.timeout)
             .await;
 
-        // Handle request error
-        if let Err(err) = reply {
-            return Ok(Response::new(m::Response {
-                did: Some(caniot_did.into()),
-                response: None,
-                response_time: 0,
-                status: match err {
-                    CaniotControllerError::Timeout => m::Status::Timeout as i32,
-                    _ => m::Status::Nok as i32,
-                },
-                timestamp: None,
-            }));
+        match reply {
+            Ok(response) => Ok(Response::new(into_model_response(&response))),
+            Err(CaniotControllerError::Timeout) => Ok(Response::new(error_response(
+                caniot_did,
+                m::Status::Timeout,
+            ))),
+            Err(CaniotControllerError::DeviceReturnedError { code, source }) => Ok(Response::new(
+                device_error_response(caniot_did, code, &source),
+            )),
+            Err(_) => Ok(Response::new(error_response(caniot_did, m::Status::Nok))),
         }
+    }
 
-        // Handle device response
-        let _response = reply.unwrap();
-
-        // let resp = match result.unwrap().data {
-        //     ResponseData::Telemetry { endpoint, payload } => {}
-        //     ResponseData::Attribute { key, value } => {}
-        //     ResponseData::Error { source, error } => {}
-        // };
+    async fn read_string_attribute(
+        &self,
+        request: Request<m::StringAttributeRequest>,
+    ) -> Result<Response<m::StringAttributeResponse>, Status> {
+        let req = request.into_inner();
+        let did = req
+            .did
+            .ok_or(Status::invalid_argument("Missing device id"))?;
+        let caniot_did = caniot::DeviceId::try_from_u8(did.did as u8)
+            .map_err(|_| Status::invalid_argument("Invalid device id"))?;
+        let attribute = caniot::Attribute::try_from(req.key as u16)
+            .map_err(|_| Status::invalid_argument("Unknown attribute key"))?;
 
-        // let r1 = model::response::Response::Telemetry(model::Telemetry {
-        //     endpoint: 0,
-        //     payload: Vec::new(),
-        // });
+        let reply = self
+            .shared
+            .controller_handle
+            .read_attribute_string(caniot_did, attribute, req.timeout)
+            .await;
 
-        // let r2 = model::response::Response::Attribute(model::Attribute { key: 0, value: 0 });
+        let (status, value) = match reply {
+            Ok(value) => (m::Status::Ok, Some(value)),
+            Err(CaniotControllerError::Timeout) => (m::Status::Timeout, None),
+            Err(_) => (m::Status::Nok, None),
+        };
 
-        Ok(Response::new(m::Response {
+        Ok(Response::new(m::StringAttributeResponse {
             did: Some(caniot_did.into()),
-            response: None,
-            response_time: 0,
-            status: m::Status::Ok as i32,
-            timestamp: None,
+            status: status as i32,
+            value,
         }))
     }
+
+    async fn discover(
+        &self,
+        request: Request<m::DiscoveryRequest>,
+    ) -> Result<Response<m::DiscoveryReport>, Status> {
+        let req = request.into_inner();
+
+        let reply = self
+            .shared
+            .controller_handle
+            .caniot_discover(req.probe_timeout, req.interval)
+            .await;
+
+        let report = match reply {
+            Ok(report) => m::DiscoveryReport {
+                status: m::Status::Ok as i32,
+                started_at: Some(utc_to_prost_timestamp(&report.started_at)),
+                duration: report.duration.as_millis() as u32,
+                probed: report.probed as u32,
+                nodes: report
+                    .nodes
+                    .iter()
+                    .map(|node| m::DiscoveredNode {
+                        did: Some(node.did.into()),
+                        class: node.class as u32,
+                        version: node.version.as_ref().map(ToString::to_string),
+                        rtt: node.rtt.as_micros() as u32,
+                        bus: node.bus as u32,
+                    })
+                    .collect(),
+            },
+            Err(_) => m::DiscoveryReport {
+                status: m::Status::Nok as i32,
+                ..Default::default()
+            },
+        };
+
+        Ok(Response::new(report))
+    }
 }
 
 pub fn get_ng_controller_server(shared: SharedHandle) -> ControllerServiceServer<NgController> {
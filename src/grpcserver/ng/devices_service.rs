@@ -6,7 +6,8 @@ use crate::{
         caniot_controller::auto_attach::{
             DEVICE_GARAGE_DID, DEVICE_HEATERS_DID, DEVICE_OUTDOOR_ALARM_DID,
         },
-        DeviceAction, DeviceActionResult, DeviceInfos,
+        AttributeReadError, AttributeReportEntry, DeviceAction, DeviceActionResult,
+        DeviceAttributesReport, DeviceInfos,
     },
     grpcserver::utc_to_prost_timestamp,
     shared::SharedHandle,
@@ -42,42 +43,81 @@ impl NgDevices {
     }
 }
 
-impl Into<m::Class0Telemetry> for ct::class0::Telemetry {
-    fn into(self) -> m::Class0Telemetry {
-        m::Class0Telemetry {
-            in1: self.in1,
-            in2: self.in2,
-            in3: self.in3,
-            in4: self.in4,
-            oc1: self.oc1,
-            oc2: self.oc2,
-            rl1: self.rl1,
-            rl2: self.rl2,
-            int_temp: self.temp_in.to_celsius(),
-            ext_temp0: self.temp_out[0].to_celsius(),
-            ext_temp1: self.temp_out[1].to_celsius(),
-            ext_temp2: self.temp_out[2].to_celsius(),
+impl Into<m::IoKind> for ct::IoKind {
+    fn into(self) -> m::IoKind {
+        match self {
+            ct::IoKind::Input => m::IoKind::Input,
+            ct::IoKind::Output => m::IoKind::Output,
+            ct::IoKind::InputOutput => m::IoKind::InputOutput,
         }
     }
 }
 
-impl Into<m::Class1Telemetry> for ct::class1::Telemetry {
-    fn into(self) -> m::Class1Telemetry {
-        m::Class1Telemetry {
-            ios: self.ios.to_vec(),
-            int_temp: self.temp_in.to_celsius(),
-            ext_temp0: self.temp_out[0].to_celsius(),
-            ext_temp1: self.temp_out[1].to_celsius(),
-            ext_temp2: self.temp_out[2].to_celsius(),
+impl Into<m::IoSnapshot> for &ct::IoSnapshot {
+    fn into(self) -> m::IoSnapshot {
+        m::IoSnapshot {
+            class: self.class as u32,
+            ios: self
+                .ios
+                .iter()
+                .map(|io| m::IoState {
+                    name: io.name.to_string(),
+                    kind: Into::<m::IoKind>::into(io.kind) as i32,
+                    state: io.state,
+                })
+                .collect(),
+            pulses: self
+                .pulses
+                .iter()
+                .map(|pulse| m::PulseState {
+                    output: pulse.output.to_string(),
+                    active: pulse.active,
+                })
+                .collect(),
+            temperatures: self
+                .temperatures
+                .iter()
+                .map(|sensor| m::TemperatureSensor {
+                    name: sensor.name.to_string(),
+                    value: sensor.value,
+                })
+                .collect(),
         }
     }
 }
 
-impl Into<m::device::Measures> for ct::classes::BoardClassTelemetry {
-    fn into(self) -> m::device::Measures {
-        match self {
-            ct::BoardClassTelemetry::Class0(t) => m::device::Measures::Class0(t.into()),
-            ct::BoardClassTelemetry::Class1(t) => m::device::Measures::Class1(t.into()),
+impl Into<m::AttributeEntry> for &AttributeReportEntry {
+    fn into(self) -> m::AttributeEntry {
+        let result = match &self.value {
+            Ok(value) => m::attribute_entry::Result::Value(value.to_string()),
+            Err(error) => {
+                let (kind, code) = match error {
+                    AttributeReadError::Device(code) => {
+                        (m::attribute_error::Kind::Device, code.map(|c| c.value()))
+                    }
+                    AttributeReadError::Timeout => (m::attribute_error::Kind::Timeout, None),
+                    AttributeReadError::Decode => (m::attribute_error::Kind::Decode, None),
+                };
+                m::attribute_entry::Result::Error(m::AttributeError {
+                    kind: kind as i32,
+                    code,
+                })
+            }
+        };
+
+        m::AttributeEntry {
+            key: self.attribute.key() as u32,
+            name: format!("{:?}", self.attribute),
+            result: Some(result),
+        }
+    }
+}
+
+impl Into<m::AttributesReport> for &DeviceAttributesReport {
+    fn into(self) -> m::AttributesReport {
+        m::AttributesReport {
+            timestamp: Some(utc_to_prost_timestamp(&self.timestamp)),
+            entries: self.entries.iter().map(|entry| entry.into()).collect(),
         }
     }
 }
@@ -113,7 +153,13 @@ impl Into<m::Device> for &DeviceInfos {
             outside_temp_min: self.outside_temp_min,
             outside_temp_max: self.outside_temp_max,
             outside_temp_avg: self.outside_temp_avg,
-            measures: self.measures.map(|m| m.into()),
+            io: self.io.as_ref().map(|io| io.into()),
+            clock_offset: self.clock_offset.map(|offset| offset as i32),
+            clock_synced_at: self.clock_synced_at.as_ref().map(utc_to_prost_timestamp),
+            watchdog_enabled: self.watchdog_enabled,
+            inhibited: self.inhibited,
+            inhibit_remaining: self.inhibit_remaining,
+            bus: self.bus as u32,
             active_alert: self.active_alert.as_ref().map(|a| a.into()),
             ui_view_name: self.ui_view_name.clone(),
             ..Default::default()
@@ -217,6 +263,18 @@ impl CaniotDevicesService for NgDevices {
                     .map_err(|e| Status::invalid_argument(format!("Invalid endpoint: {:?}", e)))?;
                 DeviceAction::Ping(endpoint.into())
             }
+            m::action::Action::ReadAttributes(..) => DeviceAction::ReadAllAttributes,
+            m::action::Action::Watchdog(enable) => DeviceAction::WatchdogControl(enable),
+            m::action::Action::SetOutput(set_output) => {
+                let output = ng::BoardOutput::try_from(set_output.output)
+                    .map_err(|e| Status::invalid_argument(format!("Invalid output: {:?}", e)))?;
+                let xps = ng::Xps::try_from(set_output.xps)
+                    .map_err(|e| Status::invalid_argument(format!("Invalid xps: {:?}", e)))?;
+                DeviceAction::SetOutput {
+                    output: output.try_into()?,
+                    xps: xps.into(),
+                }
+            }
         };
 
         // TODO is it important to compare the result type with the action type to verify they match?
@@ -237,9 +295,13 @@ impl CaniotDevicesService for NgDevices {
             DeviceActionResult::InhibitControlSent => {
                 m::action_result::ActionResult::Inhibit(true) // change
             }
+            DeviceActionResult::WatchdogControlSent => m::action_result::ActionResult::Watchdog(()),
             DeviceActionResult::Pong(response) => {
                 m::action_result::ActionResult::Pong(response.into())
             }
+            DeviceActionResult::AttributesReport(ref report) => {
+                m::action_result::ActionResult::AttributesReport(report.into())
+            }
             _ => {
                 return Err(Status::internal("Invalid action result"));
             }
@@ -5,28 +5,38 @@ use super::model::emulation::{
     emulation_service_server::{EmulationService, EmulationServiceServer},
 };
 
-use crate::shared::SharedHandle;
+use crate::{bus::CanBackend, shared::SharedHandle};
 
 #[derive(Debug)]
 pub struct NgEmulation {
     pub shared: SharedHandle,
 }
 
-fn get_status() -> m::Status {
-    m::Status {
-        feature_enabled: cfg!(feature = "emu"),
+impl NgEmulation {
+    // Emulation is active if at least one bus uses the emulated backend
+    fn is_active(&self) -> bool {
+        self.shared
+            .config
+            .can
+            .iter()
+            .any(|bus| bus.backend == CanBackend::Emulated)
+    }
+
+    fn get_status(&self) -> m::Status {
+        m::Status {
+            feature_enabled: self.is_active(),
+        }
     }
 }
 
 #[tonic::async_trait]
 impl EmulationService for NgEmulation {
     async fn get(&self, _req: Request<()>) -> Result<Response<m::Status>, Status> {
-        Ok(Response::new(get_status()))
+        Ok(Response::new(self.get_status()))
     }
 
     async fn set(&self, req: Request<m::Req>) -> Result<Response<m::Status>, Status> {
-        #[cfg(feature = "emu")]
-        {
+        if self.is_active() {
             let req = req.into_inner();
             let emu_req = m::EmuRequest::try_from(req.event).unwrap_or_default();
             self.shared
@@ -36,7 +46,7 @@ impl EmulationService for NgEmulation {
                 .await;
         }
 
-        Ok(Response::new(get_status()))
+        Ok(Response::new(self.get_status()))
     }
 }
 
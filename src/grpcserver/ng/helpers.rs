@@ -4,6 +4,11 @@ use crate::{
     grpcserver::utc_to_prost_timestamp,
 };
 
+#[cfg(any(feature = "grpc-can-iface-server", feature = "grpc-can-iface-client"))]
+use embedded_can::{ExtendedId, Frame as EmbeddedFrame, Id as EmbeddedId, StandardId};
+#[cfg(any(feature = "grpc-can-iface-server", feature = "grpc-can-iface-client"))]
+use socketcan::CanDataFrame;
+
 use super::model as ng;
 
 impl Into<ng::DeviceId> for ct::DeviceId {
@@ -42,6 +47,58 @@ impl From<ng::TwoStatePulse> for ct::TSP {
     }
 }
 
+impl From<ng::Xps> for ct::Xps {
+    fn from(value: ng::Xps) -> Self {
+        match value {
+            ng::Xps::None => ct::Xps::None,
+            ng::Xps::SetOn => ct::Xps::SetOn,
+            ng::Xps::SetOff => ct::Xps::SetOff,
+            ng::Xps::Toggle => ct::Xps::Toggle,
+            ng::Xps::Reset => ct::Xps::Reset,
+            ng::Xps::PulseOn => ct::Xps::PulseOn,
+            ng::Xps::PulseOff => ct::Xps::PulseOff,
+            ng::Xps::PulseCancel => ct::Xps::PulseCancel,
+        }
+    }
+}
+
+impl TryFrom<ng::BoardOutput> for ct::BoardOutput {
+    type Error = tonic::Status;
+
+    fn try_from(value: ng::BoardOutput) -> Result<Self, Self::Error> {
+        use ct::{class0::Output, class1::Pin};
+        let output = match value {
+            ng::BoardOutput::Unspecified => {
+                return Err(tonic::Status::invalid_argument("Unspecified output"))
+            }
+            ng::BoardOutput::Oc1 => ct::BoardOutput::Class0(Output::Oc1),
+            ng::BoardOutput::Oc2 => ct::BoardOutput::Class0(Output::Oc2),
+            ng::BoardOutput::Rl1 => ct::BoardOutput::Class0(Output::Rl1),
+            ng::BoardOutput::Rl2 => ct::BoardOutput::Class0(Output::Rl2),
+            ng::BoardOutput::Pc0 => ct::BoardOutput::Class1(Pin::Pc0),
+            ng::BoardOutput::Pc1 => ct::BoardOutput::Class1(Pin::Pc1),
+            ng::BoardOutput::Pc2 => ct::BoardOutput::Class1(Pin::Pc2),
+            ng::BoardOutput::Pc3 => ct::BoardOutput::Class1(Pin::Pc3),
+            ng::BoardOutput::Pd0 => ct::BoardOutput::Class1(Pin::Pd0),
+            ng::BoardOutput::Pd1 => ct::BoardOutput::Class1(Pin::Pd1),
+            ng::BoardOutput::Pd2 => ct::BoardOutput::Class1(Pin::Pd2),
+            ng::BoardOutput::Pd3 => ct::BoardOutput::Class1(Pin::Pd3),
+            ng::BoardOutput::Pei0 => ct::BoardOutput::Class1(Pin::Pei0),
+            ng::BoardOutput::Pei1 => ct::BoardOutput::Class1(Pin::Pei1),
+            ng::BoardOutput::Pei2 => ct::BoardOutput::Class1(Pin::Pei2),
+            ng::BoardOutput::Pei3 => ct::BoardOutput::Class1(Pin::Pei3),
+            ng::BoardOutput::Pei4 => ct::BoardOutput::Class1(Pin::Pei4),
+            ng::BoardOutput::Pei5 => ct::BoardOutput::Class1(Pin::Pei5),
+            ng::BoardOutput::Pei6 => ct::BoardOutput::Class1(Pin::Pei6),
+            ng::BoardOutput::Pei7 => ct::BoardOutput::Class1(Pin::Pei7),
+            ng::BoardOutput::Pb0 => ct::BoardOutput::Class1(Pin::Pb0),
+            ng::BoardOutput::Pe0 => ct::BoardOutput::Class1(Pin::Pe0),
+            ng::BoardOutput::Pe1 => ct::BoardOutput::Class1(Pin::Pe1),
+        };
+        Ok(output)
+    }
+}
+
 impl Into<ng::CaniotFrame> for ct::Response {
     fn into(self) -> ng::CaniotFrame {
         ng::CaniotFrame {
@@ -80,3 +137,41 @@ impl Into<ng::DeviceAlert> for &DeviceAlert {
         }
     }
 }
+
+#[cfg(any(feature = "grpc-can-iface-server", feature = "grpc-can-iface-client"))]
+impl From<&CanDataFrame> for ng::can_iface::CanFrame {
+    fn from(frame: &CanDataFrame) -> Self {
+        let (is_extended, id) = match frame.id() {
+            EmbeddedId::Standard(id) => (false, id.as_raw() as u32),
+            EmbeddedId::Extended(id) => (true, id.as_raw()),
+        };
+
+        ng::can_iface::CanFrame {
+            is_extended,
+            id,
+            payload: frame.data().to_vec(),
+        }
+    }
+}
+
+#[cfg(any(feature = "grpc-can-iface-server", feature = "grpc-can-iface-client"))]
+impl TryFrom<&ng::can_iface::CanFrame> for CanDataFrame {
+    type Error = tonic::Status;
+
+    fn try_from(frame: &ng::can_iface::CanFrame) -> Result<Self, Self::Error> {
+        let id: EmbeddedId = if frame.is_extended {
+            ExtendedId::new(frame.id)
+                .ok_or_else(|| tonic::Status::invalid_argument("Invalid extended CAN id"))?
+                .into()
+        } else {
+            u16::try_from(frame.id)
+                .ok()
+                .and_then(StandardId::new)
+                .ok_or_else(|| tonic::Status::invalid_argument("Invalid standard CAN id"))?
+                .into()
+        };
+
+        CanDataFrame::new(id, &frame.payload)
+            .ok_or_else(|| tonic::Status::invalid_argument("Invalid CAN payload"))
+    }
+}
@@ -9,7 +9,12 @@ use super::model::internal::{
     internal_service_server::{InternalService, InternalServiceServer},
 };
 
+#[cfg(feature = "can-tunnel")]
+use crate::controller::core::can_tunnel::{
+    CanTunnelDirection, CanTunnelInfos, CanTunnelQueuePolicy,
+};
 use crate::{
+    bus::{CanBusHealth, CanBusSniffedFrames, CanBusState, CanSniffedFrame},
     controller::ControllerStats,
     grpcserver::{systemtime_to_prost_timestamp, utc_to_prost_timestamp},
     internal::{
@@ -70,6 +75,88 @@ impl Into<m::FirmwareInfos> for &FirmwareInfos {
     }
 }
 
+impl Into<m::CanBusHealth> for &CanBusHealth {
+    fn into(self) -> m::CanBusHealth {
+        m::CanBusHealth {
+            state: match self.state {
+                CanBusState::ErrorActive => m::CanBusState::ErrorActive,
+                CanBusState::ErrorWarning => m::CanBusState::ErrorWarning,
+                CanBusState::ErrorPassive => m::CanBusState::ErrorPassive,
+                CanBusState::BusOff => m::CanBusState::BusOff,
+            } as i32,
+            tx_error_counter: self.tx_error_counter as u32,
+            rx_error_counter: self.rx_error_counter as u32,
+            last_no_ack: self.last_no_ack.as_ref().map(utc_to_prost_timestamp),
+            last_error: self.last_error.as_ref().map(utc_to_prost_timestamp),
+            err_tx_timeout: self.errors.tx_timeout as u32,
+            err_lost_arbitration: self.errors.lost_arbitration as u32,
+            err_controller: self.errors.controller as u32,
+            err_protocol: self.errors.protocol as u32,
+            err_transceiver: self.errors.transceiver as u32,
+            err_no_ack: self.errors.no_ack as u32,
+            err_bus_off: self.errors.bus_off as u32,
+            err_bus_error: self.errors.bus_error as u32,
+            err_restarted: self.errors.restarted as u32,
+            err_overflow: self.errors.overflow as u32,
+        }
+    }
+}
+
+#[cfg(feature = "can-tunnel")]
+impl Into<m::CanTunnel> for &CanTunnelInfos {
+    fn into(self) -> m::CanTunnel {
+        m::CanTunnel {
+            id: self.id,
+            name: self.config.name.clone(),
+            bus: self.config.bus as u32,
+            dids: self
+                .config
+                .filter
+                .dids
+                .iter()
+                .map(|did| did.to_u8() as u32)
+                .collect(),
+            direction: match self.config.filter.direction {
+                CanTunnelDirection::Both => m::CanTunnelDirection::Both,
+                CanTunnelDirection::Query => m::CanTunnelDirection::Query,
+                CanTunnelDirection::Response => m::CanTunnelDirection::Response,
+            } as i32,
+            queue_policy: match self.config.queue_policy {
+                CanTunnelQueuePolicy::Drop => m::CanTunnelQueuePolicy::Drop,
+                CanTunnelQueuePolicy::Close => m::CanTunnelQueuePolicy::Close,
+            } as i32,
+            established_at: Some(utc_to_prost_timestamp(&self.established_at)),
+            rx: self.stats.rx as u32,
+            tx: self.stats.tx as u32,
+            filtered: self.stats.filtered as u32,
+            dropped: self.stats.dropped as u32,
+        }
+    }
+}
+
+impl Into<m::SniffedFrame> for &CanSniffedFrame {
+    fn into(self) -> m::SniffedFrame {
+        m::SniffedFrame {
+            timestamp: Some(utc_to_prost_timestamp(&self.timestamp)),
+            id: self.id,
+            extended: self.extended,
+            data: self.data.clone(),
+            error: self.error.clone(),
+        }
+    }
+}
+
+impl Into<m::BusSniffedFrames> for &CanBusSniffedFrames {
+    fn into(self) -> m::BusSniffedFrames {
+        m::BusSniffedFrames {
+            name: self.name.clone(),
+            sniffed: self.stats.sniffed as u32,
+            overwritten: self.stats.overwritten as u32,
+            frames: self.frames.iter().map(Into::into).collect(),
+        }
+    }
+}
+
 impl Into<m::ControllerStats> for &ControllerStats {
     fn into(self) -> m::ControllerStats {
         m::ControllerStats {
@@ -88,6 +175,24 @@ impl Into<m::ControllerStats> for &ControllerStats {
             can_tx: self.can.tx as u32,
             can_err: self.can.err as u32,
             can_unhandled: self.can.unhandled as u32,
+            buses: self
+                .buses
+                .iter()
+                .map(|bus| m::CanBusStats {
+                    name: bus.name.clone(),
+                    rx: bus.stats.rx as u32,
+                    tx: bus.stats.tx as u32,
+                    err: bus.stats.err as u32,
+                    unhandled: bus.stats.unhandled as u32,
+                    tx_queue_depth: bus.tx_queue.depth as u32,
+                    tx_queue_max_depth: bus.tx_queue.max_depth as u32,
+                    tx_dropped: bus.tx_queue.dropped as u32,
+                    tx_retries: bus.tx_queue.retries as u32,
+                    bus_off: bus.tx_queue.bus_off as u32,
+                    restarts: bus.tx_queue.restarts as u32,
+                    health: Some((&bus.health).into()),
+                })
+                .collect(),
         }
     }
 }
@@ -205,6 +310,76 @@ impl InternalService for NgInternal {
         ))
     }
 
+    async fn get_can_alert(&self, _request: Request<()>) -> Result<Response<m::CanAlert>, Status> {
+        let alert = self.shared.controller_handle.get_can_alert().await;
+
+        Ok(Response::new(m::CanAlert {
+            active_alert: alert.as_ref().map(|a| a.into()),
+        }))
+    }
+
+    async fn get_sniffed_frames(
+        &self,
+        request: Request<m::GetSniffedFramesRequest>,
+    ) -> Result<Response<m::SniffedFrames>, Status> {
+        let clear = request.into_inner().clear;
+        let buses = self
+            .shared
+            .controller_handle
+            .get_sniffed_frames(clear)
+            .await;
+
+        Ok(Response::new(m::SniffedFrames {
+            buses: buses.iter().map(Into::into).collect(),
+        }))
+    }
+
+    #[cfg(feature = "can-tunnel")]
+    async fn get_can_tunnels(
+        &self,
+        _request: Request<()>,
+    ) -> Result<Response<m::CanTunnels>, Status> {
+        let tunnels = self.shared.controller_handle.get_can_tunnels().await;
+
+        Ok(Response::new(m::CanTunnels {
+            tunnels: tunnels.iter().map(Into::into).collect(),
+        }))
+    }
+
+    #[cfg(not(feature = "can-tunnel"))]
+    async fn get_can_tunnels(
+        &self,
+        _request: Request<()>,
+    ) -> Result<Response<m::CanTunnels>, Status> {
+        Ok(Response::new(m::CanTunnels { tunnels: vec![] }))
+    }
+
+    #[cfg(feature = "can-tunnel")]
+    async fn close_can_tunnel(
+        &self,
+        request: Request<m::CanTunnelId>,
+    ) -> Result<Response<()>, Status> {
+        let id = request.into_inner().id;
+
+        self.shared
+            .controller_handle
+            .close_can_tunnel(id)
+            .await
+            .map_err(|err| Status::not_found(err.to_string()))?;
+
+        Ok(Response::new(()))
+    }
+
+    #[cfg(not(feature = "can-tunnel"))]
+    async fn close_can_tunnel(
+        &self,
+        _request: Request<m::CanTunnelId>,
+    ) -> Result<Response<()>, Status> {
+        Err(Status::unimplemented(
+            "CAN tunnels not supported by this build",
+        ))
+    }
+
     async fn reset_stats(
         &self,
         request: Request<m::ResetStatsRequest>,
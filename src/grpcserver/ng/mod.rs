@@ -69,3 +69,6 @@ mod model {
 }
 
 pub use model::emulation::EmuRequest;
+
+#[cfg(feature = "grpc-can-iface-client")]
+pub use model::can_iface;
@@ -64,6 +64,7 @@ pub fn format_metric<'a>(
 #[derive(Clone)]
 pub enum DeviceLabel {
     Name(String),
+    Bus(String),
     Controller(String),
     Medium(String),
     Mac(String),
@@ -71,7 +72,7 @@ pub enum DeviceLabel {
     SubId(u8),
 }
 
-impl_display_for_enum!(DeviceLabel { Name(String), Controller(String), Medium(String), Mac(String), Class(String), SubId(String) });
+impl_display_for_enum!(DeviceLabel { Name(String), Bus(String), Controller(String), Medium(String), Mac(String), Class(String), SubId(String) });
 
 // prometheus library
 #[derive(Clone)]
@@ -83,6 +84,13 @@ pub enum SensorLabel {
 
 impl_display_for_enum!(SensorLabel { Controller(String), Install(String), Location(String) });
 
+#[derive(Clone)]
+pub enum BusLabel {
+    Bus(String),
+}
+
+impl_display_for_enum!(BusLabel { Bus(String) });
+
 #[cfg(test)]
 mod tests {
     use std::fmt::Display;
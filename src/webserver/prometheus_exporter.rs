@@ -25,6 +25,15 @@ pub async fn export(shared: &SharedHandle) -> String {
 
         let mut device_labels = vec![&medium_label, &mac_label, &class_label, &sub_id_label];
         let controller_label;
+        let bus_label;
+
+        // Same device ids may be used on different buses
+        if caniot_controller_stats.buses.len() > 1 {
+            if let Some(bus) = caniot_controller_stats.buses.get(device_infos.bus) {
+                bus_label = DeviceLabel::Bus(bus.name.clone());
+                device_labels.push(&bus_label);
+            }
+        }
 
         if let Some(controller_name) = device_infos.controller_name.as_ref() {
             controller_label = DeviceLabel::Controller(controller_name.clone());
@@ -52,37 +52,32 @@ function DeviceStatusCardContent({ device: resp }: IDeviceCardContentProps) {
   let tempBoard = resp.hasBoardTemp() ? resp.getBoardTemp().toFixed(2) : "N/A";
   let tempOut = resp.hasOutsideTemp() ? resp.getOutsideTemp().toFixed(2) : "N/A";
 
-  if (resp.hasClass0()) {
-    const c0 = resp.getClass0();
-
-    hasTempExt0 = c0?.hasExtTemp0() || false;
-    hasTempExt1 = c0?.hasExtTemp1() || false;
-    hasTempExt2 = c0?.hasExtTemp2() || false;
-
-    tempIn = c0?.hasIntTemp() ? c0.getIntTemp().toFixed(2) : "N/A";
-    tempExt0 = c0?.hasExtTemp0() ? c0.getExtTemp0()?.toFixed(2) : "N/A";
-    tempExt1 = c0?.hasExtTemp1() ? c0.getExtTemp1()?.toFixed(2) : "N/A";
-    tempExt2 = c0?.hasExtTemp2() ? c0.getExtTemp2()?.toFixed(2) : "N/A";
-  } else if (resp.hasClass1()) {
-    const c1 = resp.getClass1();
-
-    hasTempExt0 = c1?.hasExtTemp0() || false;
-    hasTempExt1 = c1?.hasExtTemp1() || false;
-    hasTempExt2 = c1?.hasExtTemp2() || false;
-
-    tempIn = c1?.hasIntTemp() ? c1.getIntTemp().toFixed(2) : "N/A";
-    tempExt0 = c1?.hasExtTemp0() ? c1.getExtTemp0()?.toFixed(2) : "N/A";
-    tempExt1 = c1?.hasExtTemp1() ? c1.getExtTemp1()?.toFixed(2) : "N/A";
-    tempExt2 = c1?.hasExtTemp2() ? c1.getExtTemp2()?.toFixed(2) : "N/A";
-
-    inputs = c1?.getIosList().map((io, index) => (
+  if (resp.hasIo()) {
+    const io = resp.getIo();
+    const temperatures = io?.getTemperaturesList() || [];
+    const temperature = (name: string) => temperatures.find((t) => t.getName() === name);
+    const formatTemperature = (name: string) => {
+      const sensor = temperature(name);
+      return sensor?.hasValue() ? sensor.getValue().toFixed(2) : "N/A";
+    };
+
+    hasTempExt0 = temperature("ext0")?.hasValue() || false;
+    hasTempExt1 = temperature("ext1")?.hasValue() || false;
+    hasTempExt2 = temperature("ext2")?.hasValue() || false;
+
+    tempIn = formatTemperature("board");
+    tempExt0 = formatTemperature("ext0");
+    tempExt1 = formatTemperature("ext1");
+    tempExt2 = formatTemperature("ext2");
+
+    inputs = io?.getIosList().map((state, index) => (
       <Space key={index} style={{ marginBottom: 8 }}>
         {" "}
-        <Badge status={io ? "success" : "error"} />
+        <Badge status={state.getState() ? "success" : "error"} text={state.getName()} />
       </Space>
     ));
 
-    ios_count = c1?.getIosList().length || 0;
+    ios_count = io?.getIosList().length || 0;
   }
 
   let stats = resp.getStats();
@@ -149,18 +144,8 @@ function DeviceStatusCardContent({ device: resp }: IDeviceCardContentProps) {
       {hasTempExt2 && (
         <ListLabelledItem label="Temp extérieure (sens 1)">{tempExt2} °C</ListLabelledItem>
       )}
-      {resp?.hasClass0() && (
-        <>
-          <ListLabelledItem label={"Entrées (4)"}>To Implement</ListLabelledItem>
-          <ListLabelledItem label={"Sorties (4)"}>To Implement</ListLabelledItem>
-        </>
-      )}
-      {resp?.hasClass1() && (
-        <>
-          <ListLabelledItem label={"Entrées/Sorties  (" + ios_count + ")"}>
-            {inputs}
-          </ListLabelledItem>
-        </>
+      {resp?.hasIo() && (
+        <ListLabelledItem label={"Entrées/Sorties  (" + ios_count + ")"}>{inputs}</ListLabelledItem>
       )}
       <ListLabelledItem label="Statistiques" labelAlignTop={true}>
         <Table
@@ -2,6 +2,7 @@ import { notification } from "antd";
 import EventEmitter from "events";
 import { HandleError, HandleSuccess, getApiUrl } from "./helpers";
 import {
+  CanAlert,
   ControllerStats,
   FirmwareInfos,
   HelloRequest,
@@ -128,6 +129,19 @@ class InternalStore extends EventEmitter {
     });
   };
 
+  getCanAlert = (callbackFunc: (resp: CanAlert) => void) => {
+    this.client.getCanAlert(new Empty(), null, (err, resp) => {
+      if (err !== null) {
+        HandleError(err);
+        return;
+      }
+
+      HandleSuccess("InternalStore::GetCanAlert succeeded");
+
+      callbackFunc(resp);
+    });
+  };
+
   getInfos = (callbackFunc: (resp: Infos) => void) => {
     this.client.getInfos(new Empty(), null, (err, resp) => {
       if (err !== null) {
@@ -11,7 +11,11 @@ import garageStore from "../store/GarageStore";
 import DeviceMetricsWidget from "../components/DeviceMetricsWidget";
 import DeviceAlert from "../components/DeviceAlert";
 import SoftwareInfosCard from "../components/SoftwareInfosCard";
-import { Infos, SoftwareInfos } from "@caniot-controller/caniot-api-grpc-web/api/ng_internal_pb";
+import {
+  CanAlert,
+  Infos,
+  SoftwareInfos,
+} from "@caniot-controller/caniot-api-grpc-web/api/ng_internal_pb";
 import internalStore from "../store/InternalStore";
 import FirmwareInfosCard from "../components/FirmwareInfosCard";
 import ControllerStatsCard from "../components/ControllerStatsCard";
@@ -58,6 +62,7 @@ function HomeView({ appContext, refreshInterval = 5000, uiHomeBLEDevices = false
   const [bleDevicesLoading, setBleDevicesLoading] = useState(true);
 
   const [coproAlert, setCoproAlert] = useState<CoproAlert | undefined>(undefined);
+  const [canAlert, setCanAlert] = useState<CanAlert | undefined>(undefined);
 
   const [time, setTime] = useState(Date.now());
 
@@ -118,6 +123,10 @@ function HomeView({ appContext, refreshInterval = 5000, uiHomeBLEDevices = false
       setCoproAlert(resp);
     });
 
+    internalStore.getCanAlert((resp: CanAlert) => {
+      setCanAlert(resp);
+    });
+
     const intervalRefresh = setInterval(() => setTime(Date.now()), refreshInterval);
     return () => {
       clearInterval(intervalRefresh);
@@ -187,8 +196,17 @@ function HomeView({ appContext, refreshInterval = 5000, uiHomeBLEDevices = false
     hasCoproAlertActive = appContext.uiDebugMode;
   }
 
+  // Healthy buses are only shown in debug mode
+  let hasCanAlertActive = false;
+  if (canAlert?.hasActiveAlert()) {
+    const canAlertType = canAlert.getActiveAlert()?.getAlertType();
+    hasCanAlertActive =
+      (canAlertType != DeviceAlertType.OK && canAlertType != DeviceAlertType.NOTIFICATION) ||
+      appContext.uiDebugMode;
+  }
+
   const hasDevicesAlertsActive = devicesWithAlert && devicesWithAlert.getDevicesList().length > 0;
-  const hasAlertsActive = hasCoproAlertActive || hasDevicesAlertsActive;
+  const hasAlertsActive = hasCoproAlertActive || hasCanAlertActive || hasDevicesAlertsActive;
 
   const devicesActiveAlerts = (
     <LoadableCard
@@ -208,6 +226,14 @@ function HomeView({ appContext, refreshInterval = 5000, uiHomeBLEDevices = false
               isMobile={appContext.isMobile}
             />
           )}
+          {hasCanAlertActive && (
+            <DeviceAlert
+              key="canAlert"
+              alert={canAlert?.getActiveAlert()}
+              closable={false}
+              isMobile={appContext.isMobile}
+            />
+          )}
           {hasDevicesAlertsActive &&
             devicesWithAlert
               .getDevicesList()